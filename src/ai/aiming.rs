@@ -0,0 +1,201 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use rapier2d::prelude::RigidBodyHandle;
+
+use crate::ai::{VisibleBot, WorldSnapshot};
+
+/// Represents how well the AI aims.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Slow to react and often misses.
+    Easy,
+    /// Reacts in a fraction of a second and hits most shots at short range.
+    #[default]
+    Medium,
+    /// Reacts quickly and rarely misses.
+    Hard,
+}
+
+impl Difficulty {
+    /// All the difficulty levels.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    /// Returns the name of the difficulty level.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Returns the largest error added to the aim, in radians on each side of the intercept angle.
+    pub fn aim_error(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Medium => 0.12,
+            Difficulty::Hard => 0.03,
+        }
+    }
+
+    /// Returns the time a target must stay in sight before the AI starts aiming at it.
+    pub fn reaction_delay(&self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_millis(700),
+            Difficulty::Medium => Duration::from_millis(350),
+            Difficulty::Hard => Duration::from_millis(150),
+        }
+    }
+}
+
+/// Computes the direction to fire in so a bullet meets a target moving in a straight line.
+///
+/// # Parameters
+/// - `origin`: The (x, y) position the bullet is fired from.
+/// - `target`: The (x, y) position of the target.
+/// - `target_velocity`: The (vx, vy) velocity of the target.
+/// - `bullet_speed`: The speed of the bullet.
+///
+/// # Returns
+/// The angle in radians to fire at, or the direct angle to the target if the bullet cannot catch it.
+pub fn intercept_angle(origin: (f32, f32), target: (f32, f32), target_velocity: (f32, f32), bullet_speed: f32) -> f64 {
+    let (rx, ry) = (target.0 - origin.0, target.1 - origin.1);
+    let (vx, vy) = target_velocity;
+
+    // Smallest positive time t with |r + v t| = bullet_speed * t
+    let a = vx * vx + vy * vy - bullet_speed * bullet_speed;
+    let b = 2.0 * (rx * vx + ry * vy);
+    let c = rx * rx + ry * ry;
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.0).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+                .into_iter()
+                .filter(|&time| time > 0.0)
+                .min_by(f32::total_cmp)
+        }
+    };
+
+    let (x, y) = match time {
+        Some(time) => (rx + vx * time, ry + vy * time),
+        None => (rx, ry),
+    };
+    y.atan2(x) as f64
+}
+
+/// Picks a target among the visible bots and aims at where it will be, with the error and reaction
+/// delay of a difficulty level.
+pub struct Aiming {
+    pub difficulty: Difficulty,
+    /// The handle of the current target and the time it came into sight.
+    target: Option<(RigidBodyHandle, Instant)>,
+    /// The error added to the aim until the next shot, in radians.
+    error: f64,
+    /// The ammunition seen at the previous step, to notice the shots fired.
+    last_ammo: Option<u32>,
+}
+
+impl Aiming {
+    /// Creates a new `Aiming` without any target.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the AI aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            target: None,
+            error: 0.0,
+            last_ammo: None,
+        }
+    }
+
+    /// Follows the closest visible bot, restarting the reaction delay whenever the target changes.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    ///
+    /// # Returns
+    /// The target, or `None` if no bot is in sight.
+    pub fn pick_target<'a>(&mut self, snapshot: &'a WorldSnapshot) -> Option<&'a VisibleBot> {
+        let Some(bot) = snapshot.closest_bot() else {
+            self.target = None;
+            return None;
+        };
+        if self.target.map_or(true, |(handle, _)| handle != bot.handle) {
+            self.target = Some((bot.handle, Instant::now()));
+            self.roll_error();
+        }
+        Some(bot)
+    }
+
+    /// Aims at the current target.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    /// - `tolerance`: The largest angle in radians between the gun and the aim at which the entity fires.
+    ///
+    /// # Returns
+    /// The orientation to turn the gun towards and whether to fire, or `None` while no target is in sight
+    /// or the reaction delay is not over.
+    pub fn aim(&mut self, snapshot: &WorldSnapshot, tolerance: f64) -> Option<(f64, bool)> {
+        // Each shot misses by a different amount
+        if self.last_ammo.is_some_and(|ammo| snapshot.ammo < ammo) {
+            self.roll_error();
+        }
+        self.last_ammo = Some(snapshot.ammo);
+
+        let bot = self.pick_target(snapshot)?;
+        let (_, seen_since) = self.target.as_ref()?;
+        if seen_since.elapsed() < self.difficulty.reaction_delay() {
+            return None;
+        }
+
+        let angle = intercept_angle(snapshot.position, bot.position, bot.velocity, snapshot.weapon.weapon().speed)
+            + self.error;
+        let offset = (angle - snapshot.gun_orientation + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU)
+            - std::f64::consts::PI;
+        Some((angle, offset.abs() <= tolerance))
+    }
+
+    /// Picks a new random error for the next shot.
+    fn roll_error(&mut self) {
+        let max_error = self.difficulty.aim_error();
+        self.error = rand::thread_rng().gen_range(-max_error..=max_error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn still_target_is_aimed_at_directly() {
+        let angle = intercept_angle((0.0, 0.0), (0.0, 100.0), (0.0, 0.0), 300.0);
+        assert!((angle - std::f64::consts::FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn moving_target_is_led() {
+        // The bullet travels twice as fast as the target, so it meets it 30 degrees ahead
+        let angle = intercept_angle((0.0, 0.0), (100.0, 0.0), (0.0, 50.0), 100.0);
+        assert!((angle - std::f64::consts::FRAC_PI_6).abs() < 1e-5);
+    }
+
+    #[test]
+    fn target_as_fast_as_the_bullet_is_met_when_it_comes_closer() {
+        let angle = intercept_angle((0.0, 0.0), (100.0, 0.0), (-60.0, 80.0), 100.0);
+        assert!((angle - (200.0f64 / 3.0).atan2(50.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn target_out_of_reach_is_aimed_at_directly() {
+        let angle = intercept_angle((0.0, 0.0), (100.0, 0.0), (150.0, 150.0), 100.0);
+        assert!(angle.abs() < 1e-5);
+    }
+}
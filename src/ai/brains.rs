@@ -0,0 +1,412 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::ai::aiming::{Aiming, Difficulty};
+use crate::ai::{BotActions, BotBrain, WorldSnapshot};
+use crate::app_defines::AppDefines;
+use crate::pickups::PickupKind;
+use crate::weapons::WeaponKind;
+
+/// Turns the gun turret towards the target of the aiming component, firing once the gun is on it.
+///
+/// # Parameters
+/// - `aiming`: The aiming component of the brain.
+/// - `snapshot`: What the entity knows about the world.
+/// - `actions`: The actions of the entity, receiving the aim and the decision to fire.
+/// - `tolerance`: The largest angle in radians between the gun and the aim at which the entity fires.
+///
+/// # Returns
+/// `true` if the entity aims at a target, `false` if it sees none or has not reacted yet.
+fn attack(aiming: &mut Aiming, snapshot: &WorldSnapshot, actions: &mut BotActions, tolerance: f64) -> bool {
+    match aiming.aim(snapshot, tolerance) {
+        Some((angle, fire)) => {
+            actions.aim = Some(angle);
+            actions.fire = fire;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Follows a path around the obstacles to a destination, planning it again as the destination moves.
+struct PathFollowing {
+    destination: Option<(f32, f32)>,
+    /// The waypoints left to reach the destination.
+    path: Vec<(f32, f32)>,
+    next_plan: Instant,
+}
+
+impl PathFollowing {
+    /// Creates a new `PathFollowing` without any destination.
+    fn new() -> Self {
+        Self {
+            destination: None,
+            path: Vec::new(),
+            next_plan: Instant::now(),
+        }
+    }
+
+    /// Moves along the path to a destination.
+    ///
+    /// The path is planned again every few moments in case the entity was pushed off it, and whenever the
+    /// destination moves. The entity heads straight to the destination if no path leads there.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    /// - `destination`: The (x, y) position to reach.
+    ///
+    /// # Returns
+    /// The actions moving the entity to the next waypoint.
+    fn follow(&mut self, snapshot: &WorldSnapshot, destination: (f32, f32)) -> BotActions {
+        let moved = self.destination.map_or(true, |previous| {
+            (destination.0 - previous.0).hypot(destination.1 - previous.1) > AppDefines::AI_REPLAN_DISTANCE
+        });
+        if moved || Instant::now() >= self.next_plan {
+            self.destination = Some(destination);
+            self.path = snapshot.nav_grid.find_path(snapshot.position, destination).unwrap_or_else(|| vec![destination]);
+            self.next_plan = Instant::now() + Duration::from_secs_f32(AppDefines::AI_REPLAN_INTERVAL);
+        }
+        if let Some(last) = self.path.last_mut() {
+            *last = destination;
+        }
+
+        let reached = |waypoint: &(f32, f32)| {
+            (waypoint.0 - snapshot.position.0).hypot(waypoint.1 - snapshot.position.1) < AppDefines::AI_WAYPOINT_RADIUS
+        };
+        while self.path.len() > 1 && reached(&self.path[0]) {
+            self.path.remove(0);
+        }
+
+        match self.path.as_slice() {
+            [waypoint] => BotActions::move_to(snapshot, *waypoint),
+            [waypoint, ..] => BotActions::move_through(snapshot, *waypoint),
+            [] => BotActions::move_to(snapshot, destination),
+        }
+    }
+}
+
+/// Walks to random positions, looking for pickups on the way.
+struct Wandering {
+    target: Option<(f32, f32)>,
+    next_change: Instant,
+    path_following: PathFollowing,
+}
+
+impl Wandering {
+    /// Creates a new `Wandering`, picking its first destination on its first step.
+    fn new() -> Self {
+        Self {
+            target: None,
+            next_change: Instant::now(),
+            path_following: PathFollowing::new(),
+        }
+    }
+
+    /// Moves to the closest visible pickup, or to the current destination, picking a new one every few seconds.
+    ///
+    /// Health pickups are preferred while the entity is hurt.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    ///
+    /// # Returns
+    /// The actions moving the entity, with a new random aim whenever the destination changes.
+    fn wander(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let hurt = snapshot.health < AppDefines::ENTITY_MAX_HEALTH / 2.0;
+        let distance = |position: (f32, f32)| (position.0 - snapshot.position.0).hypot(position.1 - snapshot.position.1);
+        let pickup = snapshot.pickups.iter()
+            .min_by(|(kind_a, a), (kind_b, b)| {
+                let priority = |kind: &PickupKind| !(hurt && *kind == PickupKind::Health);
+                priority(kind_a).cmp(&priority(kind_b)).then(distance(*a).total_cmp(&distance(*b)))
+            });
+        if let Some(&(_, position)) = pickup {
+            return self.path_following.follow(snapshot, position);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut aim = None;
+        if self.target.is_none() || Instant::now() >= self.next_change {
+            // Pick a destination an entity can stand on, if one is found within a few tries
+            let (arena_width, arena_height) = snapshot.arena_size;
+            let mut target = snapshot.position;
+            for _ in 0..10 {
+                target = (rng.gen_range(10.0..arena_width - 10.0), rng.gen_range(10.0..arena_height - 10.0));
+                if snapshot.nav_grid.is_free(target) {
+                    break;
+                }
+            }
+            self.target = Some(target);
+            self.next_change = Instant::now() + Duration::from_secs_f32(rng.gen_range(1.0..3.0));
+            aim = Some(rng.gen_range(0.0..std::f64::consts::TAU));
+        }
+
+        let mut actions = self.path_following.follow(snapshot, self.target.unwrap_or(snapshot.position));
+        actions.aim = aim;
+        actions
+    }
+}
+
+/// Walks to random positions, firing in random directions until it notices a bot to shoot at.
+pub struct Wanderer {
+    wandering: Wandering,
+    aiming: Aiming,
+}
+
+impl Wanderer {
+    /// Creates a new `Wanderer`.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { wandering: Wandering::new(), aiming: Aiming::new(difficulty) }
+    }
+}
+
+impl BotBrain for Wanderer {
+    fn name(&self) -> &str {
+        "Wanderer"
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let mut actions = self.wandering.wander(snapshot);
+        if !attack(&mut self.aiming, snapshot, &mut actions, AppDefines::AI_AIM_TOLERANCE) {
+            actions.fire = true;
+        }
+        actions.reload_when_empty(snapshot)
+    }
+
+    fn aiming(&self) -> Option<&Aiming> {
+        Some(&self.aiming)
+    }
+
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        Some(&mut self.aiming)
+    }
+}
+
+/// Runs after the closest bot and fires at it, wandering around while it sees nobody.
+pub struct Chaser {
+    wandering: Wandering,
+    path_following: PathFollowing,
+    aiming: Aiming,
+}
+
+impl Chaser {
+    /// Creates a new `Chaser`.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { wandering: Wandering::new(), path_following: PathFollowing::new(), aiming: Aiming::new(difficulty) }
+    }
+}
+
+impl BotBrain for Chaser {
+    fn name(&self) -> &str {
+        "Chaser"
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let Some(bot) = self.aiming.pick_target(snapshot) else {
+            return self.wandering.wander(snapshot).reload_when_empty(snapshot);
+        };
+
+        // Keep a short distance instead of ramming the target
+        let mut actions = self.path_following.follow(snapshot, bot.position);
+        if bot.distance < AppDefines::AI_CHASE_DISTANCE {
+            actions.speed = Some(0.0);
+        }
+        attack(&mut self.aiming, snapshot, &mut actions, AppDefines::AI_AIM_TOLERANCE);
+        actions.reload_when_empty(snapshot)
+    }
+
+    fn aiming(&self) -> Option<&Aiming> {
+        Some(&self.aiming)
+    }
+
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        Some(&mut self.aiming)
+    }
+}
+
+/// Keeps its distance with a rifle and only fires when its aim is steady.
+pub struct Sniper {
+    aiming: Aiming,
+}
+
+impl Sniper {
+    /// Creates a new `Sniper`.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { aiming: Aiming::new(difficulty) }
+    }
+}
+
+impl BotBrain for Sniper {
+    fn name(&self) -> &str {
+        "Sniper"
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let mut actions = match snapshot.closest_bot() {
+            // Back away from bots getting too close
+            Some(bot) if bot.distance < AppDefines::AI_SNIPER_DISTANCE => {
+                let (arena_width, arena_height) = snapshot.arena_size;
+                let away = (snapshot.position.0 - bot.position.0, snapshot.position.1 - bot.position.1);
+                let scale = AppDefines::AI_SNIPER_DISTANCE / bot.distance.max(1.0);
+                let retreat = (
+                    (snapshot.position.0 + away.0 * scale).clamp(10.0, arena_width - 10.0),
+                    (snapshot.position.1 + away.1 * scale).clamp(10.0, arena_height - 10.0),
+                );
+                BotActions::move_to(snapshot, retreat)
+            }
+            _ => BotActions { speed: Some(0.0), ..BotActions::default() },
+        };
+
+        // Sweep the arena with the gun while nobody is in sight
+        if !attack(&mut self.aiming, snapshot, &mut actions, AppDefines::AI_AIM_TOLERANCE / 4.0) && snapshot.bots.is_empty() {
+            actions.aim = Some(snapshot.gun_orientation + AppDefines::AI_SCAN_STEP);
+        }
+        if snapshot.weapon != WeaponKind::Rifle {
+            actions.weapon = Some(WeaponKind::Rifle);
+        }
+        actions.reload_when_empty(snapshot)
+    }
+
+    fn aiming(&self) -> Option<&Aiming> {
+        Some(&self.aiming)
+    }
+
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        Some(&mut self.aiming)
+    }
+}
+
+/// Steps aside from incoming bullets while firing back, wandering around otherwise.
+pub struct Dodger {
+    wandering: Wandering,
+    aiming: Aiming,
+}
+
+impl Dodger {
+    /// Creates a new `Dodger`.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { wandering: Wandering::new(), aiming: Aiming::new(difficulty) }
+    }
+
+    /// Finds the position to move to in order to avoid the most urgent incoming bullet.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    ///
+    /// # Returns
+    /// The (x, y) position out of the path of the bullet, or `None` if no bullet is about to hit the entity.
+    fn escape(snapshot: &WorldSnapshot) -> Option<(f32, f32)> {
+        let (x, y) = snapshot.position;
+        snapshot.bullets.iter()
+            .filter_map(|bullet| {
+                let (vx, vy) = bullet.velocity;
+                let speed_squared = vx * vx + vy * vy;
+                if speed_squared == 0.0 {
+                    return None;
+                }
+                // Time and position of the closest approach of the bullet to the entity
+                let (rx, ry) = (x - bullet.position.0, y - bullet.position.1);
+                let time = (rx * vx + ry * vy) / speed_squared;
+                let (mx, my) = (rx - vx * time, ry - vy * time);
+                let threatening = time > 0.0 && time < AppDefines::AI_DODGE_HORIZON
+                    && mx.hypot(my) < AppDefines::AI_DODGE_RADIUS;
+                threatening.then_some((time, (mx, my), (vx, vy)))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, (mx, my), (vx, vy))| {
+                // Step perpendicularly to the bullet, on the side the entity already leans to
+                let speed = vx.hypot(vy);
+                let (mut px, mut py) = (-vy / speed, vx / speed);
+                if px * mx + py * my < 0.0 {
+                    (px, py) = (-px, -py);
+                }
+                let (arena_width, arena_height) = snapshot.arena_size;
+                (
+                    (x + px * AppDefines::AI_DODGE_DISTANCE).clamp(10.0, arena_width - 10.0),
+                    (y + py * AppDefines::AI_DODGE_DISTANCE).clamp(10.0, arena_height - 10.0),
+                )
+            })
+    }
+}
+
+impl BotBrain for Dodger {
+    fn name(&self) -> &str {
+        "Dodger"
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let mut actions = match Self::escape(snapshot) {
+            Some(escape) => BotActions::move_to(snapshot, escape),
+            None => self.wandering.wander(snapshot),
+        };
+        attack(&mut self.aiming, snapshot, &mut actions, AppDefines::AI_AIM_TOLERANCE);
+        actions.reload_when_empty(snapshot)
+    }
+
+    fn aiming(&self) -> Option<&Aiming> {
+        Some(&self.aiming)
+    }
+
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        Some(&mut self.aiming)
+    }
+}
+
+/// Holds the closest corner of the arena and fires at whoever comes into view.
+pub struct Camper {
+    spot: Option<(f32, f32)>,
+    path_following: PathFollowing,
+    aiming: Aiming,
+}
+
+impl Camper {
+    /// Creates a new `Camper`, choosing its corner on its first step.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self { spot: None, path_following: PathFollowing::new(), aiming: Aiming::new(difficulty) }
+    }
+}
+
+impl BotBrain for Camper {
+    fn name(&self) -> &str {
+        "Camper"
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        let spot = *self.spot.get_or_insert_with(|| {
+            let (arena_width, arena_height) = snapshot.arena_size;
+            let inset = AppDefines::AI_CAMP_INSET;
+            let x = if snapshot.position.0 < arena_width / 2.0 { inset } else { arena_width - inset };
+            let y = if snapshot.position.1 < arena_height / 2.0 { inset } else { arena_height - inset };
+            (x, y)
+        });
+
+        let mut actions = self.path_following.follow(snapshot, spot);
+        if !attack(&mut self.aiming, snapshot, &mut actions, AppDefines::AI_AIM_TOLERANCE) && snapshot.bots.is_empty() {
+            actions.aim = Some(snapshot.gun_orientation + AppDefines::AI_SCAN_STEP);
+        }
+        actions.reload_when_empty(snapshot)
+    }
+
+    fn aiming(&self) -> Option<&Aiming> {
+        Some(&self.aiming)
+    }
+
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        Some(&mut self.aiming)
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::Arc;
+
+use rapier2d::prelude::RigidBodyHandle;
+
+use crate::ai::aiming::{Aiming, Difficulty};
+use crate::game_logic::VisibleBullet;
+use crate::navigation::NavGrid;
+use crate::pickups::PickupKind;
+use crate::weapons::WeaponKind;
+
+pub mod aiming;
+pub mod brains;
+pub mod script;
+
+/// Represents another bot seen by an AI entity.
+#[derive(Clone)]
+pub struct VisibleBot {
+    /// The handle of the body of the bot, telling bots with the same name apart.
+    pub handle: RigidBodyHandle,
+    pub name: String,
+    /// The (x, y) position of the bot.
+    pub position: (f32, f32),
+    /// The (vx, vy) velocity of the bot.
+    pub velocity: (f32, f32),
+    /// The distance between the bot and the AI entity.
+    pub distance: f32,
+}
+
+/// Represents what an AI entity knows about the world when it decides what to do.
+///
+/// The snapshot only holds what a network bot could learn through its queries, so the built-in brains
+/// play by the same rules, fog of war included.
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    /// The (x, y) position of the entity.
+    pub position: (f32, f32),
+    /// The heading of the body, in radians.
+    pub heading: f64,
+    /// The orientation of the gun turret, in radians.
+    pub gun_orientation: f64,
+    pub health: f32,
+    pub weapon: WeaponKind,
+    /// The ammunition left in the weapon.
+    pub ammo: u32,
+    /// Whether the weapon is being reloaded.
+    pub reloading: bool,
+    /// The maximum speed of the entity, including its speed boost.
+    pub max_speed: f32,
+    /// The width and height of the arena.
+    pub arena_size: (f32, f32),
+    /// The walkable cells of the arena, to plan paths around the obstacles as with `PATH`.
+    pub nav_grid: Arc<NavGrid>,
+    /// The other living bots the entity can see.
+    pub bots: Vec<VisibleBot>,
+    /// The bullets fired by the other bots that the entity can see.
+    pub bullets: Vec<VisibleBullet>,
+    /// The kind and (x, y) position of the pickups the entity can see.
+    pub pickups: Vec<(PickupKind, (f32, f32))>,
+}
+
+impl WorldSnapshot {
+    /// Finds the closest bot the entity can see.
+    ///
+    /// # Returns
+    /// The closest visible bot, or `None` if the entity sees no bot.
+    pub fn closest_bot(&self) -> Option<&VisibleBot> {
+        self.bots.iter().min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Computes the direction from the entity to a position.
+    ///
+    /// # Parameters
+    /// - `target`: The (x, y) position looked at.
+    ///
+    /// # Returns
+    /// The angle in radians of the direction to the position.
+    pub fn angle_to(&self, target: (f32, f32)) -> f64 {
+        (target.1 - self.position.1).atan2(target.0 - self.position.0) as f64
+    }
+}
+
+/// Represents the decisions of an AI entity for one step, the same actions a network bot can send.
+#[derive(Clone, Default)]
+pub struct BotActions {
+    /// The heading to turn the body towards, as with `TURN`.
+    pub turn: Option<f64>,
+    /// The speed to accelerate to along the heading, as with `MOVE`.
+    pub speed: Option<f32>,
+    /// The orientation to turn the gun turret towards, as with `AIM`.
+    pub aim: Option<f64>,
+    /// Whether to fire, as with `FIRE`.
+    pub fire: bool,
+    /// The weapon to switch to, as with `WEAPON`.
+    pub weapon: Option<WeaponKind>,
+    /// Whether to reload the weapon, as with `RELOAD`.
+    pub reload: bool,
+}
+
+impl BotActions {
+    /// Reloads the weapon instead of firing once it is empty.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    ///
+    /// # Returns
+    /// The actions, reloading the weapon if it has no ammunition left.
+    pub fn reload_when_empty(mut self, snapshot: &WorldSnapshot) -> Self {
+        if snapshot.ammo == 0 && !snapshot.reloading {
+            self.fire = false;
+            self.reload = true;
+        }
+        self
+    }
+
+    /// Builds the actions moving the entity towards a position.
+    ///
+    /// The entity slows down while its heading points away from the position and when it gets close to it.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    /// - `target`: The (x, y) position to reach.
+    ///
+    /// # Returns
+    /// The actions turning and moving the entity.
+    pub fn move_to(snapshot: &WorldSnapshot, target: (f32, f32)) -> Self {
+        let distance = (target.0 - snapshot.position.0).hypot(target.1 - snapshot.position.1);
+        if distance <= 1.0 {
+            return Self { speed: Some(0.0), ..Self::default() };
+        }
+        Self::move_towards(snapshot, target, snapshot.max_speed.min(distance))
+    }
+
+    /// Builds the actions moving the entity through a position without stopping there, such as a waypoint.
+    ///
+    /// The entity slows down while its heading points away from the position.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    /// - `target`: The (x, y) position to move through.
+    ///
+    /// # Returns
+    /// The actions turning and moving the entity.
+    pub fn move_through(snapshot: &WorldSnapshot, target: (f32, f32)) -> Self {
+        Self::move_towards(snapshot, target, snapshot.max_speed)
+    }
+
+    /// Builds the actions turning the entity towards a position and moving it at up to a given speed.
+    fn move_towards(snapshot: &WorldSnapshot, target: (f32, f32), speed: f32) -> Self {
+        let heading = snapshot.angle_to(target);
+        let offset = (heading - snapshot.heading) as f32;
+        Self {
+            turn: Some(heading),
+            speed: Some(speed * offset.cos().max(0.0)),
+            ..Self::default()
+        }
+    }
+}
+
+/// Represents the behaviour of an AI entity.
+///
+/// At every step, the brain observes a snapshot of the world and returns the actions of its entity.
+pub trait BotBrain: Send {
+    /// Returns the name of the brain, as shown in the game UI.
+    fn name(&self) -> &str;
+
+    /// Decides what the entity does during this step.
+    ///
+    /// # Parameters
+    /// - `snapshot`: What the entity knows about the world.
+    ///
+    /// # Returns
+    /// The actions of the entity.
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions;
+
+    /// Returns the aiming component of the brain.
+    ///
+    /// # Returns
+    /// The aiming component, or `None` if the brain has no configurable aiming.
+    fn aiming(&self) -> Option<&Aiming> {
+        None
+    }
+
+    /// Returns the aiming component of the brain, to change it.
+    ///
+    /// # Returns
+    /// The aiming component, or `None` if the brain has no configurable aiming.
+    fn aiming_mut(&mut self) -> Option<&mut Aiming> {
+        None
+    }
+
+    /// Returns how well the brain aims.
+    ///
+    /// # Returns
+    /// The difficulty level, or `None` if the brain has no configurable aiming.
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.aiming().map(|aiming| aiming.difficulty)
+    }
+
+    /// Changes how well the brain aims, if its aiming is configurable.
+    ///
+    /// # Parameters
+    /// - `difficulty`: The new difficulty level.
+    fn set_difficulty(&mut self, difficulty: Difficulty) {
+        if let Some(aiming) = self.aiming_mut() {
+            aiming.difficulty = difficulty;
+        }
+    }
+}
+
+/// Represents the built-in brains AI entities can be given.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BrainKind {
+    /// Walks to random positions, firing at the bots it comes across.
+    #[default]
+    Wanderer,
+    /// Runs after the closest bot and fires at it.
+    Chaser,
+    /// Keeps its distance with a rifle and only fires when its aim is steady.
+    Sniper,
+    /// Steps aside from incoming bullets while firing back.
+    Dodger,
+    /// Holds a corner of the arena and fires at whoever comes into view.
+    Camper,
+}
+
+impl BrainKind {
+    /// All the built-in brains.
+    pub const ALL: [BrainKind; 5] = [
+        BrainKind::Wanderer,
+        BrainKind::Chaser,
+        BrainKind::Sniper,
+        BrainKind::Dodger,
+        BrainKind::Camper,
+    ];
+
+    /// Returns the name of the brain.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrainKind::Wanderer => "Wanderer",
+            BrainKind::Chaser => "Chaser",
+            BrainKind::Sniper => "Sniper",
+            BrainKind::Dodger => "Dodger",
+            BrainKind::Camper => "Camper",
+        }
+    }
+
+    /// Creates a new brain of this kind.
+    ///
+    /// # Parameters
+    /// - `difficulty`: How well the brain aims.
+    ///
+    /// # Returns
+    /// The brain, ready to be given to an AI entity.
+    pub fn create(&self, difficulty: Difficulty) -> Box<dyn BotBrain> {
+        match self {
+            BrainKind::Wanderer => Box::new(brains::Wanderer::new(difficulty)),
+            BrainKind::Chaser => Box::new(brains::Chaser::new(difficulty)),
+            BrainKind::Sniper => Box::new(brains::Sniper::new(difficulty)),
+            BrainKind::Dodger => Box::new(brains::Dodger::new(difficulty)),
+            BrainKind::Camper => Box::new(brains::Camper::new(difficulty)),
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Position, Scope, AST};
+
+use crate::ai::{BotActions, BotBrain, VisibleBot, WorldSnapshot};
+use crate::app_defines::AppDefines;
+use crate::types::{add_message, MessageType, StyledMessage};
+use crate::weapons::WeaponKind;
+
+/// Represents the entity of a script, bound to `this` in its `think` function.
+///
+/// Its methods mirror the commands of the network protocol, so a strategy can be prototyped as a script
+/// then ported to a network bot.
+#[derive(Clone)]
+struct ScriptBot {
+    snapshot: Arc<WorldSnapshot>,
+    actions: BotActions,
+    /// The values the script keeps from one step to the next.
+    memory: Map,
+    /// The number of paths the script may still plan during this step.
+    paths_left: u32,
+}
+
+impl ScriptBot {
+    /// Builds the map describing a position.
+    fn point(position: (f32, f32)) -> Map {
+        let mut map = Map::new();
+        map.insert("x".into(), Dynamic::from_float(position.0 as f64));
+        map.insert("y".into(), Dynamic::from_float(position.1 as f64));
+        map
+    }
+
+    /// Builds the map describing a visible bot, with its name, position, velocity and distance.
+    fn bot(bot: &VisibleBot) -> Dynamic {
+        let mut map = Self::point(bot.position);
+        map.insert("name".into(), bot.name.clone().into());
+        map.insert("vx".into(), Dynamic::from_float(bot.velocity.0 as f64));
+        map.insert("vy".into(), Dynamic::from_float(bot.velocity.1 as f64));
+        map.insert("dist".into(), Dynamic::from_float(bot.distance as f64));
+        map.into()
+    }
+
+    /// Lists the visible bots, as with `NLIST` and `NBOT`.
+    fn bots(&mut self) -> Array {
+        self.snapshot.bots.iter().map(Self::bot).collect()
+    }
+
+    /// Finds the closest visible bot, or `()` if no bot is in sight, as with `CBOT`.
+    fn closest_bot(&mut self) -> Dynamic {
+        self.snapshot.closest_bot().map_or(Dynamic::UNIT, Self::bot)
+    }
+
+    /// Lists the visible bullets fired by the other bots as maps with their position, velocity and distance.
+    fn bullets(&mut self) -> Array {
+        self.snapshot.bullets.iter()
+            .map(|bullet| {
+                let mut map = Self::point(bullet.position);
+                map.insert("vx".into(), Dynamic::from_float(bullet.velocity.0 as f64));
+                map.insert("vy".into(), Dynamic::from_float(bullet.velocity.1 as f64));
+                map.insert("dist".into(), Dynamic::from_float(bullet.distance as f64));
+                map.into()
+            })
+            .collect()
+    }
+
+    /// Lists the visible pickups as maps with their kind and position, as with `PICK`.
+    fn pickups(&mut self) -> Array {
+        self.snapshot.pickups.iter()
+            .map(|(kind, position)| {
+                let mut map = Self::point(*position);
+                map.insert("kind".into(), kind.name().into());
+                map.into()
+            })
+            .collect()
+    }
+
+    /// Plans a path around the obstacles as a list of waypoints, empty if the position cannot be reached,
+    /// as with `PATH`.
+    ///
+    /// Only `AppDefines::SCRIPT_MAX_PATHS` paths may be planned in one step, the step failing past that.
+    fn path(&mut self, x: f64, y: f64) -> Result<Array, Box<EvalAltResult>> {
+        if self.paths_left == 0 {
+            return Err(EvalAltResult::ErrorRuntime(
+                format!("path() may only be called {} times per step", AppDefines::SCRIPT_MAX_PATHS).into(),
+                Position::NONE,
+            ).into());
+        }
+        self.paths_left -= 1;
+
+        Ok(self.snapshot.nav_grid.find_path(self.snapshot.position, (x as f32, y as f32))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|waypoint| Self::point(waypoint).into())
+            .collect())
+    }
+}
+
+/// Runs a bot script written in Rhai, reloading it whenever its file changes.
+///
+/// The script defines a `think()` function called at every step, in which `this` is the entity:
+/// - Properties: `x`, `y`, `heading`, `gun`, `health`, `weapon`, `ammo`, `reloading`, `max_speed`,
+///   `arena_width`, `arena_height`, and `memory`, a map kept from one step to the next.
+/// - Queries: `bots()`, `closest_bot()`, `bullets()`, `pickups()`, `path(x, y)` and `angle_to(x, y)`.
+/// - Actions: `move(speed)`, `turn(angle)`, `aim(angle)`, `fire()`, `weapon(name)`, `reload()`
+///   and `move_to(x, y)`.
+///
+/// Each call may only run a limited number of operations and plan a few paths, so a script stuck in a loop
+/// loses its step instead of freezing the game. Reloads and errors are reported in the server messages.
+pub struct ScriptBrain {
+    name: String,
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    /// The last modification time of the file, to notice when it changes.
+    modified: Option<SystemTime>,
+    next_check: Instant,
+    memory: Map,
+    /// The last error of the script, to only report each error once.
+    last_error: Option<String>,
+    /// A thread-safe, shared vector of styled messages, receiving the reloads and errors of the script.
+    messages: Arc<Mutex<Vec<StyledMessage>>>,
+}
+
+impl ScriptBrain {
+    /// Loads a bot script.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the script file.
+    /// - `messages`: The thread-safe, shared vector of styled messages receiving the reloads and errors of the script.
+    ///
+    /// # Returns
+    /// The brain running the script, or an error message if the file cannot be read or compiled.
+    pub fn load(path: &Path, messages: Arc<Mutex<Vec<StyledMessage>>>) -> Result<Self, String> {
+        let engine = Self::engine();
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        let ast = Self::compile(&engine, path)?;
+
+        Ok(Self {
+            name: path.file_stem().map_or_else(|| "Script".to_string(), |stem| stem.to_string_lossy().into_owned()),
+            path: path.to_path_buf(),
+            engine,
+            ast,
+            modified,
+            next_check: Instant::now() + Duration::from_secs_f32(AppDefines::SCRIPT_RELOAD_INTERVAL),
+            memory: Map::new(),
+            last_error: None,
+            messages,
+        })
+    }
+
+    /// Creates the sandboxed engine running the scripts, with the API of the entity registered.
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(AppDefines::SCRIPT_MAX_OPERATIONS);
+        engine.set_max_call_levels(AppDefines::SCRIPT_MAX_CALL_LEVELS);
+        engine.set_max_string_size(AppDefines::SCRIPT_MAX_STRING_SIZE);
+        engine.set_max_array_size(AppDefines::SCRIPT_MAX_COLLECTION_SIZE);
+        engine.set_max_map_size(AppDefines::SCRIPT_MAX_COLLECTION_SIZE);
+        engine.on_print(|_| {});
+        engine.on_debug(|_, _, _| {});
+
+        engine.register_type_with_name::<ScriptBot>("Bot")
+            .register_get("x", |bot: &mut ScriptBot| bot.snapshot.position.0 as f64)
+            .register_get("y", |bot: &mut ScriptBot| bot.snapshot.position.1 as f64)
+            .register_get("heading", |bot: &mut ScriptBot| bot.snapshot.heading)
+            .register_get("gun", |bot: &mut ScriptBot| bot.snapshot.gun_orientation)
+            .register_get("health", |bot: &mut ScriptBot| bot.snapshot.health as f64)
+            .register_get("weapon", |bot: &mut ScriptBot| bot.snapshot.weapon.name().to_string())
+            .register_get("ammo", |bot: &mut ScriptBot| bot.snapshot.ammo as i64)
+            .register_get("reloading", |bot: &mut ScriptBot| bot.snapshot.reloading)
+            .register_get("max_speed", |bot: &mut ScriptBot| bot.snapshot.max_speed as f64)
+            .register_get("arena_width", |bot: &mut ScriptBot| bot.snapshot.arena_size.0 as f64)
+            .register_get("arena_height", |bot: &mut ScriptBot| bot.snapshot.arena_size.1 as f64)
+            .register_get_set(
+                "memory",
+                |bot: &mut ScriptBot| bot.memory.clone(),
+                |bot: &mut ScriptBot, memory: Map| bot.memory = memory,
+            )
+            .register_fn("bots", ScriptBot::bots)
+            .register_fn("closest_bot", ScriptBot::closest_bot)
+            .register_fn("bullets", ScriptBot::bullets)
+            .register_fn("pickups", ScriptBot::pickups)
+            .register_fn("path", ScriptBot::path)
+            .register_fn("angle_to", |bot: &mut ScriptBot, x: f64, y: f64| bot.snapshot.angle_to((x as f32, y as f32)))
+            .register_fn("move", |bot: &mut ScriptBot, speed: f64| bot.actions.speed = Some(speed as f32))
+            .register_fn("turn", |bot: &mut ScriptBot, angle: f64| bot.actions.turn = Some(angle))
+            .register_fn("aim", |bot: &mut ScriptBot, angle: f64| bot.actions.aim = Some(angle))
+            .register_fn("fire", |bot: &mut ScriptBot| bot.actions.fire = true)
+            .register_fn("weapon", |bot: &mut ScriptBot, name: &str| bot.actions.weapon = WeaponKind::from_name(name))
+            .register_fn("reload", |bot: &mut ScriptBot| bot.actions.reload = true)
+            .register_fn("move_to", |bot: &mut ScriptBot, x: f64, y: f64| {
+                let movement = BotActions::move_to(&bot.snapshot, (x as f32, y as f32));
+                bot.actions.turn = movement.turn;
+                bot.actions.speed = movement.speed;
+            });
+        engine
+    }
+
+    /// Reads and compiles a script file.
+    fn compile(engine: &Engine, path: &Path) -> Result<AST, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        engine.compile(source).map_err(|e| format!("Failed to compile {}: {}", path.display(), e))
+    }
+
+    /// Compiles the script again if its file changed since the last check.
+    ///
+    /// A script which no longer compiles keeps running its previous version.
+    fn reload_if_changed(&mut self) {
+        if Instant::now() < self.next_check {
+            return;
+        }
+        self.next_check = Instant::now() + Duration::from_secs_f32(AppDefines::SCRIPT_RELOAD_INTERVAL);
+
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+        if modified.is_none() || modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        match Self::compile(&self.engine, &self.path) {
+            Ok(ast) => {
+                self.ast = ast;
+                self.last_error = None;
+                add_message(
+                    &self.messages,
+                    format!("[INFO] Reloaded bot script {}", self.path.display()),
+                    MessageType::Info,
+                );
+            }
+            Err(e) => self.report(e),
+        }
+    }
+
+    /// Reports an error of the script in the server messages, unless it is the same as the previous one.
+    fn report(&mut self, error: String) {
+        if self.last_error.as_ref() != Some(&error) {
+            add_message(&self.messages, format!("[ERROR] {}", error), MessageType::Error);
+            self.last_error = Some(error);
+        }
+    }
+}
+
+impl BotBrain for ScriptBrain {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn think(&mut self, snapshot: &WorldSnapshot) -> BotActions {
+        self.reload_if_changed();
+
+        let mut bot = Dynamic::from(ScriptBot {
+            snapshot: Arc::new(snapshot.clone()),
+            actions: BotActions::default(),
+            memory: std::mem::take(&mut self.memory),
+            paths_left: AppDefines::SCRIPT_MAX_PATHS,
+        });
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut bot);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, "think", ());
+
+        // A script may have bound `this` to another value, losing the entity and its memory
+        let Some(bot) = bot.try_cast::<ScriptBot>() else {
+            self.report(format!("Bot script {} replaced `this`", self.path.display()));
+            return BotActions::default();
+        };
+        self.memory = bot.memory;
+        match result {
+            Ok(_) => bot.actions,
+            Err(e) => {
+                self.report(format!("Bot script {} failed: {}", self.path.display(), e));
+                BotActions::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_logic::GameLogic;
+    use crate::server::server_thread::ServerSettings;
+
+    /// Loads a script written to a temporary file.
+    fn load_script(name: &str, source: &str, messages: &Arc<Mutex<Vec<StyledMessage>>>) -> ScriptBrain {
+        let path = std::env::temp_dir().join(format!("urss_test_{}_{}.rhai", name, std::process::id()));
+        fs::write(&path, source).unwrap();
+        ScriptBrain::load(&path, Arc::clone(messages)).unwrap()
+    }
+
+    /// Builds the snapshot of an entity standing alone in the middle of an empty arena.
+    fn snapshot() -> WorldSnapshot {
+        let game_logic = GameLogic::new(Arc::new(Mutex::new(ServerSettings::new())));
+        WorldSnapshot {
+            position: (game_logic.arena_width / 2.0, game_logic.arena_height / 2.0),
+            heading: 0.0,
+            gun_orientation: 0.0,
+            health: AppDefines::ENTITY_MAX_HEALTH,
+            weapon: WeaponKind::Pistol,
+            ammo: WeaponKind::Pistol.weapon().magazine,
+            reloading: false,
+            max_speed: 100.0,
+            arena_size: (game_logic.arena_width, game_logic.arena_height),
+            nav_grid: Arc::clone(&game_logic.nav_grid),
+            bots: Vec::new(),
+            bullets: Vec::new(),
+            pickups: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn script_replacing_this_is_reported_instead_of_panicking() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let mut brain = load_script("this", "fn think() { this = 42; }", &messages);
+
+        let actions = brain.think(&snapshot());
+        assert!(!actions.fire);
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].text.contains("replaced `this`"));
+    }
+
+    #[test]
+    fn path_planning_is_limited_per_step() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let source = format!(
+            "fn think() {{ for i in 0..{} {{ this.path(this.x + 50.0, this.y); }} this.fire(); }}",
+            AppDefines::SCRIPT_MAX_PATHS,
+        );
+        let mut brain = load_script("paths", &source, &messages);
+        assert!(brain.think(&snapshot()).fire);
+        assert!(messages.lock().unwrap().is_empty());
+
+        let source = format!(
+            "fn think() {{ for i in 0..{} {{ this.path(this.x + 50.0, this.y); }} this.fire(); }}",
+            AppDefines::SCRIPT_MAX_PATHS + 1,
+        );
+        let mut brain = load_script("too_many_paths", &source, &messages);
+        assert!(!brain.think(&snapshot()).fire);
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].text.contains("path()"));
+    }
+}
@@ -3,65 +3,186 @@ pub struct AppDefines;
 
 impl AppDefines {
     /// ENVIRONMENT
-    /// The target frames per second for 2D physics calculations.
-    /// 60 = fine, 30 = cheaper.
-    pub const TARGET_FPS_2D_PHYSICS: f32 = 30.0;
     /// The width of the arena.
     pub const ARENA_WIDTH: f32 = 1200.0;
     /// The height of the arena.
     pub const ARENA_HEIGHT: f32 = 1000.0;
+    /// The smallest allowed width or height of the arena.
+    pub const ARENA_MIN_SIZE: f32 = 200.0;
+    /// The largest allowed width or height of the arena.
+    pub const ARENA_MAX_SIZE: f32 = 10000.0;
     /// The probability of an obstacle appearing in the arena.
     pub const OBSTACLE_PROBABILITY: f64 = 0.3;
+    /// The size of a cell of the obstacle grid, which is also the size of an obstacle.
+    pub const OBSTACLE_CELL_SIZE: f32 = 40.0;
+    /// The size of a cell of the generated mazes.
+    pub const MAZE_CELL_SIZE: f32 = 120.0;
+    /// The thickness of the walls of the generated mazes.
+    pub const MAZE_WALL_THICKNESS: f32 = 8.0;
+    /// The number of attempts at placing a room in the rooms-and-corridors generator.
+    pub const ROOM_ATTEMPTS: usize = 40;
+    /// The minimum width or height of a room, in grid cells.
+    pub const ROOM_MIN_CELLS: usize = 4;
+    /// The maximum width or height of a room, in grid cells.
+    pub const ROOM_MAX_CELLS: usize = 9;
+    /// The hit points of generated obstacles, 0 for indestructible obstacles.
+    pub const OBSTACLE_HEALTH: f32 = 0.0;
+    /// The delay in seconds before a destroyed obstacle comes back, 0 for no regeneration.
+    pub const OBSTACLE_REGENERATION_DELAY: f32 = 0.0;
+    /// The longest allowed delay in seconds before a destroyed obstacle comes back.
+    pub const OBSTACLE_MAX_REGENERATION_DELAY: f32 = 3600.0;
+
+
+    /// NETWORK
+    /// The address the servers listen on.
+    pub const SERVER_ADDRESS: &'static str = "127.0.0.1";
+    /// The port of the raw TCP server.
+    pub const SERVER_PORT: u16 = 6969;
+    /// The port of the WebSocket server, speaking the same protocol in text frames.
+    pub const WEBSOCKET_PORT: u16 = 6970;
+
+
+    /// CONFIGURATION
+    /// The default path of the configuration file.
+    pub const CONFIG_FILE_PATH: &'static str = "urss.toml";
+    /// The default path of the map file loaded and exported from the game UI.
+    pub const MAP_FILE_PATH: &'static str = "map.json";
+    /// The default path of the bot scripts loaded from the game UI.
+    pub const SCRIPT_FILE_PATH: &'static str = "scripts/example.rhai";
+    /// The profile used when the configuration file does not exist.
+    pub const DEFAULT_PROFILE: &'static str = "practice";
+    /// The score limit of the default "tournament" profile.
+    pub const TOURNAMENT_SCORE_LIMIT: i32 = 50;
 
 
     /// BOT Gameplay
     /// The available game modes.
     // BOT Gameplay
-    pub const GAME_MODES: [&'static str; 1] = ["FreeForAll"];
+    pub const GAME_MODES: [&'static str; 3] = ["FreeForAll", "KingOfTheHill", "BattleRoyale"];
     /// The rate of fire for bots in ticks.
     pub const BOT_RATE_OF_FIRE: i32 = 100;
-    /// The penalty time for infractions in ticks.
+    /// The time a penalized bot is not allowed to fire in milliseconds.
     pub const PENALTY_TIME: i64 = 1000;
     /// The delay before a connection times out in seconds.
     pub const CONNECTION_TIMEOUT_DELAY: i32 = 10;
+    /// The default delay between two world states streamed to a spectator in milliseconds.
+    pub const SPECTATE_INTERVAL: u64 = 100;
+    /// The shortest delay between two world states a spectator can ask for in milliseconds.
+    pub const SPECTATE_MIN_INTERVAL: u64 = 20;
     /// The duration messages are displayed in ticks.
     pub const MESSAGE_DURATION: i32 = 1000;
     /// The maximum length of a message in characters.
     pub const MESSAGE_LENGTH: i32 = 40;
     /// The score limit for the game.
     pub const SCORE_LIMIT: i32 = -1;
+    /// The maximum health of an entity.
+    pub const ENTITY_MAX_HEALTH: f32 = 100.0;
+    /// The delay in seconds before an eliminated entity respawns, in the game modes with respawns.
+    pub const RESPAWN_DELAY: f32 = 3.0;
+    /// The half width and half height of the square body of an entity.
+    pub const ENTITY_HALF_SIZE: f32 = 10.0;
+    /// The heat lost by a weapon per second.
+    pub const WEAPON_COOLING_RATE: f32 = 0.3;
+    /// Whether firing with an empty weapon blocks firing for the penalty time.
+    pub const EMPTY_FIRE_PENALTY: bool = false;
+    /// The delay in seconds between two pickups spawning in the arena, 0 to disable pickups.
+    pub const PICKUP_SPAWN_INTERVAL: f32 = 10.0;
+    /// The maximum number of pickups lying in the arena at the same time.
+    pub const PICKUP_MAX_COUNT: usize = 5;
+    /// The number of random positions tried when looking for a free spot for a pickup.
+    pub const PICKUP_SPAWN_ATTEMPTS: usize = 20;
+    /// The radius of a pickup.
+    pub const PICKUP_RADIUS: f32 = 8.0;
+    /// The time in seconds the timed effects of the pickups last.
+    pub const PICKUP_EFFECT_DURATION: f32 = 10.0;
+    /// The health restored by a health pickup.
+    pub const PICKUP_HEALTH: f32 = 30.0;
+    /// The factor applied to the speed of an entity with a speed boost.
+    pub const SPEED_BOOST_MULTIPLIER: f32 = 1.5;
+    /// The factor applied to the delay between two shots of an entity with rapid fire.
+    pub const RAPID_FIRE_COOLDOWN_MULTIPLIER: f32 = 0.5;
+    /// The factor applied to the damage of the bullets of an entity with extra damage.
+    pub const EXTRA_DAMAGE_MULTIPLIER: f32 = 2.0;
+    /// The maximum speed of an entity, in units per second.
+    pub const ENTITY_MAX_SPEED: f32 = 150.0;
+    /// The maximum acceleration of an entity, in units per second squared.
+    pub const ENTITY_ACCELERATION: f32 = 300.0;
+    /// The maximum speed at which an entity turns its body, in degrees per second.
+    pub const ENTITY_TURN_RATE: f32 = 180.0;
+    /// The maximum speed at which the gun turret of an entity turns, in radians per second.
+    pub const TURRET_TURN_RATE: f64 = std::f64::consts::PI;
+    /// The maximum range of the rays cast by the bots.
+    pub const RAY_MAX_RANGE: f32 = 1000.0;
+    /// The maximum number of rays of a single lidar query.
+    pub const LIDAR_MAX_RAYS: usize = 64;
+    /// Whether the queries of the bots are restricted to what they can see.
+    pub const FOG_OF_WAR: bool = false;
+    /// The distance a bot can see when the fog of war is enabled.
+    pub const VIEW_RANGE: f32 = 500.0;
+    /// The angle of the view cone of a bot in degrees, centered on its heading.
+    pub const VIEW_ANGLE: f32 = 120.0;
+
+
+    /// AI
+    /// The distance at which the chaser stops closing in on its target.
+    pub const AI_CHASE_DISTANCE: f32 = 120.0;
+    /// The distance the sniper keeps from the other bots.
+    pub const AI_SNIPER_DISTANCE: f32 = 350.0;
+    /// The largest angle in radians between the gun and the target at which the AI fires.
+    pub const AI_AIM_TOLERANCE: f64 = 0.1;
+    /// The angle in radians the AI turns its gun by at each step while looking for a target.
+    pub const AI_SCAN_STEP: f64 = 0.5;
+    /// How far ahead in seconds the dodger watches for incoming bullets.
+    pub const AI_DODGE_HORIZON: f32 = 1.0;
+    /// The distance under which a bullet passing by is dodged.
+    pub const AI_DODGE_RADIUS: f32 = 30.0;
+    /// The distance the dodger steps aside from an incoming bullet.
+    pub const AI_DODGE_DISTANCE: f32 = 80.0;
+    /// The distance between the camper and the walls of its corner.
+    pub const AI_CAMP_INSET: f32 = 60.0;
+    /// The distance under which the AI considers a waypoint of its path reached.
+    pub const AI_WAYPOINT_RADIUS: f32 = 12.0;
+    /// The delay in seconds between two plans of the path to the same destination.
+    pub const AI_REPLAN_INTERVAL: f32 = 1.0;
+    /// The distance the destination of the AI must move by before its path is planned again.
+    pub const AI_REPLAN_DISTANCE: f32 = 40.0;
+
 
+    /// SCRIPTS
+    /// The maximum number of operations a bot script may run in one step.
+    pub const SCRIPT_MAX_OPERATIONS: u64 = 10_000;
+    /// The maximum number of paths a bot script may plan in one step, each path search costing far more than an operation.
+    pub const SCRIPT_MAX_PATHS: u32 = 2;
+    /// The maximum depth of function calls in a bot script.
+    pub const SCRIPT_MAX_CALL_LEVELS: usize = 32;
+    /// The maximum length of a string built by a bot script.
+    pub const SCRIPT_MAX_STRING_SIZE: usize = 1_000;
+    /// The maximum number of elements in an array or map built by a bot script.
+    pub const SCRIPT_MAX_COLLECTION_SIZE: usize = 1_000;
+    /// The delay in seconds between two checks of a bot script file for changes.
+    pub const SCRIPT_RELOAD_INTERVAL: f32 = 1.0;
 
-    /// USER command keywords
-    /// Command to set the user's name. Argument: string.
-    pub const SET_NAME: &'static str = "NAME";
-    /// Command to set the user's color. Arguments: 3 integers for RGB.
-    pub const SET_COLOR: &'static str = "COL";
-    /// Command to quit. No arguments.
-    pub const QUIT: &'static str = "EXIT";
-    /// Command to indicate the user is alive. No arguments.
-    pub const ALIVE: &'static str = "LIVE";
-    /// Command to send a message. Argument: string (a short message).
-    pub const MESSAGE: &'static str = "MSG";
 
-    /// Command to query the closest bot. No arguments.
-    pub const QUERY_CLOSEST_BOT: &'static str = "CBOT";
-    /// Command to query the closest projectile. No arguments.
-    pub const QUERY_CLOSEST_PROJECTILE: &'static str = "CPROJ";
-    /// Command to query a bot by name. Argument: string (name of the player).
-    pub const QUERY_BY_NAME: &'static str = "NBOT";
-    /// Command to query the list of names. No arguments.
-    pub const QUERY_NAME_LIST: &'static str = "NLIST";
-    /// Command to query the orientation. No arguments.
-    pub const QUERY_ORIENTATION: &'static str = "ORIENT";
-    /// Command to query messages from a user. Argument: string (name of the player).
-    pub const QUERY_MESSAGES_FROM_USER: &'static str = "USRMSG";
+    /// NAVIGATION
+    /// The width and height of a cell of the navigation grid, small enough to find the gaps between obstacles.
+    pub const NAV_CELL_SIZE: f32 = 10.0;
+    /// The distance kept between the center of an entity and the obstacles.
+    pub const NAV_CLEARANCE: f32 = 12.0;
+    /// The maximum number of cells explored when planning a path, past which the target is considered unreachable.
+    pub const NAV_MAX_EXPANDED_CELLS: usize = 20_000;
+    /// The minimum delay in seconds between two `PATH` queries of a client.
+    pub const PATH_QUERY_INTERVAL: f32 = 0.1;
 
-    /// Command for an empty reply. No arguments.
-    pub const EMPTY_REPLY: &'static str = "EMPTY";
 
-    /// Separator for commands.
-    pub const COMMAND_SEP: &'static str = "#";
-    /// Separator for arguments.
-    pub const ARGUMENT_SEP: &'static str = "=";
+    /// ZONES
+    /// The radius of the King of the Hill control zone.
+    pub const CONTROL_ZONE_RADIUS: f32 = 100.0;
+    /// The delay in seconds between two points awarded to the control zone owner.
+    pub const CONTROL_ZONE_AWARD_INTERVAL: f32 = 1.0;
+    /// The radius of the Battle Royale safe zone once fully shrunk.
+    pub const SAFE_ZONE_MIN_RADIUS: f32 = 100.0;
+    /// The time in seconds the Battle Royale safe zone takes to shrink.
+    pub const SAFE_ZONE_SHRINK_DURATION: u64 = 120;
+    /// The damage per second taken by entities outside the safe zone.
+    pub const SAFE_ZONE_DAMAGE_PER_SECOND: f32 = 10.0;
 }
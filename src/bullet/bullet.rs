@@ -1,42 +1,53 @@
 use rapier2d::prelude::*;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use crate::app_defines::AppDefines;
 use crate::physics::physics::PhysicsEngine;
+use crate::weapons::Weapon;
 
 /// Represents a bullet in the physics simulation.
 pub struct Bullet {
     pub handle: RigidBodyHandle,
     pub shooter: RigidBodyHandle,
     pub created_at: Instant,
+    /// The time after which the bullet disappears.
+    pub lifetime: Duration,
+    /// The damage dealt by the bullet to entities and destructible obstacles.
+    pub damage: f32,
+    /// The number of times the bullet can still bounce off walls and obstacles.
+    pub bounces_left: u32,
 }
 
 impl Bullet {
     /// Creates a new `Bullet`.
     ///
+    /// The bullet starts just outside the shooter so it does not collide with it.
+    ///
     /// # Parameters
     /// - `shooter_handle`: The handle of the shooter entity.
     /// - `physics_engine`: A mutable reference to the physics engine.
-    /// - `speed`: The speed of the bullet.
-    /// - `radius`: The radius of the bullet's collider.
+    /// - `angle`: The direction of the bullet in radians, usually the gun orientation of the shooter.
+    /// - `weapon`: The weapon firing the bullet.
     ///
     /// # Returns
     /// A new instance of `Bullet`.
     ///
     /// # Examples
     /// ```
-    /// let bullet = Bullet::new(shooter_handle, &mut physics_engine, speed, radius);
+    /// let bullet = Bullet::new(shooter_handle, &mut physics_engine, entity.gun_orientation as f32, entity.weapon.weapon());
     /// ```
-    pub fn new(shooter_handle: RigidBodyHandle, physics_engine: &mut PhysicsEngine, speed: f32, radius: f32) -> Self {
-        let shooter_body = &physics_engine.bodies[shooter_handle];
-        let pos = shooter_body.translation().clone();
-        let angle = shooter_body.rotation().angle();
+    pub fn new(shooter_handle: RigidBodyHandle, physics_engine: &mut PhysicsEngine, angle: f32, weapon: &Weapon) -> Self {
         let direction = vector![angle.cos(), angle.sin()];
+        let spawn_distance = AppDefines::ENTITY_HALF_SIZE * std::f32::consts::SQRT_2 + weapon.radius + 1.0;
+        let pos = *physics_engine.bodies[shooter_handle].translation() + direction * spawn_distance;
 
         let rigid_body = RigidBodyBuilder::dynamic()
             .translation(pos)
-            .linvel(direction * speed)
+            .linvel(direction * weapon.speed)
             .build();
-        let collider = ColliderBuilder::ball(radius)
-            .restitution(0.0)
+        // Collisions are reported to count the bounces and apply the damage
+        let collider = ColliderBuilder::ball(weapon.radius)
+            .restitution(if weapon.bounces > 0 { 1.0 } else { 0.0 })
+            .active_events(ActiveEvents::COLLISION_EVENTS)
             .build();
 
         let handle = physics_engine.bodies.insert(rigid_body);
@@ -46,6 +57,29 @@ impl Bullet {
             handle,
             shooter: shooter_handle,
             created_at: Instant::now(),
+            lifetime: weapon.lifetime,
+            damage: weapon.damage,
+            bounces_left: weapon.bounces,
+        }
+    }
+
+    /// Uses one of the remaining bounces of the bullet.
+    ///
+    /// # Returns
+    /// `true` if the bullet bounced, `false` if it had no bounce left and must disappear.
+    pub fn bounce(&mut self) -> bool {
+        if self.bounces_left == 0 {
+            return false;
         }
+        self.bounces_left -= 1;
+        true
+    }
+
+    /// Checks whether the bullet has outlived its lifetime.
+    ///
+    /// # Returns
+    /// `true` if the bullet must disappear.
+    pub fn is_expired(&self) -> bool {
+        self.created_at.elapsed() >= self.lifetime
     }
 }
@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_defines::AppDefines;
+use crate::server::server_thread::ServerSettings;
+
+/// Represents the configuration file, holding named profiles of server settings.
+///
+/// Unknown keys are rejected, so a misspelled setting is reported instead of silently ignored.
+///
+/// # Examples
+/// ```toml
+/// active_profile = "practice"
+///
+/// [profiles.practice]
+/// arena_width = 1200.0
+///
+/// [profiles.tournament]
+/// score_limit = 50
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigFile {
+    /// The profile used when none is given on the command line.
+    pub active_profile: String,
+    /// The server settings of each profile, by name.
+    pub profiles: BTreeMap<String, ServerSettings>,
+}
+
+impl ConfigFile {
+    /// Loads a configuration file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the TOML file to read.
+    ///
+    /// # Returns
+    /// The loaded configuration, or an error message if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read config file {}: {}", path.display(), e))?;
+        toml::from_str(&content)
+            .map_err(|e| format!("Cannot parse config file {}: {}", path.display(), e))
+    }
+
+    /// Loads a configuration file, falling back to the default profiles if it does not exist.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the TOML file to read.
+    ///
+    /// # Returns
+    /// The loaded configuration, or an error message if the file exists but is invalid.
+    pub fn load_or_default(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Saves the configuration to a file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the TOML file to write.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error message if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| format!("Cannot serialize config: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Cannot write config file {}: {}", path.display(), e))
+    }
+
+    /// Returns the settings of a profile.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the profile.
+    ///
+    /// # Returns
+    /// The settings of the profile, or an error message listing the known profiles.
+    pub fn profile(&self, name: &str) -> Result<ServerSettings, String> {
+        self.profiles.get(name).cloned().ok_or_else(|| {
+            format!(
+                "Unknown profile '{}', available profiles: {}",
+                name,
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+}
+
+impl Default for ConfigFile {
+    /// Creates the default configuration with a "practice" and a "tournament" profile.
+    fn default() -> Self {
+        let practice = ServerSettings::new();
+
+        let mut tournament = ServerSettings::new();
+        tournament.score_limit = AppDefines::TOURNAMENT_SCORE_LIMIT;
+
+        let mut profiles = BTreeMap::new();
+        profiles.insert("practice".to_string(), practice);
+        profiles.insert("tournament".to_string(), tournament);
+
+        Self {
+            active_profile: AppDefines::DEFAULT_PROFILE.to_string(),
+            profiles,
+        }
+    }
+}
+
+/// Represents the options given on the command line.
+pub(crate) struct CommandLine {
+    /// The path of the configuration file.
+    pub config_path: PathBuf,
+    /// The profile to use instead of the active profile of the configuration file.
+    pub profile: Option<String>,
+    /// The settings overridden on the command line, as (name, value) pairs.
+    pub overrides: Vec<(String, String)>,
+}
+
+impl CommandLine {
+    /// The usage message printed when the command line is invalid.
+    pub const USAGE: &'static str = "Usage: universal_rust_server_software [--config <path>] [--profile <name>] [--<setting> <value>]...
+Settings: --arena-width, --arena-height, --obstacle-probability, --obstacle-health, --obstacle-regeneration-delay,
+          --max-speed, --acceleration, --turn-rate, --pickup-spawn-interval, --pickup-max-count,
+          --bot-rate-of-fire, --penalty-time, --empty-fire-penalty,
+          --connection-timeout-delay, --message-duration, --message-length, --score-limit,
+          --fog-of-war, --view-range, --view-angle, --server-port, --websocket-port";
+
+    /// Parses the command line arguments.
+    ///
+    /// # Parameters
+    /// - `args`: The arguments, without the program name.
+    ///
+    /// # Returns
+    /// The parsed command line, or an error message if an option is missing its value.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut command_line = CommandLine {
+            config_path: PathBuf::from(AppDefines::CONFIG_FILE_PATH),
+            profile: None,
+            overrides: Vec::new(),
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(option) = arg.strip_prefix("--") else {
+                return Err(format!("Unexpected argument '{}'", arg));
+            };
+            let value = args.next().ok_or_else(|| format!("Missing value for option '{}'", arg))?;
+
+            match option {
+                "config" => command_line.config_path = PathBuf::from(value),
+                "profile" => command_line.profile = Some(value),
+                _ => command_line.overrides.push((option.replace('-', "_"), value)),
+            }
+        }
+
+        Ok(command_line)
+    }
+
+    /// Builds the server settings from the configuration file and the command line.
+    ///
+    /// The selected profile is loaded, the command line overrides are applied on top of it,
+    /// and the result is validated.
+    ///
+    /// # Parameters
+    /// - `config`: The loaded configuration file.
+    ///
+    /// # Returns
+    /// The name of the selected profile and its settings, or the list of error messages.
+    pub fn resolve_settings(&self, config: &ConfigFile) -> Result<(String, ServerSettings), Vec<String>> {
+        let profile = self.profile.clone().unwrap_or_else(|| config.active_profile.clone());
+        let mut settings = config.profile(&profile).map_err(|e| vec![e])?;
+
+        let override_errors: Vec<String> = self.overrides.iter()
+            .filter_map(|(name, value)| settings.set_from_str(name, value).err())
+            .collect();
+        if !override_errors.is_empty() {
+            return Err(override_errors);
+        }
+
+        if let Err(errors) = settings.validate() {
+            return Err(errors.into_iter()
+                .map(|error| format!("Profile '{}': {}", profile, error))
+                .collect());
+        }
+
+        Ok((profile, settings))
+    }
+}
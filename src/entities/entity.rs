@@ -1,52 +1,80 @@
-use rand::Rng;
 use rapier2d::prelude::*;
-use std::time::Instant;
-use eframe::egui;
+use std::time::{Duration, Instant};
+use crate::ai::aiming::Difficulty;
+use crate::ai::{BotBrain, BrainKind};
+use crate::app_defines::AppDefines;
 use crate::physics::physics::PhysicsEngine;
+use crate::pickups::{ActiveEffect, PickupKind};
+use crate::weapons::WeaponKind;
 
 /// Represents an entity in the physics simulation.
 pub struct Entity {
     pub name: String,
     pub score: i32,
+    pub health: f32,
     pub handle: RigidBodyHandle,
-    pub is_ai: bool,
     pub last_shot: Instant,
     pub x: f32,
     pub y: f32,
     pub self_orientation: f64,
     pub gun_orientation: f64,
-    pub target_x: f32,
-    pub target_y: f32,
-    pub color: egui::Color32,
+    /// The heading the body is turning towards, in radians.
+    pub target_orientation: f64,
+    /// The speed the entity accelerates to along its heading, negative when moving backwards.
+    pub target_speed: f32,
+    /// The maximum speed of the entity, in units per second.
+    pub max_speed: f32,
+    /// The maximum acceleration of the entity, in units per second squared.
+    pub acceleration: f32,
+    /// The maximum speed at which the entity turns its body, in radians per second.
+    pub turn_rate: f64,
+    /// The orientation the gun turret is turning towards, in radians.
+    pub target_gun_orientation: f64,
+    /// The weapon fired by the entity.
+    pub weapon: WeaponKind,
+    /// The ammunition left in the weapon.
+    pub ammo: u32,
+    /// The time at which the current reload started, if the weapon is being reloaded.
+    pub reload_started: Option<Instant>,
+    /// The heat of the weapon, from 0 to 1.
+    pub heat: f32,
+    /// Whether the weapon overheated and is cooling down.
+    pub overheated: bool,
+    /// The time until which the entity is not allowed to fire.
+    pub penalty_until: Option<Instant>,
+    /// The timed effects of the pickups collected by the entity.
+    pub effects: Vec<ActiveEffect>,
+    /// The time at which the entity was eliminated, `None` while it is alive.
+    pub eliminated_at: Option<Instant>,
+    /// The behaviour of the entity, `None` for the entities controlled by network bots.
+    pub brain: Option<Box<dyn BotBrain>>,
 }
 
 impl Entity {
-    /// Creates a new `Entity`.
+    /// Creates a new `Entity`, at rest and with the default movement limits.
     ///
     /// # Parameters
     /// - `name`: The name of the entity.
     /// - `physics_engine`: A mutable reference to the physics engine.
     /// - `is_ai`: A boolean indicating whether the entity is controlled by AI.
+    /// - `position`: A tuple representing the (x, y) spawn position of the entity.
     ///
     /// # Returns
     /// A new instance of `Entity`.
     ///
     /// # Examples
     /// ```
-    /// let entity = Entity::new("Player1".to_string(), &mut physics_engine, false);
+    /// let entity = Entity::new("Player1".to_string(), &mut physics_engine, false, (600.0, 500.0));
     /// ```
-    pub fn new(name: String, physics_engine: &mut PhysicsEngine, is_ai: bool) -> Self {
-        let mut rng = rand::thread_rng();
-        let random_x = rng.gen_range(10.0..1190.0);
-        let random_y = rng.gen_range(10.0..990.0);
-        let vx = rng.gen_range(-100.0..100.0);
-        let vy = rng.gen_range(-100.0..100.0);
+    pub fn new(name: String, physics_engine: &mut PhysicsEngine, is_ai: bool, position: (f32, f32)) -> Self {
+        let (x, y) = position;
 
+        // The body only moves through `update_movement`, its orientation is kept by the entity
         let rigid_body = RigidBodyBuilder::dynamic()
-            .translation(vector![random_x, random_y])
-            .linvel(vector![vx, vy])
+            .translation(vector![x, y])
+            .lock_rotations()
             .build();
-        let collider = ColliderBuilder::cuboid(10.0, 10.0)
+        let collider = ColliderBuilder::cuboid(AppDefines::ENTITY_HALF_SIZE, AppDefines::ENTITY_HALF_SIZE)
             .restitution(0.0)
             .build();
 
@@ -56,16 +84,254 @@ impl Entity {
         Self {
             name,
             score: 0,
+            health: AppDefines::ENTITY_MAX_HEALTH,
             handle,
-            is_ai,
             last_shot: Instant::now(),
-            x: random_x,
-            y: random_y,
+            x,
+            y,
             self_orientation: 0.0,
             gun_orientation: 0.0,
-            target_x: random_x,
-            target_y: random_y,
-            color: egui::Color32::GREEN,
+            target_orientation: 0.0,
+            target_speed: 0.0,
+            max_speed: AppDefines::ENTITY_MAX_SPEED,
+            acceleration: AppDefines::ENTITY_ACCELERATION,
+            turn_rate: (AppDefines::ENTITY_TURN_RATE as f64).to_radians(),
+            target_gun_orientation: 0.0,
+            weapon: WeaponKind::Pistol,
+            ammo: WeaponKind::Pistol.weapon().magazine,
+            reload_started: None,
+            heat: 0.0,
+            overheated: false,
+            penalty_until: None,
+            effects: Vec::new(),
+            eliminated_at: None,
+            brain: is_ai.then(|| BrainKind::default().create(Difficulty::default())),
         }
     }
+
+    /// Turns the body towards its target heading and accelerates it towards its target speed,
+    /// within the movement limits of the entity.
+    ///
+    /// The velocity change is applied as an impulse, so the body still collides with walls and other entities.
+    /// Eliminated entities brake to a stop.
+    ///
+    /// # Parameters
+    /// - `body`: The rigid body of the entity.
+    /// - `delta_time`: The duration of the physics step, in seconds.
+    pub fn update_movement(&mut self, body: &mut RigidBody, delta_time: f32) {
+        let max_turn = self.turn_rate * delta_time as f64;
+        let offset = (self.target_orientation - self.self_orientation + std::f64::consts::PI)
+            .rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+        self.self_orientation = (self.self_orientation + offset.clamp(-max_turn, max_turn)).rem_euclid(std::f64::consts::TAU);
+
+        let max_speed = self.max_speed * self.speed_multiplier();
+        let speed = if self.is_alive() { self.target_speed.clamp(-max_speed, max_speed) } else { 0.0 };
+        let heading = self.self_orientation as f32;
+        let desired_velocity = vector![heading.cos(), heading.sin()] * speed;
+
+        let velocity_change = desired_velocity - *body.linvel();
+        let max_change = self.acceleration * delta_time;
+        let velocity_change = if velocity_change.norm() > max_change {
+            velocity_change.normalize() * max_change
+        } else {
+            velocity_change
+        };
+        body.apply_impulse(velocity_change * body.mass(), true);
+    }
+
+    /// Turns the gun turret towards its target orientation, no faster than `AppDefines::TURRET_TURN_RATE`.
+    ///
+    /// # Parameters
+    /// - `delta_time`: The time elapsed since the last update, in seconds.
+    pub fn update_turret(&mut self, delta_time: f32) {
+        let max_turn = AppDefines::TURRET_TURN_RATE * delta_time as f64;
+        // Shortest signed angle between the current and the target orientation
+        let offset = (self.target_gun_orientation - self.gun_orientation + std::f64::consts::PI)
+            .rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+        self.gun_orientation = (self.gun_orientation + offset.clamp(-max_turn, max_turn)).rem_euclid(std::f64::consts::TAU);
+    }
+
+    /// Switches to another weapon, which must be reloaded before firing.
+    ///
+    /// # Parameters
+    /// - `weapon`: The new weapon.
+    pub fn switch_weapon(&mut self, weapon: WeaponKind) {
+        self.weapon = weapon;
+        self.ammo = 0;
+        self.heat = 0.0;
+        self.overheated = false;
+        self.reload_started = None;
+        self.reload();
+    }
+
+    /// Starts reloading the weapon, unless it is already being reloaded.
+    pub fn reload(&mut self) {
+        if self.reload_started.is_none() {
+            self.reload_started = Some(Instant::now());
+        }
+    }
+
+    /// Cools the weapon down and finishes its reload once the reload time has elapsed.
+    ///
+    /// # Parameters
+    /// - `delta_time`: The time elapsed since the last update, in seconds.
+    pub fn update_weapon(&mut self, delta_time: f32) {
+        self.heat = (self.heat - AppDefines::WEAPON_COOLING_RATE * delta_time).max(0.0);
+        if self.heat == 0.0 {
+            self.overheated = false;
+        }
+
+        if let Some(reload_started) = self.reload_started {
+            if reload_started.elapsed() >= self.weapon.weapon().reload_time {
+                self.ammo = self.weapon.weapon().magazine;
+                self.reload_started = None;
+            }
+        }
+    }
+
+    /// Returns the time left before the reload of the weapon ends.
+    ///
+    /// # Returns
+    /// The remaining reload time, zero if the weapon is not being reloaded.
+    pub fn reload_remaining(&self) -> Duration {
+        self.reload_started
+            .map(|reload_started| self.weapon.weapon().reload_time.saturating_sub(reload_started.elapsed()))
+            .unwrap_or_default()
+    }
+
+    /// Refills the weapon and clears its heat and any firing penalty.
+    pub fn refill_weapon(&mut self) {
+        self.ammo = self.weapon.weapon().magazine;
+        self.reload_started = None;
+        self.heat = 0.0;
+        self.overheated = false;
+        self.penalty_until = None;
+    }
+
+    /// Applies the effect of a collected pickup.
+    ///
+    /// Collecting a timed effect the entity already has restarts it.
+    ///
+    /// # Parameters
+    /// - `kind`: The kind of the collected pickup.
+    pub fn apply_pickup(&mut self, kind: PickupKind) {
+        if kind == PickupKind::Health {
+            self.health = (self.health + AppDefines::PICKUP_HEALTH).min(AppDefines::ENTITY_MAX_HEALTH);
+            return;
+        }
+
+        let expires_at = Instant::now() + kind.effect_duration();
+        match self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.expires_at = expires_at,
+            None => self.effects.push(ActiveEffect { kind, expires_at }),
+        }
+    }
+
+    /// Checks whether a timed effect is active on the entity.
+    ///
+    /// # Parameters
+    /// - `kind`: The kind of pickup giving the effect.
+    ///
+    /// # Returns
+    /// `true` if the effect is active.
+    pub fn has_effect(&self, kind: PickupKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Removes the timed effects that have ended.
+    pub fn update_effects(&mut self) {
+        let now = Instant::now();
+        self.effects.retain(|effect| effect.expires_at > now);
+    }
+
+    /// Returns the factor applied to the speed of the entity by its effects.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has_effect(PickupKind::SpeedBoost) { AppDefines::SPEED_BOOST_MULTIPLIER } else { 1.0 }
+    }
+
+    /// Returns the factor applied to the damage of the bullets of the entity by its effects.
+    pub fn damage_multiplier(&self) -> f32 {
+        if self.has_effect(PickupKind::ExtraDamage) { AppDefines::EXTRA_DAMAGE_MULTIPLIER } else { 1.0 }
+    }
+
+    /// Returns the minimum delay between two shots of the entity, shortened by rapid fire.
+    pub fn cooldown(&self) -> Duration {
+        let cooldown = self.weapon.weapon().cooldown;
+        if self.has_effect(PickupKind::RapidFire) {
+            cooldown.mul_f32(AppDefines::RAPID_FIRE_COOLDOWN_MULTIPLIER)
+        } else {
+            cooldown
+        }
+    }
+
+    /// Checks whether the entity is still in play.
+    ///
+    /// # Returns
+    /// `true` if the entity has health left, `false` if it has been eliminated.
+    pub fn is_alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    /// Enables or disables the colliders of the entity, so eliminated entities stop blocking bodies and bullets.
+    ///
+    /// A disabled entity is also brought to a stop, as it no longer has the mass to brake.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    /// - `enabled`: Whether the entity collides with the rest of the world.
+    pub fn set_collisions_enabled(&self, physics_engine: &mut PhysicsEngine, enabled: bool) {
+        let Some(body) = physics_engine.bodies.get_mut(self.handle) else {
+            return;
+        };
+        if !enabled {
+            body.set_linvel(vector![0.0, 0.0], true);
+        }
+        for &collider_handle in body.colliders() {
+            if let Some(collider) = physics_engine.colliders.get_mut(collider_handle) {
+                collider.set_enabled(enabled);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(physics_engine: &mut PhysicsEngine) -> Entity {
+        Entity::new("bot".to_string(), physics_engine, false, (100.0, 100.0))
+    }
+
+    #[test]
+    fn reload_refills_the_magazine_once_the_reload_time_elapsed() {
+        let mut physics_engine = PhysicsEngine::default();
+        let mut entity = entity(&mut physics_engine);
+        entity.switch_weapon(WeaponKind::Rifle);
+        assert_eq!(entity.ammo, 0);
+
+        entity.update_weapon(0.0);
+        assert_eq!(entity.ammo, 0);
+        assert!(entity.reload_remaining() > Duration::ZERO);
+
+        entity.reload_started = Some(Instant::now() - WeaponKind::Rifle.weapon().reload_time);
+        entity.update_weapon(0.0);
+        assert_eq!(entity.ammo, WeaponKind::Rifle.weapon().magazine);
+        assert_eq!(entity.reload_started, None);
+    }
+
+    #[test]
+    fn overheated_weapon_cools_down_completely_before_firing_again() {
+        let mut physics_engine = PhysicsEngine::default();
+        let mut entity = entity(&mut physics_engine);
+        entity.heat = 1.0;
+        entity.overheated = true;
+
+        entity.update_weapon(1.0);
+        assert!(entity.overheated);
+        assert!((entity.heat - (1.0 - AppDefines::WEAPON_COOLING_RATE)).abs() < 1e-6);
+
+        entity.update_weapon(10.0);
+        assert_eq!(entity.heat, 0.0);
+        assert!(!entity.overheated);
+    }
 }
@@ -1,12 +1,90 @@
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use rand::Rng;
 use rapier2d::prelude::*;
 
+use crate::app_defines::AppDefines;
 use crate::bullet::bullet::Bullet;
 use crate::entities::entity::Entity;
-use crate::obstacles::Obstacle;
+use crate::maps::{ControlZoneDefinition, MapFile, ObstacleDefinition, SafeZoneDefinition};
+use crate::obstacles::generator::MapGenerator;
+use crate::obstacles::{Obstacle, ObstacleMaterial, ObstacleShape};
+use crate::ai::{BotActions, BotBrain, VisibleBot, WorldSnapshot};
+use crate::navigation::NavGrid;
 use crate::physics::physics::PhysicsEngine;
+use crate::pickups::{Pickup, PickupKind};
+use crate::server::server_thread::ServerSettings;
+use crate::weapons::ShotError;
+use crate::zones::{ControlZone, SafeZone};
+
+pub mod world_state;
+
+/// Represents the available game modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameMode {
+    /// Every entity for itself, points are earned by eliminating other entities.
+    FreeForAll,
+    /// Points are earned over time by holding the control zone alone.
+    KingOfTheHill,
+    /// Entities outside the shrinking safe zone take damage until eliminated.
+    BattleRoyale,
+}
+
+impl GameMode {
+    /// All the game modes, in the same order as `AppDefines::GAME_MODES`.
+    pub const ALL: [GameMode; 3] = [GameMode::FreeForAll, GameMode::KingOfTheHill, GameMode::BattleRoyale];
+
+    /// Checks whether eliminated entities come back after `AppDefines::RESPAWN_DELAY`.
+    ///
+    /// # Returns
+    /// `true` in every mode but Battle Royale, where eliminated entities stay out until the next round.
+    pub fn respawns(&self) -> bool {
+        *self != GameMode::BattleRoyale
+    }
+
+    /// Checks whether eliminating an entity earns a point to the shooter.
+    ///
+    /// # Returns
+    /// `true` in every mode but King of the Hill, where points are only earned by holding the zone.
+    pub fn scores_eliminations(&self) -> bool {
+        *self != GameMode::KingOfTheHill
+    }
+
+    /// Returns the name of the game mode as listed in `AppDefines::GAME_MODES`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::FreeForAll => AppDefines::GAME_MODES[0],
+            GameMode::KingOfTheHill => AppDefines::GAME_MODES[1],
+            GameMode::BattleRoyale => AppDefines::GAME_MODES[2],
+        }
+    }
+}
+
+/// Represents what a ray cast by an entity hit.
+pub enum RayHit {
+    /// The boundaries of the arena.
+    Wall,
+    /// An obstacle.
+    Obstacle,
+    /// Another entity, with its name.
+    Bot(String),
+    /// A bullet.
+    Bullet,
+}
+
+/// Represents a bullet seen by an entity.
+#[derive(Clone)]
+pub struct VisibleBullet {
+    /// The (x, y) position of the bullet.
+    pub position: (f32, f32),
+    /// The (vx, vy) velocity of the bullet.
+    pub velocity: (f32, f32),
+    /// The distance between the bullet and the entity.
+    pub distance: f32,
+    /// The handle of the rigid body of the entity that fired the bullet.
+    pub shooter: RigidBodyHandle,
+}
 
 /// Represents the game logic and manages the state of the game.
 pub struct GameLogic {
@@ -18,27 +96,87 @@ pub struct GameLogic {
     pub bullets: Vec<Bullet>,
     /// A list of obstacles in the game.
     pub obstacles: Vec<Obstacle>,
+    /// A list of pickups lying in the arena.
+    pub pickups: Vec<Pickup>,
+    /// The walkable cells of the arena, rebuilt whenever the obstacles change.
+    pub nav_grid: Arc<NavGrid>,
+    /// The time at which the last pickup spawned.
+    last_pickup_spawn: Instant,
+    /// The seed the current map was generated from.
+    pub map_seed: u64,
+    /// The positions where entities can spawn without overlapping obstacles.
+    pub spawn_points: Vec<(f32, f32)>,
+    /// The control zone of the loaded map file, if it defines one.
+    map_control_zone: Option<ControlZoneDefinition>,
+    /// The safe zone of the loaded map file, if it defines one.
+    map_safe_zone: Option<SafeZoneDefinition>,
+    /// The strategy used to generate the obstacles of the map.
+    pub map_generator: MapGenerator,
+    /// The current game mode.
+    pub game_mode: GameMode,
+    /// The control zone, present in King of the Hill mode.
+    pub control_zone: Option<ControlZone>,
+    /// The shrinking safe zone, present in Battle Royale mode.
+    pub safe_zone: Option<SafeZone>,
+    /// The width of the arena currently simulated.
+    pub arena_width: f32,
+    /// The height of the arena currently simulated.
+    pub arena_height: f32,
+    /// Thread-safe, shared server settings the arena is read from.
+    settings: Arc<Mutex<ServerSettings>>,
+    /// The time of the last simulation step.
+    last_step: Instant,
 }
 
 impl GameLogic {
     /// Creates a new `GameLogic` instance.
     ///
+    /// # Parameters
+    /// - `settings`: Thread-safe, shared server settings holding the arena dimensions.
+    ///
     /// # Returns
     /// A new instance of `GameLogic`.
     ///
     /// # Examples
     /// ```
-    /// let game_logic = GameLogic::new();
+    /// let game_logic = GameLogic::new(Arc::new(Mutex::new(ServerSettings::new())));
     /// ```
-    pub fn new() -> Self {
+    pub fn new(settings: Arc<Mutex<ServerSettings>>) -> Self {
+        let (arena_width, arena_height) = {
+            let settings = settings.lock().unwrap();
+            (settings.arena_width, settings.arena_height)
+        };
         let mut physics_engine = PhysicsEngine::default();
-        physics_engine.setup_boundaries();
+        physics_engine.setup_boundaries(arena_width, arena_height);
+        let nav_grid = NavGrid::build(
+            &physics_engine,
+            &[],
+            arena_width,
+            arena_height,
+            AppDefines::NAV_CELL_SIZE,
+            AppDefines::NAV_CLEARANCE,
+        );
 
         Self {
             physics_engine,
             entities: Vec::new(),
             bullets: Vec::new(),
             obstacles: Vec::new(),
+            pickups: Vec::new(),
+            nav_grid: Arc::new(nav_grid),
+            last_pickup_spawn: Instant::now(),
+            map_seed: 0,
+            spawn_points: Vec::new(),
+            map_control_zone: None,
+            map_safe_zone: None,
+            map_generator: MapGenerator::Scattered,
+            game_mode: GameMode::FreeForAll,
+            control_zone: None,
+            safe_zone: None,
+            arena_width,
+            arena_height,
+            settings,
+            last_step: Instant::now(),
         }
     }
 
@@ -46,67 +184,559 @@ impl GameLogic {
     ///
     /// # Parameters
     /// - `name`: The name of the entity.
-    pub fn add_entity(&mut self, name: String) {
-        let entity = Entity::new(name, &mut self.physics_engine, false);
+    ///
+    /// # Returns
+    /// The handle of the rigid body of the new entity.
+    pub fn add_entity(&mut self, name: String) -> RigidBodyHandle {
+        self.spawn_entity(name, false)
+    }
+
+    /// Creates an entity at a spawn point, with the movement limits of the server settings.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the entity.
+    /// - `is_ai`: Whether the entity is controlled by AI.
+    ///
+    /// # Returns
+    /// The handle of the rigid body of the new entity.
+    fn spawn_entity(&mut self, name: String, is_ai: bool) -> RigidBodyHandle {
+        let position = self.spawn_position();
+        let mut entity = Entity::new(name, &mut self.physics_engine, is_ai, position);
+        {
+            let settings = self.settings.lock().unwrap();
+            entity.max_speed = settings.max_speed;
+            entity.acceleration = settings.acceleration;
+            entity.turn_rate = (settings.turn_rate as f64).to_radians();
+        }
+        let handle = entity.handle;
         self.entities.push(entity);
+        handle
     }
 
-    /// Makes an entity shoot a bullet.
+    /// Removes an entity and its rigid body from the game.
     ///
     /// # Parameters
-    /// - `shooter_index`: The index of the entity that is shooting.
-    pub fn shoot_ball(&mut self, shooter_index: usize) {
-        if shooter_index >= self.entities.len() {
-            return;
+    /// - `handle`: The handle of the rigid body of the entity.
+    pub fn remove_entity(&mut self, handle: RigidBodyHandle) {
+        if let Some(index) = self.entities.iter().position(|entity| entity.handle == handle) {
+            self.entities.remove(index);
+            self.physics_engine.bodies.remove(
+                handle,
+                &mut self.physics_engine.islands,
+                &mut self.physics_engine.colliders,
+                &mut self.physics_engine.impulse_joints,
+                &mut self.physics_engine.multibody_joints,
+                true,
+            );
+        }
+    }
+
+    /// Finds an entity from the handle of its rigid body.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity.
+    ///
+    /// # Returns
+    /// A mutable reference to the entity, or `None` if it is not in the game.
+    pub fn entity_mut(&mut self, handle: RigidBodyHandle) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|entity| entity.handle == handle)
+    }
+
+    /// Casts a ray from an entity and finds what it hits first.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity casting the ray.
+    /// - `relative_angle`: The direction of the ray relative to the heading of the entity, in radians.
+    /// - `max_range`: The maximum distance the ray travels.
+    ///
+    /// # Returns
+    /// The distance to the first hit and what was hit, or `None` if nothing is within range
+    /// or the entity is not in the game.
+    pub fn cast_ray(&self, handle: RigidBodyHandle, relative_angle: f32, max_range: f32) -> Option<(f32, RayHit)> {
+        let entity = self.entities.iter().find(|entity| entity.handle == handle)?;
+        let position = self.physics_engine.bodies.get(handle)?.translation();
+        let angle = entity.self_orientation as f32 + relative_angle;
+        let (collider_handle, distance) = self.physics_engine.cast_ray((position.x, position.y), angle, max_range, handle)?;
+
+        let hit = if self.physics_engine.boundary_handles.contains(&collider_handle) {
+            RayHit::Wall
+        } else if self.obstacles.iter().any(|obstacle| obstacle.collider_handle == collider_handle) {
+            RayHit::Obstacle
+        } else {
+            let body = self.physics_engine.colliders.get(collider_handle)?.parent()?;
+            match self.entities.iter().find(|entity| entity.handle == body) {
+                Some(entity) => RayHit::Bot(entity.name.clone()),
+                None => RayHit::Bullet,
+            }
+        };
+        Some((distance, hit))
+    }
+
+    /// Returns the view cone of the entities, when the fog of war is enabled.
+    ///
+    /// # Returns
+    /// The view range and half of the view angle in radians, or `None` if entities see the whole arena.
+    pub fn view_cone(&self) -> Option<(f32, f32)> {
+        let settings = self.settings.lock().unwrap();
+        settings.fog_of_war.then(|| (settings.view_range, settings.view_angle.to_radians() / 2.0))
+    }
+
+    /// Returns the position of a rigid body.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body.
+    ///
+    /// # Returns
+    /// The (x, y) position of the rigid body, or `None` if it is not in the physics engine.
+    fn body_position(&self, handle: RigidBodyHandle) -> Option<(f32, f32)> {
+        let translation = self.physics_engine.bodies.get(handle)?.translation();
+        Some((translation.x, translation.y))
+    }
+
+    /// Checks whether an entity can see a position.
+    ///
+    /// The position must be within the view range and view angle of the entity, and the line between
+    /// them must not cross a wall or an obstacle.
+    ///
+    /// # Parameters
+    /// - `viewer`: The entity looking.
+    /// - `origin`: The position of the entity looking.
+    /// - `target`: The position looked at.
+    /// - `view_cone`: The view cone from `view_cone`, `None` if everything is visible.
+    ///
+    /// # Returns
+    /// `true` if the position is visible.
+    fn can_see(&self, viewer: &Entity, origin: (f32, f32), target: (f32, f32), view_cone: Option<(f32, f32)>) -> bool {
+        let Some((view_range, half_view_angle)) = view_cone else {
+            return true;
+        };
+
+        let (dx, dy) = (target.0 - origin.0, target.1 - origin.1);
+        let distance = dx.hypot(dy);
+        if distance > view_range {
+            return false;
         }
 
+        let angle = dy.atan2(dx);
+        let offset = (angle - viewer.self_orientation as f32 + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+        if offset.abs() > half_view_angle {
+            return false;
+        }
+
+        self.physics_engine.cast_ray_against_walls(origin, angle, distance).is_none()
+    }
+
+    /// Lists the other living entities an entity can see.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity looking.
+    ///
+    /// # Returns
+    /// The visible entities with their (x, y) position and distance.
+    pub fn visible_entities(&self, handle: RigidBodyHandle) -> Vec<(&Entity, (f32, f32), f32)> {
+        let (Some(viewer), Some(origin)) = (self.entities.iter().find(|entity| entity.handle == handle), self.body_position(handle)) else {
+            return Vec::new();
+        };
+        let view_cone = self.view_cone();
+
+        self.entities.iter()
+            .filter(|entity| entity.handle != handle && entity.is_alive())
+            .filter_map(|entity| Some((entity, self.body_position(entity.handle)?)))
+            .filter(|&(_, position)| self.can_see(viewer, origin, position, view_cone))
+            .map(|(entity, position)| (entity, position, (position.0 - origin.0).hypot(position.1 - origin.1)))
+            .collect()
+    }
+
+    /// Lists the bullets an entity can see.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity looking.
+    ///
+    /// # Returns
+    /// The visible bullets.
+    pub fn visible_bullets(&self, handle: RigidBodyHandle) -> Vec<VisibleBullet> {
+        let (Some(viewer), Some(origin)) = (self.entities.iter().find(|entity| entity.handle == handle), self.body_position(handle)) else {
+            return Vec::new();
+        };
+        let view_cone = self.view_cone();
+
+        self.bullets.iter()
+            .filter_map(|bullet| Some((bullet.shooter, self.physics_engine.bodies.get(bullet.handle)?)))
+            .map(|(shooter, body)| (shooter, (body.translation().x, body.translation().y), (body.linvel().x, body.linvel().y)))
+            .filter(|&(_, position, _)| self.can_see(viewer, origin, position, view_cone))
+            .map(|(shooter, position, velocity)| VisibleBullet {
+                position,
+                velocity,
+                distance: (position.0 - origin.0).hypot(position.1 - origin.1),
+                shooter,
+            })
+            .collect()
+    }
+
+    /// Computes the area an entity can see, clipped by the walls and obstacles.
+    ///
+    /// # Parameters
+    /// - `entity`: The entity looking.
+    ///
+    /// # Returns
+    /// The outline of the visible area, or `None` if the fog of war is disabled.
+    pub fn view_outline(&self, entity: &Entity) -> Option<Vec<(f32, f32)>> {
+        let (view_range, half_view_angle) = self.view_cone()?;
+        let origin = self.body_position(entity.handle)?;
+        let segments = 32;
+
+        let mut outline = vec![origin];
+        for i in 0..=segments {
+            let angle = entity.self_orientation as f32 - half_view_angle + 2.0 * half_view_angle * i as f32 / segments as f32;
+            let distance = self.physics_engine.cast_ray_against_walls(origin, angle, view_range).unwrap_or(view_range);
+            outline.push((origin.0 + distance * angle.cos(), origin.1 + distance * angle.sin()));
+        }
+        outline.push(origin);
+        Some(outline)
+    }
+
+    /// Casts rays evenly spread around an entity, starting from its heading.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity casting the rays.
+    /// - `ray_count`: The number of rays.
+    /// - `max_range`: The maximum distance the rays travel.
+    ///
+    /// # Returns
+    /// The distance to the first hit of each ray, `max_range` for the rays hitting nothing.
+    pub fn lidar(&self, handle: RigidBodyHandle, ray_count: usize, max_range: f32) -> Vec<f32> {
+        (0..ray_count)
+            .map(|i| {
+                let relative_angle = i as f32 * std::f32::consts::TAU / ray_count as f32;
+                self.cast_ray(handle, relative_angle, max_range)
+                    .map_or(max_range, |(distance, _)| distance)
+            })
+            .collect()
+    }
+
+    /// Makes an entity shoot with its weapon in the direction of its gun turret.
+    ///
+    /// Each pellet of the weapon is fired in a random direction within the spread of the weapon.
+    ///
+    /// # Parameters
+    /// - `shooter_index`: The index of the entity that is shooting.
+    ///
+    /// # Returns
+    /// `Ok(())` if the weapon fired, or the reason why the entity cannot fire.
+    ///
+    /// # Panics
+    /// If `shooter_index` is not the index of an entity.
+    pub fn shoot_ball(&mut self, shooter_index: usize) -> Result<(), ShotError> {
         let shooter = &self.entities[shooter_index];
-        if shooter.last_shot.elapsed().as_secs_f64() < 1.0 {
-            return;
+        let weapon = shooter.weapon.weapon();
+        if !shooter.is_alive() {
+            return Err(ShotError::Eliminated);
+        }
+        if shooter.penalty_until.is_some_and(|penalty_until| Instant::now() < penalty_until) {
+            return Err(ShotError::Penalized);
+        }
+        if shooter.reload_started.is_some() {
+            return Err(ShotError::Reloading);
+        }
+        if shooter.ammo == 0 {
+            return Err(ShotError::NoAmmo);
+        }
+        if shooter.overheated {
+            return Err(ShotError::Overheated);
+        }
+        if shooter.last_shot.elapsed() < shooter.cooldown() {
+            return Err(ShotError::CoolingDown);
         }
 
-        let bullet = Bullet::new(
-            shooter.handle,
-            &mut self.physics_engine,
-            500.0,  // speed
-            5.0,     // radius
-        );
+        let mut rng = rand::thread_rng();
+        for _ in 0..weapon.pellets {
+            let deviation = if weapon.spread > 0.0 {
+                rng.gen_range(-weapon.spread / 2.0..=weapon.spread / 2.0)
+            } else {
+                0.0
+            };
+            let angle = shooter.gun_orientation as f32 + deviation;
+            let mut bullet = Bullet::new(shooter.handle, &mut self.physics_engine, angle, weapon);
+            bullet.damage *= shooter.damage_multiplier();
+            self.bullets.push(bullet);
+        }
 
-        self.bullets.push(bullet);
-        self.entities[shooter_index].last_shot = Instant::now();
+        let shooter = &mut self.entities[shooter_index];
+        shooter.last_shot = Instant::now();
+        shooter.ammo -= 1;
+        shooter.heat += weapon.heat_per_shot;
+        if shooter.heat >= 1.0 {
+            shooter.overheated = true;
+        }
+        Ok(())
     }
 
     /// Advances the simulation by one step.
     pub fn step(&mut self) {
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.last_step).as_secs_f32();
+        self.last_step = now;
+
+        self.sync_arena_size();
+        self.sync_movement_limits();
+        let physics_delta_time = self.physics_engine.integration_parameters.dt;
+        for entity in &mut self.entities {
+            entity.update_movement(&mut self.physics_engine.bodies[entity.handle], physics_delta_time);
+            entity.update_turret(delta_time);
+            entity.update_weapon(delta_time);
+            entity.update_effects();
+        }
         self.physics_engine.step();
         self.handle_collisions();
+        self.collect_pickups();
         self.remove_out_of_bounds_bullets();
         self.remove_expired_bullets();
+        self.update_obstacles();
+        self.spawn_pickups();
+        self.update_zones(delta_time);
+        self.update_eliminations();
+    }
+
+    /// Takes the entities eliminated during the step out of play, and respawns the ones whose respawn
+    /// delay has elapsed when the game mode allows it.
+    fn update_eliminations(&mut self) {
+        let respawns = self.game_mode.respawns();
+        let mut respawning = Vec::new();
+        for (index, entity) in self.entities.iter_mut().enumerate() {
+            match entity.eliminated_at {
+                None if !entity.is_alive() => {
+                    entity.eliminated_at = Some(Instant::now());
+                    entity.set_collisions_enabled(&mut self.physics_engine, false);
+                }
+                Some(eliminated_at) if respawns && eliminated_at.elapsed().as_secs_f32() >= AppDefines::RESPAWN_DELAY => {
+                    respawning.push(index);
+                }
+                _ => {}
+            }
+        }
+
+        for index in respawning {
+            let position = self.spawn_position();
+            let entity = &mut self.entities[index];
+            entity.health = AppDefines::ENTITY_MAX_HEALTH;
+            entity.eliminated_at = None;
+            entity.target_speed = 0.0;
+            entity.refill_weapon();
+            entity.effects.clear();
+            (entity.x, entity.y) = position;
+            self.physics_engine.bodies[entity.handle].set_translation(vector![position.0, position.1], true);
+            entity.set_collisions_enabled(&mut self.physics_engine, true);
+        }
+    }
+
+    /// Resizes the arena if the dimensions in the server settings changed.
+    fn sync_arena_size(&mut self) {
+        let (arena_width, arena_height) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.arena_width, settings.arena_height)
+        };
+        if arena_width != self.arena_width || arena_height != self.arena_height {
+            self.set_arena_size(arena_width, arena_height);
+        }
+    }
+
+    /// Applies the movement limits of the server settings to every entity, so changes made while the
+    /// game runs reach the entities already in the arena.
+    fn sync_movement_limits(&mut self) {
+        let (max_speed, acceleration, turn_rate) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.max_speed, settings.acceleration, (settings.turn_rate as f64).to_radians())
+        };
+        for entity in &mut self.entities {
+            entity.max_speed = max_speed;
+            entity.acceleration = acceleration;
+            entity.turn_rate = turn_rate;
+        }
+    }
+
+    /// Resizes the arena, rebuilding its boundaries, zones and map.
+    ///
+    /// The simulation is not reset: the entities keep their score and health and are only repositioned.
+    ///
+    /// # Parameters
+    /// - `arena_width`: The new width of the arena.
+    /// - `arena_height`: The new height of the arena.
+    pub fn set_arena_size(&mut self, arena_width: f32, arena_height: f32) {
+        self.arena_width = arena_width;
+        self.arena_height = arena_height;
+        self.physics_engine.setup_boundaries(arena_width, arena_height);
+
+        // The zones of a loaded map do not fit the new arena, the default ones are recentered instead
+        self.map_control_zone = None;
+        self.map_safe_zone = None;
+        self.recenter_zones();
+        self.generate_map();
+    }
+
+    /// Moves the zones of the current game mode to the center of the arena, with their default size.
+    ///
+    /// The owner of the control zone and the shrinking progress of the safe zone are kept.
+    fn recenter_zones(&mut self) {
+        let center = (self.arena_width / 2.0, self.arena_height / 2.0);
+        if let Some(control_zone) = &mut self.control_zone {
+            control_zone.center = center;
+            control_zone.radius = AppDefines::CONTROL_ZONE_RADIUS;
+        }
+        if let Some(safe_zone) = &mut self.safe_zone {
+            safe_zone.center = center;
+            safe_zone.initial_radius = center.0.hypot(center.1);
+            safe_zone.min_radius = AppDefines::SAFE_ZONE_MIN_RADIUS;
+            safe_zone.update();
+        }
+    }
+
+    /// Changes the game mode, sets up its zones and resets the simulation.
+    ///
+    /// # Parameters
+    /// - `game_mode`: The new game mode.
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        let center = (self.arena_width / 2.0, self.arena_height / 2.0);
+
+        self.game_mode = game_mode;
+        self.control_zone = None;
+        self.safe_zone = None;
+        match game_mode {
+            GameMode::FreeForAll => {}
+            GameMode::KingOfTheHill => {
+                self.control_zone = Some(match &self.map_control_zone {
+                    Some(zone) => ControlZone::new((zone.x, zone.y), zone.radius),
+                    None => ControlZone::new(center, AppDefines::CONTROL_ZONE_RADIUS),
+                });
+            }
+            GameMode::BattleRoyale => {
+                let shrink_duration = Duration::from_secs(AppDefines::SAFE_ZONE_SHRINK_DURATION);
+                self.safe_zone = Some(match &self.map_safe_zone {
+                    Some(zone) => SafeZone::new((zone.x, zone.y), zone.initial_radius, zone.min_radius, shrink_duration),
+                    // The safe zone starts by covering the whole arena
+                    None => SafeZone::new(center, center.0.hypot(center.1), AppDefines::SAFE_ZONE_MIN_RADIUS, shrink_duration),
+                });
+            }
+        }
+
+        self.reset_simulation();
+    }
+
+    /// Updates the zones of the current game mode.
+    ///
+    /// # Parameters
+    /// - `delta_time`: The time in seconds elapsed since the previous step.
+    fn update_zones(&mut self, delta_time: f32) {
+        if let Some(control_zone) = &mut self.control_zone {
+            let occupants: Vec<usize> = self.entities.iter()
+                .enumerate()
+                .filter(|(_, entity)| {
+                    let position = self.physics_engine.bodies[entity.handle].translation();
+                    entity.is_alive() && control_zone.contains(position.x, position.y)
+                })
+                .map(|(index, _)| index)
+                .collect();
+
+            // Points are only awarded while a single entity holds the zone
+            if let [owner_index] = occupants[..] {
+                let owner = self.entities[owner_index].handle;
+                if control_zone.owner != Some(owner) {
+                    control_zone.owner = Some(owner);
+                    control_zone.last_award = Instant::now();
+                } else if control_zone.last_award.elapsed().as_secs_f32() >= AppDefines::CONTROL_ZONE_AWARD_INTERVAL {
+                    self.entities[owner_index].score += 1;
+                    control_zone.last_award = Instant::now();
+                }
+            } else {
+                control_zone.owner = None;
+            }
+        }
+
+        if let Some(safe_zone) = &mut self.safe_zone {
+            safe_zone.update();
+            for entity in &mut self.entities {
+                let position = self.physics_engine.bodies[entity.handle].translation();
+                if entity.is_alive() && !safe_zone.contains(position.x, position.y) {
+                    entity.health = (entity.health - AppDefines::SAFE_ZONE_DAMAGE_PER_SECOND * delta_time).max(0.0);
+                }
+            }
+        }
+    }
+
+    /// Returns the name of the entity currently holding the control zone.
+    ///
+    /// # Returns
+    /// The name of the owner, or `None` if there is no control zone or nobody holds it alone.
+    pub fn control_zone_owner(&self) -> Option<&str> {
+        let owner = self.control_zone.as_ref()?.owner?;
+        self.entities.iter()
+            .find(|entity| entity.handle == owner)
+            .map(|entity| entity.name.as_str())
     }
 
     /// Handles collisions between entities and bullets.
     fn handle_collisions(&mut self) {
         let mut bullet_indices_to_remove = Vec::new();
+        // Obstacles destroyed for good open new paths
+        let mut nav_grid_outdated = false;
 
-        for event in self.physics_engine.collision_events.drain(..) {
+        for event in std::mem::take(&mut self.physics_engine.collision_events) {
+            // Pickups are sensors collected by the entities, bullets go through them
+            if event.sensor() {
+                continue;
+            }
             if let CollisionEvent::Started(collider1, collider2, _) = event {
-                let body1 = self.physics_engine.colliders[collider1].parent();
-                let body2 = self.physics_engine.colliders[collider2].parent();
-
-                if let (Some(body1), Some(body2)) = (body1, body2) {
-                    for (bullet_index, bullet) in self.bullets.iter().enumerate() {
-                        if bullet.handle == body1 || bullet.handle == body2 {
-                            bullet_indices_to_remove.push(bullet_index);
-
-                            // Update scores if the bullet hit an entity
-                            if let Some(entity_index) = self.entities.iter().position(|e| e.handle == body1 || e.handle == body2) {
-                                if bullet.shooter != self.entities[entity_index].handle {
-                                    let shooter_index = self.entities.iter().position(|e| e.handle == bullet.shooter).unwrap();
-                                    self.entities[shooter_index].score += 1;
-                                }
-                            }
+                let body1 = self.physics_engine.colliders.get(collider1).and_then(|collider| collider.parent());
+                let body2 = self.physics_engine.colliders.get(collider2).and_then(|collider| collider.parent());
 
-                            break;
+                let Some(bullet_index) = self.bullets.iter()
+                    .position(|bullet| Some(bullet.handle) == body1 || Some(bullet.handle) == body2) else {
+                    continue;
+                };
+                let (other_collider, other_body) = if Some(self.bullets[bullet_index].handle) == body1 {
+                    (collider2, body2)
+                } else {
+                    (collider1, body1)
+                };
+                let shooter = self.bullets[bullet_index].shooter;
+                let other_bullet_index = self.bullets.iter().position(|other| Some(other.handle) == other_body);
+                let bullet = &mut self.bullets[bullet_index];
+
+                if let Some(obstacle) = self.obstacles.iter_mut().find(|obstacle| obstacle.collider_handle == other_collider) {
+                    // Bullets damage destructible obstacles, and are destroyed by them and by absorbing obstacles
+                    obstacle.take_damage(&mut self.physics_engine, bullet.damage);
+                    nav_grid_outdated |= !obstacle.blocks_paths();
+                    if obstacle.max_health.is_some() || obstacle.material == ObstacleMaterial::Absorbing || !bullet.bounce() {
+                        bullet_indices_to_remove.push(bullet_index);
+                    }
+                } else if self.physics_engine.boundary_handles.contains(&other_collider) {
+                    if !bullet.bounce() {
+                        bullet_indices_to_remove.push(bullet_index);
+                    }
+                } else if let Some(other_bullet_index) = other_bullet_index {
+                    // Bullets colliding with each other are both destroyed, unless they were fired by the same entity
+                    if self.bullets[other_bullet_index].shooter != shooter {
+                        bullet_indices_to_remove.push(bullet_index);
+                        bullet_indices_to_remove.push(other_bullet_index);
+                    }
+                } else if let Some(entity_index) = self.entities.iter().position(|entity| Some(entity.handle) == other_body) {
+                    let damage = bullet.damage;
+                    // A bullet never hits its own shooter, and goes through eliminated entities
+                    let target = &self.entities[entity_index];
+                    if target.handle == shooter || !target.is_alive() {
+                        continue;
+                    }
+                    bullet_indices_to_remove.push(bullet_index);
+
+                    // Damage the entity unless it is shielded, the shooter scoring a point if it is eliminated
+                    let target = &mut self.entities[entity_index];
+                    if !target.has_effect(PickupKind::Shield) {
+                        target.health = (target.health - damage).max(0.0);
+                        if !target.is_alive() && self.game_mode.scores_eliminations() {
+                            // The shooter may have left the game since it fired
+                            if let Some(shooter) = self.entities.iter_mut().find(|e| e.handle == shooter) {
+                                shooter.score += 1;
+                            }
                         }
                     }
                 }
@@ -115,9 +745,14 @@ impl GameLogic {
 
         // Remove bullets based on collected indices
         bullet_indices_to_remove.sort_unstable_by(|a, b| b.cmp(a));
+        bullet_indices_to_remove.dedup();
         for &index in &bullet_indices_to_remove {
             self.remove_bullet(index);
         }
+
+        if nav_grid_outdated {
+            self.rebuild_nav_grid();
+        }
     }
 
     /// Removes a bullet from the game.
@@ -138,12 +773,11 @@ impl GameLogic {
 
     /// Removes bullets that are out of bounds.
     fn remove_out_of_bounds_bullets(&mut self) {
-        let bounds = 1200.0;
         let mut bullet_indices_to_remove = Vec::new();
 
         for (index, bullet) in self.bullets.iter().enumerate() {
             let position = self.physics_engine.bodies[bullet.handle].translation();
-            if position.x < 0.0 || position.x > bounds || position.y < 0.0 || position.y > bounds {
+            if position.x < 0.0 || position.x > self.arena_width || position.y < 0.0 || position.y > self.arena_height {
                 bullet_indices_to_remove.push(index);
             }
         }
@@ -156,11 +790,10 @@ impl GameLogic {
 
     /// Removes bullets that have expired.
     fn remove_expired_bullets(&mut self) {
-        let now = Instant::now();
         let mut bullet_indices_to_remove = Vec::new();
 
         for (index, bullet) in self.bullets.iter().enumerate() {
-            if now.duration_since(bullet.created_at).as_secs() >= 2 {
+            if bullet.is_expired() {
                 bullet_indices_to_remove.push(index);
             }
         }
@@ -175,6 +808,20 @@ impl GameLogic {
     pub fn reset_simulation(&mut self) {
         for entity in &mut self.entities {
             entity.score = 0;
+            entity.health = AppDefines::ENTITY_MAX_HEALTH;
+            entity.refill_weapon();
+            entity.effects.clear();
+            entity.eliminated_at = None;
+            entity.set_collisions_enabled(&mut self.physics_engine, true);
+        }
+        self.remove_all_pickups();
+
+        // Restart the zones of the current game mode
+        if let Some(control_zone) = &mut self.control_zone {
+            control_zone.owner = None;
+        }
+        if let Some(safe_zone) = &mut self.safe_zone {
+            safe_zone.restart();
         }
 
         // Delete all bullets
@@ -190,8 +837,110 @@ impl GameLogic {
         }
         self.bullets.clear();
 
-        // Reposition entities
+        // Reposition entities, then repair the destructible obstacles outside of their new positions
         self.reposition_entities();
+        self.physics_engine.refresh_queries();
+        for obstacle in &mut self.obstacles {
+            obstacle.repair(&mut self.physics_engine);
+        }
+        self.rebuild_nav_grid();
+    }
+
+    /// Gives the effect of the pickups touched by living entities to the first of them, and removes those pickups.
+    fn collect_pickups(&mut self) {
+        let mut index = 0;
+        while index < self.pickups.len() {
+            let collector = self.pickups[index].touching_bodies(&self.physics_engine).into_iter()
+                .find_map(|body| self.entities.iter().position(|entity| entity.handle == body && entity.is_alive()));
+            match collector {
+                Some(entity_index) => {
+                    let pickup = self.pickups.remove(index);
+                    self.entities[entity_index].apply_pickup(pickup.kind);
+                    self.remove_pickup_collider(&pickup);
+                }
+                None => index += 1,
+            }
+        }
+    }
+
+    /// Spawns a random pickup at a free position once the spawn interval of the server settings has elapsed.
+    fn spawn_pickups(&mut self) {
+        let (spawn_interval, max_count) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.pickup_spawn_interval, settings.pickup_max_count)
+        };
+        if spawn_interval <= 0.0 || self.pickups.len() >= max_count
+            || self.last_pickup_spawn.elapsed().as_secs_f32() < spawn_interval {
+            return;
+        }
+        self.last_pickup_spawn = Instant::now();
+
+        let mut rng = rand::thread_rng();
+        let margin = AppDefines::PICKUP_RADIUS + 10.0;
+        for _ in 0..AppDefines::PICKUP_SPAWN_ATTEMPTS {
+            let position = (
+                rng.gen_range(margin..self.arena_width - margin),
+                rng.gen_range(margin..self.arena_height - margin),
+            );
+            if self.physics_engine.is_area_free(position, AppDefines::PICKUP_RADIUS) {
+                let kind = PickupKind::ALL[rng.gen_range(0..PickupKind::ALL.len())];
+                self.pickups.push(Pickup::new(&mut self.physics_engine, kind, position));
+                return;
+            }
+        }
+    }
+
+    /// Removes the collider of a pickup from the physics engine.
+    ///
+    /// # Parameters
+    /// - `pickup`: The pickup removed from the game.
+    fn remove_pickup_collider(&mut self, pickup: &Pickup) {
+        self.physics_engine.colliders.remove(
+            pickup.collider_handle,
+            &mut self.physics_engine.islands,
+            &mut self.physics_engine.bodies,
+            true,
+        );
+    }
+
+    /// Removes all pickups from the game and restarts their spawn interval.
+    fn remove_all_pickups(&mut self) {
+        for pickup in std::mem::take(&mut self.pickups) {
+            self.remove_pickup_collider(&pickup);
+        }
+        self.last_pickup_spawn = Instant::now();
+    }
+
+    /// Lists the pickups an entity can see.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity looking.
+    ///
+    /// # Returns
+    /// The visible pickups with their distance.
+    pub fn visible_pickups(&self, handle: RigidBodyHandle) -> Vec<(&Pickup, f32)> {
+        let (Some(viewer), Some(origin)) = (self.entities.iter().find(|entity| entity.handle == handle), self.body_position(handle)) else {
+            return Vec::new();
+        };
+        let view_cone = self.view_cone();
+
+        self.pickups.iter()
+            .filter(|pickup| self.can_see(viewer, origin, pickup.position, view_cone))
+            .map(|pickup| (pickup, (pickup.position.0 - origin.0).hypot(pickup.position.1 - origin.1)))
+            .collect()
+    }
+
+    /// Brings back the destroyed obstacles whose regeneration delay has elapsed.
+    fn update_obstacles(&mut self) {
+        let mut nav_grid_outdated = false;
+        for obstacle in &mut self.obstacles {
+            let blocked_paths = obstacle.blocks_paths();
+            obstacle.update(&mut self.physics_engine);
+            nav_grid_outdated |= obstacle.blocks_paths() != blocked_paths;
+        }
+        if nav_grid_outdated {
+            self.rebuild_nav_grid();
+        }
     }
 
     /// Removes all obstacles from the game.
@@ -208,135 +957,447 @@ impl GameLogic {
     }
 
     /// Generates obstacles in the game.
-    fn generate_obstacles(&mut self) {
-        let mut rng = rand::thread_rng();
+    ///
+    /// The walls are built by the selected map generator, sized to the arena and using the obstacle
+    /// probability of the server settings, and its safe spawn points are kept for the entities.
+    /// The walls get the obstacle health and regeneration delay of the server settings.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the map.
+    fn generate_obstacles(&mut self, seed: u64) {
+        let (obstacle_probability, health, regeneration_delay) = {
+            let settings = self.settings.lock().unwrap();
+            (settings.obstacle_probability, settings.obstacle_health, settings.obstacle_regeneration_delay)
+        };
+        let map = self.map_generator.generate(seed, self.arena_width, self.arena_height, obstacle_probability);
 
-        for _ in 0..25 {
-            let random_x = rng.gen_range(10.0..1190.0) as f64;
-            let random_y = rng.gen_range(10.0..990.0) as f64;
+        for ((x, y), (half_width, half_height)) in map.walls {
+            self.add_obstacle(&ObstacleDefinition {
+                x,
+                y,
+                rotation: 0.0,
+                material: ObstacleMaterial::Solid,
+                health: (health > 0.0).then_some(health),
+                regeneration_delay: (regeneration_delay > 0.0).then_some(regeneration_delay),
+                shape: ObstacleShape::Rectangle { half_width, half_height },
+            });
+        }
 
-            let collider = ColliderBuilder::cuboid(10.0, 10.0)
-                .translation(vector![random_x as f32, random_y as f32])
-                .build();
-            let collider_handle = self.physics_engine.colliders.insert(collider);
+        self.spawn_points = map.spawn_points;
+    }
 
-            self.obstacles.push(Obstacle::new((random_x, random_y), collider_handle));
+    /// Adds an obstacle to the game.
+    ///
+    /// Polygons whose points do not form a convex hull, and obstacles whose regeneration delay is not
+    /// a valid duration, are ignored.
+    ///
+    /// # Parameters
+    /// - `definition`: The position, rotation, shape, material and hit points of the obstacle.
+    fn add_obstacle(&mut self, definition: &ObstacleDefinition) {
+        let Ok(regeneration_delay) = definition.regeneration_delay.map(Duration::try_from_secs_f32).transpose() else {
+            return;
+        };
+        let Some(mut obstacle) = Obstacle::new(
+            &mut self.physics_engine,
+            (definition.x, definition.y),
+            definition.rotation,
+            definition.shape.clone(),
+            definition.material,
+        ) else {
+            return;
+        };
+
+        if let Some(health) = definition.health {
+            obstacle = obstacle.with_health(&mut self.physics_engine, health, regeneration_delay);
         }
+        self.obstacles.push(obstacle);
     }
 
-    /// Repositions entities to new random locations.
-    fn reposition_entities(&mut self) {
+    /// Picks a random spawn point for a new entity.
+    ///
+    /// # Returns
+    /// A spawn point of the map, or a random position in the arena if the map has none.
+    fn spawn_position(&self) -> (f32, f32) {
         let mut rng = rand::thread_rng();
-        for entity in &mut self.entities {
-            let random_x = rng.gen_range(10.0..1190.0);
-            let random_y = rng.gen_range(10.0..990.0);
+        if self.spawn_points.is_empty() {
+            (
+                rng.gen_range(10.0..self.arena_width - 10.0),
+                rng.gen_range(10.0..self.arena_height - 10.0),
+            )
+        } else {
+            self.spawn_points[rng.gen_range(0..self.spawn_points.len())]
+        }
+    }
+
+    /// Repositions entities to the spawn points of the map.
+    ///
+    /// Entities are dealt the spawn points in order, so they do not overlap as long as there are enough of them.
+    fn reposition_entities(&mut self) {
+        for index in 0..self.entities.len() {
+            let (x, y) = if self.spawn_points.is_empty() {
+                self.spawn_position()
+            } else {
+                self.spawn_points[index % self.spawn_points.len()]
+            };
+
+            let entity = &mut self.entities[index];
+            entity.target_speed = 0.0;
             let body = &mut self.physics_engine.bodies[entity.handle];
-            body.set_translation(vector![random_x, random_y], true);
+            body.set_translation(vector![x, y], true);
+            body.set_linvel(vector![0.0, 0.0], true);
 
             // Update entity's internal position
-            entity.x = random_x;
-            entity.y = random_y;
+            entity.x = x;
+            entity.y = y;
         }
     }
 
-    /// Generates a new map with obstacles and repositions entities.
+    /// Generates a new map from a random seed.
     pub fn generate_map(&mut self) {
-        // Remove all obstacles
+        // Seeds are kept within u32 so they can be typed back exactly in the UI
+        let seed = rand::thread_rng().gen::<u32>() as u64;
+        self.generate_map_with_seed(seed);
+    }
+
+    /// Generates the map of a given seed and repositions entities.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the map. The same seed and settings always produce the same obstacles.
+    pub fn generate_map_with_seed(&mut self, seed: u64) {
+        self.map_seed = seed;
+
+        // Generated maps use the default zones
+        let had_map_zones = self.map_control_zone.is_some() || self.map_safe_zone.is_some();
+        self.map_control_zone = None;
+        self.map_safe_zone = None;
+        if had_map_zones {
+            self.set_game_mode(self.game_mode);
+        }
+
+        // Remove all obstacles, and the pickups which could now be inside the new ones
         self.remove_all_obstacles();
+        self.remove_all_pickups();
 
         // Generate new obstacles
-        self.generate_obstacles();
+        self.generate_obstacles(seed);
+        self.rebuild_nav_grid();
 
         // Reposition entities
         self.reposition_entities();
     }
 
+    /// Replaces the arena with a map loaded from a file.
+    ///
+    /// The arena is resized to the map dimensions, which are also written to the server settings,
+    /// then the obstacles, spawn points and zones of the map are set up and the simulation is reset.
+    ///
+    /// # Parameters
+    /// - `map`: The map to load.
+    pub fn load_map(&mut self, map: &MapFile) {
+        {
+            let mut settings = self.settings.lock().unwrap();
+            settings.arena_width = map.arena_width;
+            settings.arena_height = map.arena_height;
+        }
+        self.arena_width = map.arena_width;
+        self.arena_height = map.arena_height;
+        self.physics_engine.setup_boundaries(map.arena_width, map.arena_height);
+
+        self.remove_all_obstacles();
+        for obstacle in &map.obstacles {
+            self.add_obstacle(obstacle);
+        }
+        self.rebuild_nav_grid();
+        self.spawn_points = map.spawn_points.clone();
+        self.map_control_zone = map.control_zone.clone();
+        self.map_safe_zone = map.safe_zone.clone();
+
+        // Sets up the zones of the map and repositions entities on its spawn points
+        self.set_game_mode(self.game_mode);
+    }
+
+    /// Rebuilds the navigation grid from the current obstacles and arena dimensions.
+    fn rebuild_nav_grid(&mut self) {
+        self.nav_grid = Arc::new(NavGrid::build(
+            &self.physics_engine,
+            &self.obstacles,
+            self.arena_width,
+            self.arena_height,
+            AppDefines::NAV_CELL_SIZE,
+            AppDefines::NAV_CLEARANCE,
+        ));
+    }
+
+    /// Plans a path from an entity to a position, around the obstacles.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the rigid body of the entity.
+    /// - `target`: The (x, y) position to reach.
+    ///
+    /// # Returns
+    /// The waypoints leading to the position, ending with the position itself, or `None` if the position
+    /// cannot be reached or the entity is not in the game.
+    pub fn find_path(&self, handle: RigidBodyHandle, target: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        let position = self.physics_engine.bodies.get(handle)?.translation();
+        self.nav_grid.find_path((position.x, position.y), target)
+    }
+
+    /// Exports the current map so it can be saved to a file.
+    ///
+    /// # Returns
+    /// A `MapFile` describing the arena, obstacles, spawn points and zones of the current map.
+    pub fn to_map_file(&self) -> MapFile {
+        let center = (self.arena_width / 2.0, self.arena_height / 2.0);
+        MapFile {
+            arena_width: self.arena_width,
+            arena_height: self.arena_height,
+            obstacles: self.obstacles.iter()
+                .map(|obstacle| ObstacleDefinition {
+                    x: obstacle.position.0 as f32,
+                    y: obstacle.position.1 as f32,
+                    rotation: obstacle.rotation,
+                    material: obstacle.material,
+                    health: obstacle.max_health,
+                    regeneration_delay: obstacle.regeneration_delay.map(|delay| delay.as_secs_f32()),
+                    shape: obstacle.shape.clone(),
+                })
+                .collect(),
+            spawn_points: self.spawn_points.clone(),
+            control_zone: Some(self.map_control_zone.clone().unwrap_or(ControlZoneDefinition {
+                x: center.0,
+                y: center.1,
+                radius: AppDefines::CONTROL_ZONE_RADIUS,
+            })),
+            safe_zone: Some(self.map_safe_zone.clone().unwrap_or(SafeZoneDefinition {
+                x: center.0,
+                y: center.1,
+                initial_radius: center.0.hypot(center.1),
+                min_radius: AppDefines::SAFE_ZONE_MIN_RADIUS,
+            })),
+        }
+    }
+
     /// Adds a new AI-controlled entity to the game.
     ///
     /// # Parameters
     /// - `name`: The name of the AI entity.
     pub fn add_ai(&mut self, name: String) {
-        let entity = Entity::new(name, &mut self.physics_engine, true);
-        self.entities.push(entity);
+        self.spawn_entity(name, true);
+    }
+
+    /// Adds a new AI-controlled entity to the game, driven by a given brain.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the AI entity.
+    /// - `brain`: The brain deciding what the entity does, such as a bot script.
+    pub fn add_ai_with_brain(&mut self, name: String, brain: Box<dyn BotBrain>) {
+        let handle = self.spawn_entity(name, true);
+        if let Some(entity) = self.entity_mut(handle) {
+            entity.brain = Some(brain);
+        }
+    }
+
+    /// Builds what an entity knows about the world, for its AI brain.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the entity.
+    ///
+    /// # Returns
+    /// The snapshot of the world as seen by the entity.
+    fn snapshot(&self, index: usize) -> WorldSnapshot {
+        let entity = &self.entities[index];
+        let body = &self.physics_engine.bodies[entity.handle];
+
+        WorldSnapshot {
+            position: (body.translation().x, body.translation().y),
+            heading: entity.self_orientation,
+            gun_orientation: entity.gun_orientation,
+            health: entity.health,
+            weapon: entity.weapon,
+            ammo: entity.ammo,
+            reloading: entity.reload_started.is_some(),
+            max_speed: entity.max_speed * entity.speed_multiplier(),
+            arena_size: (self.arena_width, self.arena_height),
+            nav_grid: Arc::clone(&self.nav_grid),
+            bots: self.visible_entities(entity.handle).into_iter()
+                .map(|(other, position, distance)| {
+                    let velocity = self.physics_engine.bodies[other.handle].linvel();
+                    VisibleBot {
+                        handle: other.handle,
+                        name: other.name.clone(),
+                        position,
+                        velocity: (velocity.x, velocity.y),
+                        distance,
+                    }
+                })
+                .collect(),
+            bullets: self.visible_bullets(entity.handle).into_iter()
+                .filter(|bullet| bullet.shooter != entity.handle)
+                .collect(),
+            pickups: self.visible_pickups(entity.handle).into_iter()
+                .map(|(pickup, _)| (pickup.kind, pickup.position))
+                .collect(),
+        }
+    }
+
+    /// Applies the actions decided by the brain of an entity, as if a network bot had sent them.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the entity.
+    /// - `actions`: The actions of the entity.
+    fn apply_actions(&mut self, index: usize, actions: BotActions) {
+        let entity = &mut self.entities[index];
+        if let Some(weapon) = actions.weapon.filter(|&weapon| weapon != entity.weapon) {
+            entity.switch_weapon(weapon);
+        }
+        if let Some(turn) = actions.turn {
+            entity.target_orientation = turn.rem_euclid(std::f64::consts::TAU);
+        }
+        if let Some(speed) = actions.speed {
+            entity.target_speed = speed;
+        }
+        if let Some(aim) = actions.aim {
+            entity.target_gun_orientation = aim.rem_euclid(std::f64::consts::TAU);
+        }
+        if actions.reload {
+            entity.reload();
+        }
+        if actions.fire {
+            // Like a network bot, the AI learns why it could not fire from the next snapshot
+            let _ = self.shoot_ball(index);
+        }
     }
 
     /// Updates AI entities in the game.
+    ///
+    /// Each living AI entity lets its brain observe the world and decide its actions.
     pub fn update_ai(&mut self) {
-        let mut rng = rand::thread_rng();
+        for index in 0..self.entities.len() {
+            if !self.entities[index].is_alive() {
+                continue;
+            }
+            // The brain is taken out of the entity while it looks at the world the entity is part of
+            let Some(mut brain) = self.entities[index].brain.take() else {
+                continue;
+            };
+            let actions = brain.think(&self.snapshot(index));
+            self.entities[index].brain = Some(brain);
+            self.apply_actions(index, actions);
+        }
+    }
+}
 
-        // Gather data first
-        let updates: Vec<(RigidBodyHandle, Vector<f32>, Vector<f32>)> = self.entities.iter_mut()
-            .filter_map(|entity| {
-                if entity.is_ai {
-                    // Randomly change the target position every few seconds
-                    if entity.last_shot.elapsed().as_secs_f32() > rng.gen_range(1.0..3.0) {
-                        entity.target_x = rng.gen_range(10.0..1190.0);
-                        entity.target_y = rng.gen_range(10.0..990.0);
-                        entity.last_shot = Instant::now();
-
-                        // Change the gun orientation randomly at each target change
-                        entity.gun_orientation = rng.gen_range(0.0..std::f64::consts::TAU);
-                    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapons::WeaponKind;
 
-                    // Move towards the target position
-                    let current_pos = self.physics_engine.bodies[entity.handle].translation().clone();
-                    let target_pos = vector![entity.target_x as f32, entity.target_y as f32];
-                    let direction = target_pos - current_pos;
-                    let distance = direction.norm();
+    /// Creates an empty arena with a single entity holding a loaded weapon.
+    fn arena_with_shooter(weapon: WeaponKind) -> GameLogic {
+        let mut game_logic = GameLogic::new(Arc::new(Mutex::new(ServerSettings::new())));
+        game_logic.add_entity("shooter".to_string());
+        let shooter = &mut game_logic.entities[0];
+        shooter.weapon = weapon;
+        shooter.refill_weapon();
+        // Allow the first shot right away
+        shooter.last_shot = Instant::now() - Duration::from_secs(60);
+        game_logic
+    }
 
-                    if distance > 1.0 {
-                        let movement = direction.normalize() * 1.0; // adjust the speed here
-                        return Some((entity.handle, current_pos, movement));
-                    }
-                }
-                None
-            }).collect();
-
-        // Apply updates
-        for (handle, current_pos, movement) in updates {
-            self.physics_engine.bodies[handle].set_next_kinematic_position(
-                Isometry::translation(
-                    current_pos.x + movement.x,
-                    current_pos.y + movement.y,
-                ),
-            );
+    #[test]
+    fn shot_fires_every_pellet_and_uses_one_round() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Shotgun);
+        assert_eq!(game_logic.shoot_ball(0), Ok(()));
+        assert_eq!(game_logic.bullets.len(), WeaponKind::Shotgun.weapon().pellets as usize);
+        assert_eq!(game_logic.entities[0].ammo, WeaponKind::Shotgun.weapon().magazine - 1);
+    }
+
+    #[test]
+    fn second_shot_waits_for_the_cooldown() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Pistol);
+        assert_eq!(game_logic.shoot_ball(0), Ok(()));
+        assert_eq!(game_logic.shoot_ball(0), Err(ShotError::CoolingDown));
+        assert_eq!(game_logic.bullets.len(), 1);
+    }
+
+    #[test]
+    fn eliminated_entity_cannot_fire() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Pistol);
+        game_logic.entities[0].health = 0.0;
+        assert_eq!(game_logic.shoot_ball(0), Err(ShotError::Eliminated));
+    }
+
+    #[test]
+    fn bullets_without_bounces_do_not_bounce() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Rifle);
+        game_logic.shoot_ball(0).unwrap();
+        let collider = game_logic.physics_engine.bodies[game_logic.bullets[0].handle].colliders()[0];
+        assert_eq!(game_logic.physics_engine.colliders[collider].restitution(), 0.0);
+    }
+
+    #[test]
+    fn eliminated_entity_respawns_only_in_modes_with_respawns() {
+        for (game_mode, respawns) in [(GameMode::FreeForAll, true), (GameMode::BattleRoyale, false)] {
+            let mut game_logic = arena_with_shooter(WeaponKind::Pistol);
+            game_logic.set_game_mode(game_mode);
+            game_logic.entities[0].health = 0.0;
+            game_logic.step();
+            assert!(game_logic.entities[0].eliminated_at.is_some());
+
+            game_logic.entities[0].eliminated_at = Some(Instant::now() - Duration::from_secs(60));
+            game_logic.step();
+            assert_eq!(game_logic.entities[0].is_alive(), respawns, "{:?}", game_mode);
         }
+    }
 
-        // Update entity positions and handle shooting
-        for entity in &mut self.entities {
-            if entity.is_ai {
-                let current_pos = self.physics_engine.bodies[entity.handle].translation();
-                let target_pos = vector![entity.target_x, entity.target_y];
-                let direction = target_pos - current_pos;
-                entity.self_orientation = direction.y.atan2(direction.x) as f64;
-
-                // Randomly shoot a bullet every 500ms
-                if entity.last_shot.elapsed().as_millis() >= 500 {
-                    // Change the gun orientation randomly at each shoot
-                    let random_angle = rng.gen_range(0.0..std::f64::consts::TAU);
-                    let (sin, cos) = random_angle.sin_cos();
-
-                    let bullet_handle = self.physics_engine.bodies.insert(
-                        RigidBodyBuilder::dynamic()
-                            .translation(*current_pos)
-                            .linvel(vector![cos as f32 * 500.0, sin as f32 * 500.0])
-                            .build(),
-                    );
-                    let bullet_collider = ColliderBuilder::ball(5.0)
-                        .restitution(1.0)
-                        .build();
-                    self.physics_engine.colliders.insert_with_parent(bullet_collider, bullet_handle, &mut self.physics_engine.bodies);
-
-                    let bullet = Bullet {
-                        handle: bullet_handle,
-                        shooter: entity.handle.clone(),
-                        created_at: Instant::now(),
-                    };
-
-                    self.bullets.push(bullet);
-                    entity.last_shot = Instant::now();
-                }
-            }
+    #[test]
+    fn continuous_fire_overheats_the_machine_gun() {
+        let mut game_logic = arena_with_shooter(WeaponKind::MachineGun);
+        let shots_to_overheat = (1.0 / WeaponKind::MachineGun.weapon().heat_per_shot).ceil() as usize;
+        for _ in 0..shots_to_overheat {
+            game_logic.entities[0].last_shot = Instant::now() - Duration::from_secs(60);
+            assert_eq!(game_logic.shoot_ball(0), Ok(()));
         }
+        game_logic.entities[0].last_shot = Instant::now() - Duration::from_secs(60);
+        assert_eq!(game_logic.shoot_ball(0), Err(ShotError::Overheated));
+    }
+
+    #[test]
+    fn empty_weapon_must_be_reloaded() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Rifle);
+        game_logic.entities[0].ammo = 0;
+        assert_eq!(game_logic.shoot_ball(0), Err(ShotError::NoAmmo));
+        game_logic.entities[0].reload();
+        assert_eq!(game_logic.shoot_ball(0), Err(ShotError::Reloading));
+    }
+
+    #[test]
+    fn obstacle_with_an_oversized_regeneration_delay_is_ignored() {
+        let mut game_logic = GameLogic::new(Arc::new(Mutex::new(ServerSettings::new())));
+        let colliders = game_logic.physics_engine.colliders.len();
+        let mut definition = ObstacleDefinition {
+            x: 100.0,
+            y: 100.0,
+            rotation: 0.0,
+            material: ObstacleMaterial::Solid,
+            health: Some(50.0),
+            regeneration_delay: Some(1e30),
+            shape: ObstacleShape::Circle { radius: 20.0 },
+        };
+        game_logic.add_obstacle(&definition);
+        assert!(game_logic.obstacles.is_empty());
+        assert_eq!(game_logic.physics_engine.colliders.len(), colliders);
+
+        definition.regeneration_delay = Some(10.0);
+        game_logic.add_obstacle(&definition);
+        assert_eq!(game_logic.obstacles[0].regeneration_delay, Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn movement_limits_follow_the_server_settings() {
+        let mut game_logic = arena_with_shooter(WeaponKind::Pistol);
+        game_logic.settings.lock().unwrap().max_speed = 42.0;
+        game_logic.step();
+        assert_eq!(game_logic.entities[0].max_speed, 42.0);
     }
 }
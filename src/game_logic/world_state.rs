@@ -0,0 +1,289 @@
+use serde::Serialize;
+use urss_protocol::Keywords;
+
+use crate::game_logic::GameLogic;
+
+/// Represents an entity in the world state.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityState {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    /// The heading of the body, in radians.
+    pub heading: f64,
+    /// The orientation of the gun turret, in radians.
+    pub gun: f64,
+    pub health: f32,
+    pub score: i32,
+    pub weapon: &'static str,
+    pub alive: bool,
+}
+
+/// Represents a bullet in the world state.
+#[derive(Debug, Clone, Serialize)]
+pub struct BulletState {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// The name of the entity that fired the bullet, empty if it left the game.
+    pub shooter: String,
+}
+
+/// Represents an obstacle in the world state.
+#[derive(Debug, Clone, Serialize)]
+pub struct ObstacleState {
+    pub x: f32,
+    pub y: f32,
+    /// The radius of a circle enclosing the obstacle.
+    pub radius: f32,
+    /// The hit points of the obstacle, `None` if it is indestructible.
+    pub health: Option<f32>,
+}
+
+impl ObstacleState {
+    /// Captures the current state of all the obstacles.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic to capture.
+    ///
+    /// # Returns
+    /// The state of every obstacle, in the order of the game logic.
+    pub fn capture_all(game_logic: &GameLogic) -> Vec<Self> {
+        game_logic.obstacles.iter()
+            .filter_map(|obstacle| {
+                let radius = game_logic.physics_engine.colliders.get(obstacle.collider_handle)?
+                    .shape()
+                    .compute_local_bounding_sphere()
+                    .radius;
+                Some(ObstacleState {
+                    x: obstacle.position.0 as f32,
+                    y: obstacle.position.1 as f32,
+                    radius,
+                    health: obstacle.max_health.map(|_| obstacle.health),
+                })
+            })
+            .collect()
+    }
+
+    /// Encodes the obstacle as fields of the text protocol.
+    ///
+    /// # Returns
+    /// `x`, `y`, `radius` and `health`, the health being `-1` if the obstacle is indestructible.
+    pub fn to_fields(&self) -> [String; 4] {
+        [
+            self.x.to_string(),
+            self.y.to_string(),
+            self.radius.to_string(),
+            self.health.unwrap_or(-1.0).to_string(),
+        ]
+    }
+}
+
+/// Represents a pickup in the world state.
+#[derive(Debug, Clone, Serialize)]
+pub struct PickupState {
+    pub kind: &'static str,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Represents the zone of the current game mode in the world state.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ZoneState {
+    /// The King of the Hill control zone, and the name of the entity holding it alone, if any.
+    Control { x: f32, y: f32, radius: f32, owner: Option<String> },
+    /// The Battle Royale safe zone.
+    Safe { x: f32, y: f32, radius: f32 },
+}
+
+impl ZoneState {
+    /// Captures the current state of the zone.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic to capture.
+    ///
+    /// # Returns
+    /// The control zone in King of the Hill mode, the safe zone in Battle Royale mode, `None` in the other modes.
+    pub fn capture(game_logic: &GameLogic) -> Option<Self> {
+        if let Some(control_zone) = &game_logic.control_zone {
+            Some(ZoneState::Control {
+                x: control_zone.center.0,
+                y: control_zone.center.1,
+                radius: control_zone.radius,
+                owner: game_logic.control_zone_owner().map(str::to_string),
+            })
+        } else {
+            game_logic.safe_zone.as_ref().map(|safe_zone| ZoneState::Safe {
+                x: safe_zone.center.0,
+                y: safe_zone.center.1,
+                radius: safe_zone.radius,
+            })
+        }
+    }
+
+    /// Encodes the zone as a reply of the text protocol.
+    ///
+    /// # Returns
+    /// `CONTROL=x=y=radius=owner`, the owner being `EMPTY` when nobody holds the zone alone, or `SAFE=x=y=radius`.
+    pub fn to_text(&self) -> String {
+        match self {
+            ZoneState::Control { x, y, radius, owner } => [
+                Keywords::CONTROL_ZONE_REPLY.to_string(),
+                x.to_string(),
+                y.to_string(),
+                radius.to_string(),
+                owner.clone().unwrap_or_else(|| Keywords::EMPTY_REPLY.to_string()),
+            ].join(Keywords::ARGUMENT_SEP),
+            ZoneState::Safe { x, y, radius } => [
+                Keywords::SAFE_ZONE_REPLY.to_string(),
+                x.to_string(),
+                y.to_string(),
+                radius.to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+        }
+    }
+}
+
+/// Represents everything happening in the arena at a given time, as streamed to spectators.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldState {
+    pub game_mode: &'static str,
+    pub arena_width: f32,
+    pub arena_height: f32,
+    pub entities: Vec<EntityState>,
+    pub bullets: Vec<BulletState>,
+    pub obstacles: Vec<ObstacleState>,
+    pub pickups: Vec<PickupState>,
+    pub zone: Option<ZoneState>,
+}
+
+impl WorldState {
+    /// Captures the current state of the game.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic to capture.
+    ///
+    /// # Returns
+    /// The state of the world, with no fog of war applied.
+    pub fn capture(game_logic: &GameLogic) -> Self {
+        let bodies = &game_logic.physics_engine.bodies;
+        let entities = game_logic.entities.iter()
+            .filter_map(|entity| {
+                let position = bodies.get(entity.handle)?.translation();
+                Some(EntityState {
+                    name: entity.name.clone(),
+                    x: position.x,
+                    y: position.y,
+                    heading: entity.self_orientation,
+                    gun: entity.gun_orientation,
+                    health: entity.health,
+                    score: entity.score,
+                    weapon: entity.weapon.name(),
+                    alive: entity.is_alive(),
+                })
+            })
+            .collect();
+
+        let bullets = game_logic.bullets.iter()
+            .filter_map(|bullet| {
+                let body = bodies.get(bullet.handle)?;
+                let shooter = game_logic.entities.iter()
+                    .find(|entity| entity.handle == bullet.shooter)
+                    .map(|entity| entity.name.clone())
+                    .unwrap_or_default();
+                Some(BulletState {
+                    x: body.translation().x,
+                    y: body.translation().y,
+                    vx: body.linvel().x,
+                    vy: body.linvel().y,
+                    shooter,
+                })
+            })
+            .collect();
+
+        let pickups = game_logic.pickups.iter()
+            .map(|pickup| PickupState { kind: pickup.kind.name(), x: pickup.position.0, y: pickup.position.1 })
+            .collect();
+
+        Self {
+            game_mode: game_logic.game_mode.name(),
+            arena_width: game_logic.arena_width,
+            arena_height: game_logic.arena_height,
+            entities,
+            bullets,
+            obstacles: ObstacleState::capture_all(game_logic),
+            pickups,
+            zone: ZoneState::capture(game_logic),
+        }
+    }
+
+    /// Encodes the state as one line of the text protocol.
+    ///
+    /// The line holds sections separated by `#`, each starting with its tag:
+    /// - `STATE=mode=width=height`
+    /// - `ENTITIES=count` then `name=x=y=heading=gun=health=score=weapon=alive` for each entity
+    /// - `BULLETS=count` then `x=y=vx=vy=shooter` for each bullet
+    /// - `OBSTACLES=count` then `x=y=radius=health` for each obstacle, the health being `-1` if indestructible
+    /// - `PICKUPS=count` then `kind=x=y` for each pickup
+    /// - `ZONE` followed by the reply to a zone query
+    ///
+    /// # Returns
+    /// The encoded state, without line ending.
+    pub fn to_line(&self) -> String {
+        let section = |tag: &str, count: usize, values: Vec<String>| {
+            std::iter::once(tag.to_string())
+                .chain(std::iter::once(count.to_string()))
+                .chain(values)
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP)
+        };
+
+        let header = [
+            Keywords::STATE_SECTION.to_string(),
+            self.game_mode.to_string(),
+            self.arena_width.to_string(),
+            self.arena_height.to_string(),
+        ].join(Keywords::ARGUMENT_SEP);
+
+        let entities = section(Keywords::ENTITIES_SECTION, self.entities.len(), self.entities.iter()
+            .flat_map(|entity| [
+                entity.name.clone(),
+                entity.x.to_string(),
+                entity.y.to_string(),
+                entity.heading.to_string(),
+                entity.gun.to_string(),
+                entity.health.to_string(),
+                entity.score.to_string(),
+                entity.weapon.to_string(),
+                (entity.alive as u8).to_string(),
+            ])
+            .collect());
+
+        let bullets = section(Keywords::BULLETS_SECTION, self.bullets.len(), self.bullets.iter()
+            .flat_map(|bullet| [
+                bullet.x.to_string(),
+                bullet.y.to_string(),
+                bullet.vx.to_string(),
+                bullet.vy.to_string(),
+                bullet.shooter.clone(),
+            ])
+            .collect());
+
+        let obstacles = section(Keywords::OBSTACLES_SECTION, self.obstacles.len(), self.obstacles.iter()
+            .flat_map(ObstacleState::to_fields)
+            .collect());
+
+        let pickups = section(Keywords::PICKUPS_SECTION, self.pickups.len(), self.pickups.iter()
+            .flat_map(|pickup| [pickup.kind.to_string(), pickup.x.to_string(), pickup.y.to_string()])
+            .collect());
+
+        let zone = [
+            Keywords::ZONE_SECTION.to_string(),
+            self.zone.as_ref().map_or_else(|| Keywords::EMPTY_REPLY.to_string(), ZoneState::to_text),
+        ].join(Keywords::ARGUMENT_SEP);
+
+        [header, entities, bullets, obstacles, pickups, zone].join(Keywords::COMMAND_SEP)
+    }
+}
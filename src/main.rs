@@ -3,8 +3,14 @@ use std::thread;
 
 pub use types::StyledMessage;
 
-use crate::server::server_thread::{ServerSettings, ServerThread};
+use crate::app_defines::AppDefines;
+use crate::config::{CommandLine, ConfigFile};
+use crate::game_logic::GameLogic;
+use crate::server::server_thread::ServerThread;
+use crate::types::{add_message, MessageType};
 use crate::ui::game_ui::GameUI;
+use crate::ui::main_ui::MainUi;
+use crate::ui::server_ui::ServerUi;
 
 mod server;
 mod ui;
@@ -15,37 +21,83 @@ mod entities;
 mod bullet;
 mod game_logic;
 mod obstacles;
+mod zones;
+mod config;
+mod maps;
+mod weapons;
+mod pickups;
+mod ai;
+mod navigation;
+
+/// Prints an error preventing the server from starting and exits.
+///
+/// # Arguments
+///
+/// * `message` - The error to print.
+///
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load the settings from the configuration file and the command line
+    let command_line = CommandLine::parse(std::env::args().skip(1))
+        .unwrap_or_else(|e| exit_with_error(&format!("{}\n{}", e, CommandLine::USAGE)));
+    let config = ConfigFile::load_or_default(&command_line.config_path)
+        .unwrap_or_else(|e| exit_with_error(&e));
+    let (profile, server_settings) = command_line.resolve_settings(&config).unwrap_or_else(|errors| {
+        let errors: Vec<String> = errors.iter().map(|error| format!("Invalid setting: {}", error)).collect();
+        exit_with_error(&errors.join("\n"))
+    });
+
     // Shared state for messages
     let messages = Arc::new(Mutex::new(Vec::new()));
-    let settings = Arc::new(Mutex::new(ServerSettings::new()));
+    // The ports are only read once, the servers keep listening on them until the application exits
+    let (server_port, websocket_port) = (server_settings.server_port, server_settings.websocket_port);
+    let settings = Arc::new(Mutex::new(server_settings));
+    add_message(
+        &messages,
+        format!("[START] Using profile '{}' from {}", profile, command_line.config_path.display()),
+        MessageType::Default,
+    );
+
+    // Shared state for the simulation, driven by the game UI and queried by the clients
+    let mut game_logic = GameLogic::new(Arc::clone(&settings));
+    game_logic.generate_map();
+    let game_logic = Arc::new(Mutex::new(game_logic));
 
     // Clone the Arcs to move into the server thread
     let server_messages = Arc::clone(&messages);
     let server_settings = Arc::clone(&settings);
+    let server_game_logic = Arc::clone(&game_logic);
+
+    // Start the WebSocket server in a separate thread, sharing the same game
+    let websocket_server = ServerThread::new(
+        AppDefines::SERVER_ADDRESS.to_string(),
+        websocket_port,
+        Arc::clone(&messages),
+        Arc::clone(&settings),
+        Arc::clone(&game_logic),
+    );
+    thread::spawn(move || websocket_server.start_websocket());
 
     // Start the server in a separate thread
     thread::spawn(move || {
-        let serv = ServerThread::new("127.0.0.1".to_string(),6969, server_messages, server_settings);
+        let serv = ServerThread::new(AppDefines::SERVER_ADDRESS.to_string(), server_port, server_messages, server_settings, server_game_logic);
         serv.start();
     });
 
-    // Run the GUI
+    // Run the game and server GUIs in the main thread
     let options = eframe::NativeOptions::default();
     eframe::run_native(
         "Physics Simulation",
         options,
-        Box::new(|_cc| Box::new(GameUI::default())),
-    ).expect("Failed to run game UI");
-
-    // Run the GUI in the main thread
-    let native_options = eframe::NativeOptions::default();
-    eframe::run_native(
-        "Server GUI",
-        native_options,
-        Box::new(|_cc| Box::new(ui::server_ui::ServerUi::new(messages, settings))),
-    ).expect("Failed to run server GUI");
+        Box::new(|_cc| Box::new(MainUi::new(
+            GameUI::new(game_logic, Arc::clone(&messages)),
+            ServerUi::new(messages, settings, config, command_line.config_path, profile),
+        ))),
+    ).expect("Failed to run GUI");
 
     Ok(())
 }
@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_defines::AppDefines;
+use crate::obstacles::{ObstacleMaterial, ObstacleShape};
+
+/// Represents a hand-designed arena map, stored as JSON.
+///
+/// # Examples
+/// ```json
+/// {
+///   "arena_width": 1200.0,
+///   "arena_height": 1000.0,
+///   "obstacles": [
+///     { "shape": "rectangle", "x": 600.0, "y": 500.0, "half_width": 100.0, "half_height": 20.0 },
+///     { "shape": "rectangle", "x": 200.0, "y": 800.0, "half_width": 20.0, "half_height": 20.0, "health": 50.0, "regeneration_delay": 10.0 },
+///     { "shape": "circle", "x": 300.0, "y": 300.0, "radius": 40.0, "material": "bouncy" },
+///     { "shape": "polygon", "x": 900.0, "y": 300.0, "points": [[-40.0, -30.0], [40.0, -30.0], [0.0, 40.0]] },
+///     { "shape": "line", "x": 600.0, "y": 800.0, "length": 300.0, "rotation": 0.5, "material": "absorbing" }
+///   ],
+///   "spawn_points": [[100.0, 100.0], [1100.0, 900.0]],
+///   "control_zone": { "x": 600.0, "y": 300.0, "radius": 80.0 }
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapFile {
+    /// The width of the arena.
+    pub arena_width: f32,
+    /// The height of the arena.
+    pub arena_height: f32,
+    /// The obstacles of the arena.
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleDefinition>,
+    /// The positions where entities spawn. Random positions are used if empty.
+    #[serde(default)]
+    pub spawn_points: Vec<(f32, f32)>,
+    /// The control zone used in King of the Hill mode. Centered in the arena if missing.
+    #[serde(default)]
+    pub control_zone: Option<ControlZoneDefinition>,
+    /// The safe zone used in Battle Royale mode. Centered in the arena if missing.
+    #[serde(default)]
+    pub safe_zone: Option<SafeZoneDefinition>,
+}
+
+/// Represents an obstacle of a map file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObstacleDefinition {
+    /// The x position of the center of the obstacle.
+    pub x: f32,
+    /// The y position of the center of the obstacle.
+    pub y: f32,
+    /// The rotation of the obstacle, in radians.
+    #[serde(default)]
+    pub rotation: f32,
+    /// The material of the obstacle. Solid if missing.
+    #[serde(default)]
+    pub material: ObstacleMaterial,
+    /// The hit points of the obstacle. Indestructible if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<f32>,
+    /// The delay in seconds before the obstacle comes back once destroyed, at most
+    /// `AppDefines::OBSTACLE_MAX_REGENERATION_DELAY`. Stays destroyed if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regeneration_delay: Option<f32>,
+    /// The shape of the obstacle, tagged by its `shape` field.
+    #[serde(flatten)]
+    pub shape: ObstacleShape,
+}
+
+/// Represents the King of the Hill control zone of a map file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlZoneDefinition {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+}
+
+/// Represents the Battle Royale safe zone of a map file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeZoneDefinition {
+    pub x: f32,
+    pub y: f32,
+    pub initial_radius: f32,
+    pub min_radius: f32,
+}
+
+impl MapFile {
+    /// Loads and validates a map file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the JSON file to read.
+    ///
+    /// # Returns
+    /// The loaded map, or an error message if the file cannot be read, parsed, or is invalid.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read map file {}: {}", path.display(), e))?;
+        let map: MapFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Cannot parse map file {}: {}", path.display(), e))?;
+        map.validate()
+            .map_err(|errors| format!("Invalid map file {}: {}", path.display(), errors.join(", ")))?;
+        Ok(map)
+    }
+
+    /// Saves the map to a file.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the JSON file to write.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error message if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Cannot serialize map: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Cannot write map file {}: {}", path.display(), e))
+    }
+
+    /// Checks that the arena size is allowed and that everything lies inside the arena.
+    ///
+    /// # Returns
+    /// `Ok(())` if the map is valid, or the list of error messages describing each bad value.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let inside = |x: f32, y: f32| (0.0..=self.arena_width).contains(&x) && (0.0..=self.arena_height).contains(&y);
+
+        for (name, size) in [("width", self.arena_width), ("height", self.arena_height)] {
+            if !(AppDefines::ARENA_MIN_SIZE..=AppDefines::ARENA_MAX_SIZE).contains(&size) {
+                errors.push(format!(
+                    "Arena {} must be between {} and {}, got {}",
+                    name, AppDefines::ARENA_MIN_SIZE, AppDefines::ARENA_MAX_SIZE, size
+                ));
+            }
+        }
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            if !inside(obstacle.x, obstacle.y) {
+                errors.push(format!("Obstacle {} is outside the arena", index));
+            }
+            if let Err(e) = obstacle.shape.validate() {
+                errors.push(format!("Obstacle {} {}", index, e));
+            }
+            if obstacle.health.is_some_and(|health| !health.is_finite() || health <= 0.0) {
+                errors.push(format!("Obstacle {} must have positive health", index));
+            }
+            if obstacle.regeneration_delay.is_some_and(|delay| !(0.0..=AppDefines::OBSTACLE_MAX_REGENERATION_DELAY).contains(&delay)) {
+                errors.push(format!(
+                    "Obstacle {} must have a regeneration delay between 0 and {} seconds",
+                    index, AppDefines::OBSTACLE_MAX_REGENERATION_DELAY
+                ));
+            }
+        }
+        for (index, &(x, y)) in self.spawn_points.iter().enumerate() {
+            if !inside(x, y) {
+                errors.push(format!("Spawn point {} is outside the arena", index));
+            }
+        }
+        if let Some(zone) = &self.control_zone {
+            if !inside(zone.x, zone.y) || zone.radius <= 0.0 {
+                errors.push("Control zone must be inside the arena with a positive radius".to_string());
+            }
+        }
+        if let Some(zone) = &self.safe_zone {
+            if !inside(zone.x, zone.y) || zone.min_radius <= 0.0 || zone.initial_radius < zone.min_radius {
+                errors.push("Safe zone must be inside the arena with an initial radius above its positive minimum radius".to_string());
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a map holding a single destructible obstacle with the given regeneration delay.
+    fn map_with_regeneration_delay(regeneration_delay: f32) -> MapFile {
+        MapFile {
+            arena_width: 1200.0,
+            arena_height: 1000.0,
+            obstacles: vec![ObstacleDefinition {
+                x: 600.0,
+                y: 500.0,
+                rotation: 0.0,
+                material: ObstacleMaterial::Solid,
+                health: Some(50.0),
+                regeneration_delay: Some(regeneration_delay),
+                shape: ObstacleShape::Circle { radius: 40.0 },
+            }],
+            spawn_points: Vec::new(),
+            control_zone: None,
+            safe_zone: None,
+        }
+    }
+
+    #[test]
+    fn regeneration_delay_must_be_finite_and_bounded() {
+        assert!(map_with_regeneration_delay(10.0).validate().is_ok());
+        for delay in [-1.0, 1e30, f32::INFINITY, f32::NAN] {
+            assert!(map_with_regeneration_delay(delay).validate().is_err(), "{}", delay);
+        }
+    }
+
+    #[test]
+    fn oversized_regeneration_delay_in_a_map_file_is_rejected() {
+        let content = r#"{ "arena_width": 1200.0, "arena_height": 1000.0, "obstacles": [
+            { "shape": "circle", "x": 600.0, "y": 500.0, "radius": 40.0, "health": 50.0, "regeneration_delay": 1e30 }
+        ] }"#;
+        let map: MapFile = serde_json::from_str(content).unwrap();
+        assert!(map.validate().is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+
+use rapier2d::parry::query::intersection_test;
+use rapier2d::prelude::*;
+
+use crate::app_defines::AppDefines;
+use crate::obstacles::Obstacle;
+use crate::physics::physics::PhysicsEngine;
+
+/// The cost of moving to a neighbouring cell along a row or a column.
+const STRAIGHT_COST: u32 = 10;
+/// The cost of moving to a neighbouring cell along a diagonal.
+const DIAGONAL_COST: u32 = 14;
+
+/// Represents the walkable cells of the arena, used to plan paths around the obstacles.
+///
+/// A cell is blocked when an entity standing at its center would touch an obstacle or the arena boundaries.
+pub struct NavGrid {
+    /// The width and height of a cell.
+    pub cell_size: f32,
+    pub columns: usize,
+    pub rows: usize,
+    /// Whether each cell is blocked, row by row.
+    blocked: Vec<bool>,
+    /// The buffers of the path search, kept between searches to avoid allocating them for every path.
+    search: Mutex<SearchBuffers>,
+}
+
+/// Holds the state of a path search over the cells of a grid.
+struct SearchBuffers {
+    /// The cost of the best known path to each cell.
+    costs: Vec<u32>,
+    /// The cell each cell is reached from along the best known path.
+    previous: Vec<usize>,
+    /// The search during which each cell was last reached, so the buffers do not have to be cleared.
+    visits: Vec<u32>,
+    /// The number of the current search.
+    generation: u32,
+    open: BinaryHeap<Reverse<(u32, usize)>>,
+}
+
+impl SearchBuffers {
+    /// Creates the buffers for a grid.
+    ///
+    /// # Parameters
+    /// - `cells`: The number of cells of the grid.
+    fn new(cells: usize) -> Self {
+        Self {
+            costs: vec![u32::MAX; cells],
+            previous: vec![usize::MAX; cells],
+            visits: vec![0; cells],
+            generation: 0,
+            open: BinaryHeap::new(),
+        }
+    }
+
+    /// Starts a new search, forgetting the cells reached by the previous ones.
+    fn start(&mut self) {
+        self.open.clear();
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            self.visits.fill(0);
+            self.generation = 1;
+        }
+    }
+
+    /// Returns the cost of the best known path to a cell during the current search.
+    fn cost(&self, cell: usize) -> u32 {
+        if self.visits[cell] == self.generation { self.costs[cell] } else { u32::MAX }
+    }
+
+    /// Records a better path to a cell.
+    fn reach(&mut self, cell: usize, cost: u32, previous: usize) {
+        self.visits[cell] = self.generation;
+        self.costs[cell] = cost;
+        self.previous[cell] = previous;
+    }
+}
+
+impl NavGrid {
+    /// Builds the grid of an arena.
+    ///
+    /// Destroyed obstacles are only considered blocking if they come back.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A reference to the physics engine holding the colliders of the obstacles.
+    /// - `obstacles`: The obstacles of the arena.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `cell_size`: The width and height of a cell.
+    /// - `clearance`: The distance to keep between the center of an entity and the obstacles.
+    ///
+    /// # Returns
+    /// The grid covering the arena.
+    pub fn build(
+        physics_engine: &PhysicsEngine,
+        obstacles: &[Obstacle],
+        arena_width: f32,
+        arena_height: f32,
+        cell_size: f32,
+        clearance: f32,
+    ) -> Self {
+        let columns = (arena_width / cell_size).ceil().max(1.0) as usize;
+        let rows = (arena_height / cell_size).ceil().max(1.0) as usize;
+        let mut grid = Self::open(columns, rows, cell_size);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let (x, y) = grid.cell_center(column, row);
+                if x < clearance || y < clearance || x > arena_width - clearance || y > arena_height - clearance {
+                    grid.blocked[row * columns + column] = true;
+                }
+            }
+        }
+
+        // Only the cells around each obstacle are tested against it
+        let entity_shape = Ball::new(clearance);
+        for obstacle in obstacles.iter().filter(|obstacle| obstacle.blocks_paths()) {
+            let Some(collider) = physics_engine.colliders.get(obstacle.collider_handle) else {
+                continue;
+            };
+            let aabb = collider.shape().compute_aabb(collider.position());
+            let (min_column, min_row) = grid.cell_of((aabb.mins.x - clearance, aabb.mins.y - clearance));
+            let (max_column, max_row) = grid.cell_of((aabb.maxs.x + clearance, aabb.maxs.y + clearance));
+            for row in min_row..=max_row {
+                for column in min_column..=max_column {
+                    let (x, y) = grid.cell_center(column, row);
+                    let entity_position = Isometry::translation(x, y);
+                    if intersection_test(collider.position(), collider.shape(), &entity_position, &entity_shape).unwrap_or(false) {
+                        grid.blocked[row * columns + column] = true;
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Creates a grid without any blocked cell.
+    fn open(columns: usize, rows: usize, cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            columns,
+            rows,
+            blocked: vec![false; columns * rows],
+            search: Mutex::new(SearchBuffers::new(columns * rows)),
+        }
+    }
+
+    /// Returns the center of a cell.
+    ///
+    /// # Parameters
+    /// - `column`: The column of the cell.
+    /// - `row`: The row of the cell.
+    ///
+    /// # Returns
+    /// The (x, y) position of the center of the cell.
+    pub fn cell_center(&self, column: usize, row: usize) -> (f32, f32) {
+        ((column as f32 + 0.5) * self.cell_size, (row as f32 + 0.5) * self.cell_size)
+    }
+
+    /// Finds the cell containing a position, clamped to the grid.
+    ///
+    /// # Parameters
+    /// - `position`: The (x, y) position.
+    ///
+    /// # Returns
+    /// The column and row of the cell.
+    pub fn cell_of(&self, position: (f32, f32)) -> (usize, usize) {
+        let column = (position.0 / self.cell_size).floor().clamp(0.0, (self.columns - 1) as f32) as usize;
+        let row = (position.1 / self.cell_size).floor().clamp(0.0, (self.rows - 1) as f32) as usize;
+        (column, row)
+    }
+
+    /// Checks whether an entity can stand at a position.
+    ///
+    /// # Parameters
+    /// - `position`: The (x, y) position.
+    ///
+    /// # Returns
+    /// `true` if the cell containing the position is not blocked.
+    pub fn is_free(&self, position: (f32, f32)) -> bool {
+        let (column, row) = self.cell_of(position);
+        !self.blocked[row * self.columns + column]
+    }
+
+    /// Checks whether a position lies on the grid.
+    ///
+    /// # Parameters
+    /// - `position`: The (x, y) position.
+    ///
+    /// # Returns
+    /// `true` if the position is inside the area covered by the cells.
+    pub fn contains(&self, position: (f32, f32)) -> bool {
+        (0.0..=self.columns as f32 * self.cell_size).contains(&position.0)
+            && (0.0..=self.rows as f32 * self.cell_size).contains(&position.1)
+    }
+
+    /// Checks whether an entity can walk in a straight line between two positions.
+    ///
+    /// # Parameters
+    /// - `from`: The (x, y) start position.
+    /// - `to`: The (x, y) end position.
+    ///
+    /// # Returns
+    /// `true` if no blocked cell lies along the segment.
+    pub fn line_of_sight(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        let distance = (to.0 - from.0).hypot(to.1 - from.1);
+        let steps = (distance / (self.cell_size / 2.0)).ceil() as usize;
+        (1..=steps).all(|step| {
+            let t = step as f32 / steps as f32;
+            self.is_free((from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t))
+        })
+    }
+
+    /// Plans a path between two positions with A*, moving between neighbouring cells without cutting corners.
+    ///
+    /// The path is shortened by skipping the waypoints that can be seen from an earlier one.
+    /// The search gives up after exploring `AppDefines::NAV_MAX_EXPANDED_CELLS` cells, so a far away target
+    /// on a large arena cannot stall the game.
+    ///
+    /// # Parameters
+    /// - `from`: The (x, y) start position, usually the position of an entity.
+    /// - `to`: The (x, y) position to reach.
+    ///
+    /// # Returns
+    /// The waypoints leading to `to`, ending with `to` itself, or `None` if `to` is outside the grid, blocked,
+    /// unreachable or too far to be found.
+    pub fn find_path(&self, from: (f32, f32), to: (f32, f32)) -> Option<Vec<(f32, f32)>> {
+        if !self.contains(to) || !self.is_free(to) {
+            return None;
+        }
+        let start = self.index(self.cell_of(from));
+        let goal = self.index(self.cell_of(to));
+
+        let heuristic = |index: usize| {
+            let (column, row) = (index % self.columns, index / self.columns);
+            let (goal_column, goal_row) = (goal % self.columns, goal / self.columns);
+            let dx = column.abs_diff(goal_column) as u32;
+            let dy = row.abs_diff(goal_row) as u32;
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        };
+
+        let mut search = self.search.lock().unwrap();
+        search.start();
+        search.reach(start, 0, usize::MAX);
+        search.open.push(Reverse((heuristic(start), start)));
+
+        let mut expanded = 0;
+        while let Some(Reverse((_, current))) = search.open.pop() {
+            if current == goal {
+                break;
+            }
+            expanded += 1;
+            if expanded > AppDefines::NAV_MAX_EXPANDED_CELLS {
+                return None;
+            }
+            let (column, row) = (current % self.columns, current / self.columns);
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+                let (Some(next_column), Some(next_row)) = (column.checked_add_signed(dx), row.checked_add_signed(dy)) else {
+                    continue;
+                };
+                if next_column >= self.columns || next_row >= self.rows || self.blocked[self.index((next_column, next_row))] {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                // Moving diagonally requires both cells along the corner to be free
+                if diagonal && (self.blocked[self.index((next_column, row))] || self.blocked[self.index((column, next_row))]) {
+                    continue;
+                }
+
+                let next = self.index((next_column, next_row));
+                let cost = search.cost(current) + if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                if cost < search.cost(next) {
+                    search.reach(next, cost, current);
+                    search.open.push(Reverse((cost + heuristic(next), next)));
+                }
+            }
+        }
+
+        if search.cost(goal) == u32::MAX {
+            return None;
+        }
+
+        let mut cells = vec![goal];
+        while let Some(&cell) = cells.last().filter(|&&cell| cell != start) {
+            cells.push(search.previous[cell]);
+        }
+        cells.reverse();
+        drop(search);
+
+        // Keep only the waypoints where the path has to turn around a blocked cell
+        let mut waypoints: Vec<(f32, f32)> = cells[1..].iter()
+            .map(|&cell| self.cell_center(cell % self.columns, cell / self.columns))
+            .collect();
+        waypoints.pop();
+        waypoints.push(to);
+        let mut path = Vec::new();
+        let mut position = from;
+        let mut index = 0;
+        while index < waypoints.len() {
+            let mut furthest = index;
+            while furthest + 1 < waypoints.len() && self.line_of_sight(position, waypoints[furthest + 1]) {
+                furthest += 1;
+            }
+            position = waypoints[furthest];
+            path.push(position);
+            index = furthest + 1;
+        }
+        Some(path)
+    }
+
+    /// Returns the index of a cell in the list of cells.
+    fn index(&self, (column, row): (usize, usize)) -> usize {
+        row * self.columns + column
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a grid of 10 by 10 cells of size 10, with the given cells blocked.
+    fn grid_with_blocked(cells: &[(usize, usize)]) -> NavGrid {
+        let mut grid = NavGrid::open(10, 10, 10.0);
+        for &cell in cells {
+            let index = grid.index(cell);
+            grid.blocked[index] = true;
+        }
+        grid
+    }
+
+    #[test]
+    fn open_grid_path_goes_straight_to_the_target() {
+        let grid = grid_with_blocked(&[]);
+        assert_eq!(grid.find_path((5.0, 5.0), (95.0, 95.0)), Some(vec![(95.0, 95.0)]));
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        // A wall across the grid with a gap in the last row
+        let wall: Vec<_> = (0..9).map(|row| (5, row)).collect();
+        let grid = grid_with_blocked(&wall);
+
+        let path = grid.find_path((15.0, 15.0), (85.0, 15.0)).unwrap();
+        assert_eq!(path.last(), Some(&(85.0, 15.0)));
+        assert!(path.len() > 1);
+        let mut position = (15.0, 15.0);
+        for &waypoint in &path {
+            assert!(grid.line_of_sight(position, waypoint));
+            position = waypoint;
+        }
+    }
+
+    #[test]
+    fn blocked_or_unreachable_target_has_no_path() {
+        let wall: Vec<_> = (0..10).map(|row| (5, row)).collect();
+        let grid = grid_with_blocked(&wall);
+        assert_eq!(grid.find_path((15.0, 15.0), (55.0, 15.0)), None);
+        assert_eq!(grid.find_path((15.0, 15.0), (85.0, 15.0)), None);
+    }
+
+    #[test]
+    fn target_outside_the_grid_has_no_path() {
+        let grid = grid_with_blocked(&[]);
+        assert_eq!(grid.find_path((15.0, 15.0), (150.0, 15.0)), None);
+        assert_eq!(grid.find_path((15.0, 15.0), (15.0, -5.0)), None);
+    }
+
+    #[test]
+    fn search_gives_up_past_the_expanded_cell_limit() {
+        // A grid larger than the limit, with the target walled off so every reachable cell is explored
+        let side = 200;
+        assert!(side * side > AppDefines::NAV_MAX_EXPANDED_CELLS);
+        let mut grid = NavGrid::open(side, side, 10.0);
+        for (column, row) in [(side - 2, side - 1), (side - 2, side - 2), (side - 1, side - 2)] {
+            let index = grid.index((column, row));
+            grid.blocked[index] = true;
+        }
+        let target = grid.cell_center(side - 1, side - 1);
+        assert_eq!(grid.find_path((5.0, 5.0), target), None);
+
+        // A close target is still found afterwards, the buffers being reused
+        assert_eq!(grid.find_path((5.0, 5.0), (55.0, 5.0)), Some(vec![(55.0, 5.0)]));
+    }
+}
@@ -0,0 +1,674 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+use crate::app_defines::AppDefines;
+
+/// Represents the available map generation strategies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapGenerator {
+    /// Square obstacles scattered on a grid with the obstacle probability.
+    Scattered,
+    /// A maze carved by a recursive backtracker, with thin walls between its cells.
+    Maze,
+    /// Rectangular rooms joined by corridors, surrounded by solid walls.
+    Rooms,
+    /// Scattered obstacles mirrored around the center of the arena, for fair 1v1 games.
+    Symmetric,
+}
+
+/// Represents the result of a map generator.
+pub struct GeneratedMap {
+    /// The rectangular walls, as ((x, y) center, (half width, half height)) pairs.
+    pub walls: Vec<((f32, f32), (f32, f32))>,
+    /// The positions where entities can spawn without overlapping a wall.
+    pub spawn_points: Vec<(f32, f32)>,
+}
+
+impl MapGenerator {
+    /// All the map generators.
+    pub const ALL: [MapGenerator; 4] = [MapGenerator::Scattered, MapGenerator::Maze, MapGenerator::Rooms, MapGenerator::Symmetric];
+
+    /// Returns the name of the map generator.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapGenerator::Scattered => "Scattered",
+            MapGenerator::Maze => "Maze",
+            MapGenerator::Rooms => "Rooms",
+            MapGenerator::Symmetric => "Symmetric",
+        }
+    }
+
+    /// Generates a map sized to the arena.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the random generator. The same seed always produces the same map.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `obstacle_probability`: The density of obstacles, used by the scattered generators
+    ///   and to decide which extra maze walls are kept.
+    ///
+    /// # Returns
+    /// The walls and spawn points of the generated map.
+    pub fn generate(&self, seed: u64, arena_width: f32, arena_height: f32, obstacle_probability: f64) -> GeneratedMap {
+        let cell_size = AppDefines::OBSTACLE_CELL_SIZE;
+        match self {
+            MapGenerator::Scattered => {
+                let grid = ObstacleGrid::generate(seed, arena_width, arena_height, cell_size, obstacle_probability);
+                GeneratedMap {
+                    walls: grid.obstacle_cells(),
+                    spawn_points: grid.spawn_points(seed),
+                }
+            }
+            MapGenerator::Maze => generate_maze(seed, arena_width, arena_height, obstacle_probability),
+            MapGenerator::Rooms => {
+                let (grid, room_centers) = ObstacleGrid::rooms(seed, arena_width, arena_height, cell_size);
+                GeneratedMap {
+                    walls: grid.wall_segments(),
+                    // Arenas too small for a room get an open map, spawning anywhere
+                    spawn_points: if room_centers.is_empty() { grid.spawn_points(seed) } else { room_centers },
+                }
+            }
+            MapGenerator::Symmetric => {
+                let grid = ObstacleGrid::symmetric(seed, arena_width, arena_height, cell_size, obstacle_probability);
+                GeneratedMap {
+                    walls: grid.obstacle_cells(),
+                    spawn_points: grid.symmetric_spawn_points(seed, arena_width, arena_height),
+                }
+            }
+        }
+    }
+}
+
+/// Generates a maze with a recursive backtracker.
+///
+/// The arena is split into `AppDefines::MAZE_CELL_SIZE` cells and a perfect maze is carved between them.
+/// Each remaining inner wall is then removed with a probability of `(1 - obstacle_probability) / 2`,
+/// which adds loops so entities are not trapped in dead ends.
+///
+/// # Parameters
+/// - `seed`: The seed of the random generator.
+/// - `arena_width`: The width of the arena.
+/// - `arena_height`: The height of the arena.
+/// - `obstacle_probability`: The density of the maze walls.
+///
+/// # Returns
+/// The thin walls of the maze, with a spawn point at the center of every maze cell.
+fn generate_maze(seed: u64, arena_width: f32, arena_height: f32, obstacle_probability: f64) -> GeneratedMap {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let columns = (arena_width / AppDefines::MAZE_CELL_SIZE).floor().max(1.0) as usize;
+    let rows = (arena_height / AppDefines::MAZE_CELL_SIZE).floor().max(1.0) as usize;
+    let cell_width = arena_width / columns as f32;
+    let cell_height = arena_height / rows as f32;
+
+    // Walls on the east and north side of each cell, the arena boundaries close the others
+    let mut east_walls = vec![true; columns * rows];
+    let mut north_walls = vec![true; columns * rows];
+    let mut visited = vec![false; columns * rows];
+
+    let mut stack = vec![(0usize, 0usize)];
+    visited[0] = true;
+    while let Some(&(column, row)) = stack.last() {
+        let mut neighbours = Vec::new();
+        if column > 0 && !visited[row * columns + column - 1] {
+            neighbours.push((column - 1, row));
+        }
+        if column + 1 < columns && !visited[row * columns + column + 1] {
+            neighbours.push((column + 1, row));
+        }
+        if row > 0 && !visited[(row - 1) * columns + column] {
+            neighbours.push((column, row - 1));
+        }
+        if row + 1 < rows && !visited[(row + 1) * columns + column] {
+            neighbours.push((column, row + 1));
+        }
+
+        match neighbours.choose(&mut rng) {
+            Some(&(next_column, next_row)) => {
+                // Open the wall between the current cell and the chosen neighbour
+                if next_column != column {
+                    east_walls[row * columns + column.min(next_column)] = false;
+                } else {
+                    north_walls[row.min(next_row) * columns + column] = false;
+                }
+                visited[next_row * columns + next_column] = true;
+                stack.push((next_column, next_row));
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    let removal_probability = ((1.0 - obstacle_probability) / 2.0).clamp(0.0, 1.0);
+    let half_thickness = AppDefines::MAZE_WALL_THICKNESS / 2.0;
+    let mut walls = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = row * columns + column;
+            if column + 1 < columns && east_walls[index] && !rng.gen_bool(removal_probability) {
+                walls.push((
+                    ((column + 1) as f32 * cell_width, (row as f32 + 0.5) * cell_height),
+                    (half_thickness, cell_height / 2.0 + half_thickness),
+                ));
+            }
+            if row + 1 < rows && north_walls[index] && !rng.gen_bool(removal_probability) {
+                walls.push((
+                    ((column as f32 + 0.5) * cell_width, (row + 1) as f32 * cell_height),
+                    (cell_width / 2.0 + half_thickness, half_thickness),
+                ));
+            }
+        }
+    }
+
+    let mut spawn_points: Vec<(f32, f32)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| ((column as f32 + 0.5) * cell_width, (row as f32 + 0.5) * cell_height)))
+        .collect();
+    spawn_points.shuffle(&mut rng);
+
+    GeneratedMap { walls, spawn_points }
+}
+
+/// Represents an arena split into square cells, each cell being either blocked by an obstacle or open.
+///
+/// The grid is centered in the arena, the space left by the partial cells being split between both sides.
+pub struct ObstacleGrid {
+    pub columns: usize,
+    pub rows: usize,
+    pub cell_size: f32,
+    /// The (x, y) position of the corner of the first cell in the arena.
+    pub origin: (f32, f32),
+    blocked: Vec<bool>,
+}
+
+impl ObstacleGrid {
+    /// Creates a grid covering the arena, with every cell in the same state.
+    ///
+    /// # Parameters
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `cell_size`: The size of a cell.
+    /// - `blocked`: Whether the cells are blocked.
+    ///
+    /// # Returns
+    /// A new instance of `ObstacleGrid`.
+    fn filled(arena_width: f32, arena_height: f32, cell_size: f32, blocked: bool) -> Self {
+        let columns = (arena_width / cell_size).floor().max(1.0) as usize;
+        let rows = (arena_height / cell_size).floor().max(1.0) as usize;
+        Self {
+            columns,
+            rows,
+            cell_size,
+            origin: (
+                (arena_width - columns as f32 * cell_size).max(0.0) / 2.0,
+                (arena_height - rows as f32 * cell_size).max(0.0) / 2.0,
+            ),
+            blocked: vec![blocked; columns * rows],
+        }
+    }
+
+    /// Generates a grid where each cell is an obstacle with the given probability.
+    ///
+    /// The open cells are then connected together by carving corridors through the obstacles,
+    /// so every open area can be reached from any other one, and a spawn area is kept open even on the
+    /// densest grids. The same seed always produces the same grid.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the random generator.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `cell_size`: The size of a cell, which is also the size of an obstacle.
+    /// - `obstacle_probability`: The probability of a cell being an obstacle.
+    ///
+    /// # Returns
+    /// A new instance of `ObstacleGrid`.
+    pub fn generate(seed: u64, arena_width: f32, arena_height: f32, cell_size: f32, obstacle_probability: f64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut grid = Self::filled(arena_width, arena_height, cell_size, false);
+        for blocked in &mut grid.blocked {
+            *blocked = rng.gen_bool(obstacle_probability.clamp(0.0, 1.0));
+        }
+        grid.ensure_spawn_area();
+        grid.connect_open_areas();
+        grid
+    }
+
+    /// Generates a grid of rectangular rooms joined by corridors, every other cell being a wall.
+    ///
+    /// Arenas too small to hold a single room get an open grid instead of a solid one.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the random generator.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `cell_size`: The size of a cell.
+    ///
+    /// # Returns
+    /// The grid and the center of each room, shuffled by the seed, with no room centers for an open grid.
+    pub fn rooms(seed: u64, arena_width: f32, arena_height: f32, cell_size: f32) -> (Self, Vec<(f32, f32)>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut grid = Self::filled(arena_width, arena_height, cell_size, true);
+        let (columns, rows) = (grid.columns, grid.rows);
+
+        // Place non-overlapping rooms, keeping a wall between them
+        let mut rooms: Vec<(usize, usize, usize, usize)> = Vec::new();
+        for _ in 0..AppDefines::ROOM_ATTEMPTS {
+            let width = rng.gen_range(AppDefines::ROOM_MIN_CELLS..=AppDefines::ROOM_MAX_CELLS);
+            let height = rng.gen_range(AppDefines::ROOM_MIN_CELLS..=AppDefines::ROOM_MAX_CELLS);
+            if width + 2 > columns || height + 2 > rows {
+                continue;
+            }
+            let column = rng.gen_range(1..columns - width);
+            let row = rng.gen_range(1..rows - height);
+
+            let overlaps = rooms.iter().any(|&(other_column, other_row, other_width, other_height)| {
+                column <= other_column + other_width
+                    && other_column <= column + width
+                    && row <= other_row + other_height
+                    && other_row <= row + height
+            });
+            if !overlaps {
+                rooms.push((column, row, width, height));
+            }
+        }
+        if rooms.is_empty() {
+            return (Self::filled(arena_width, arena_height, cell_size, false), Vec::new());
+        }
+
+        for &(column, row, width, height) in &rooms {
+            for r in row..row + height {
+                for c in column..column + width {
+                    grid.blocked[r * columns + c] = false;
+                }
+            }
+        }
+
+        // Join each room to the previous one with a two cells wide corridor
+        let room_cells: Vec<(usize, usize)> = rooms.iter()
+            .map(|&(column, row, width, height)| (column + width / 2, row + height / 2))
+            .collect();
+        for pair in room_cells.windows(2) {
+            let ((from_column, from_row), (to_column, to_row)) = (pair[0], pair[1]);
+            for c in from_column.min(to_column)..=from_column.max(to_column) {
+                for r in from_row..(from_row + 2).min(rows) {
+                    grid.blocked[r * columns + c] = false;
+                }
+            }
+            for r in from_row.min(to_row)..=from_row.max(to_row) {
+                for c in to_column..(to_column + 2).min(columns) {
+                    grid.blocked[r * columns + c] = false;
+                }
+            }
+        }
+
+        let mut room_centers: Vec<(f32, f32)> = room_cells.iter()
+            .map(|&(column, row)| grid.cell_center(column, row))
+            .collect();
+        room_centers.shuffle(&mut rng);
+        (grid, room_centers)
+    }
+
+    /// Generates a scattered grid that is symmetric around the center of the arena.
+    ///
+    /// Each cell has the same state as the cell mirrored through the center, so both halves of the
+    /// arena offer the same cover. The grid being centered in the arena, mirroring the cells through the
+    /// center of the grid mirrors their walls through the center of the arena.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed of the random generator.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    /// - `cell_size`: The size of a cell, which is also the size of an obstacle.
+    /// - `obstacle_probability`: The probability of a cell being an obstacle.
+    ///
+    /// # Returns
+    /// A new instance of `ObstacleGrid`.
+    pub fn symmetric(seed: u64, arena_width: f32, arena_height: f32, cell_size: f32, obstacle_probability: f64) -> Self {
+        let mut grid = Self::generate(seed, arena_width, arena_height, cell_size, obstacle_probability);
+
+        // Copy the first half onto the second one, then connect the areas again
+        let cell_count = grid.blocked.len();
+        for index in 0..cell_count / 2 {
+            grid.blocked[cell_count - 1 - index] = grid.blocked[index];
+        }
+        grid.ensure_spawn_area();
+        grid.connect_open_areas();
+
+        // Corridors may have been carved on one side only, open their mirror as well
+        for index in 0..cell_count {
+            if !grid.blocked[index] {
+                grid.blocked[cell_count - 1 - index] = false;
+            }
+        }
+        grid
+    }
+
+    /// Checks whether a cell is blocked by an obstacle.
+    ///
+    /// # Parameters
+    /// - `column`: The column of the cell.
+    /// - `row`: The row of the cell.
+    ///
+    /// # Returns
+    /// `true` if the cell is blocked, `false` if it is open.
+    pub fn is_blocked(&self, column: usize, row: usize) -> bool {
+        self.blocked[row * self.columns + column]
+    }
+
+    /// Returns the center of a cell in arena coordinates.
+    ///
+    /// # Parameters
+    /// - `column`: The column of the cell.
+    /// - `row`: The row of the cell.
+    ///
+    /// # Returns
+    /// A tuple representing the (x, y) center of the cell.
+    pub fn cell_center(&self, column: usize, row: usize) -> (f32, f32) {
+        (
+            self.origin.0 + (column as f32 + 0.5) * self.cell_size,
+            self.origin.1 + (row as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    /// Returns one square wall per blocked cell.
+    ///
+    /// # Returns
+    /// The ((x, y) center, (half width, half height)) of each obstacle.
+    pub fn obstacle_cells(&self) -> Vec<((f32, f32), (f32, f32))> {
+        let half_size = self.cell_size / 2.0;
+        self.cells()
+            .filter(|&(column, row)| self.is_blocked(column, row))
+            .map(|(column, row)| (self.cell_center(column, row), (half_size, half_size)))
+            .collect()
+    }
+
+    /// Returns the blocked cells merged into horizontal wall segments.
+    ///
+    /// Each run of consecutive blocked cells on a row becomes a single wall, which keeps
+    /// the number of colliders low for mostly solid grids.
+    ///
+    /// # Returns
+    /// The ((x, y) center, (half width, half height)) of each wall segment.
+    pub fn wall_segments(&self) -> Vec<((f32, f32), (f32, f32))> {
+        let mut walls = Vec::new();
+        for row in 0..self.rows {
+            let mut column = 0;
+            while column < self.columns {
+                if !self.is_blocked(column, row) {
+                    column += 1;
+                    continue;
+                }
+                let start = column;
+                while column < self.columns && self.is_blocked(column, row) {
+                    column += 1;
+                }
+                let length = (column - start) as f32 * self.cell_size;
+                walls.push((
+                    (self.origin.0 + start as f32 * self.cell_size + length / 2.0, self.cell_center(0, row).1),
+                    (length / 2.0, self.cell_size / 2.0),
+                ));
+            }
+        }
+        walls
+    }
+
+    /// Returns safe spawn points, in an order shuffled by the seed.
+    ///
+    /// A spawn point is the center of an open cell whose eight neighbours are open as well,
+    /// so an entity spawned there never overlaps an obstacle. If no such cell exists, every
+    /// open cell is used instead.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed used to shuffle the spawn points.
+    ///
+    /// # Returns
+    /// The (x, y) spawn points.
+    pub fn spawn_points(&self, seed: u64) -> Vec<(f32, f32)> {
+        let mut spawn_cells: Vec<(usize, usize)> = self.cells()
+            .filter(|&(column, row)| self.is_clear_around(column, row))
+            .collect();
+        if spawn_cells.is_empty() {
+            spawn_cells = self.cells()
+                .filter(|&(column, row)| !self.is_blocked(column, row))
+                .collect();
+        }
+
+        spawn_cells.shuffle(&mut StdRng::seed_from_u64(seed));
+        spawn_cells.into_iter()
+            .map(|(column, row)| self.cell_center(column, row))
+            .collect()
+    }
+
+    /// Returns safe spawn points in mirrored pairs.
+    ///
+    /// Every spawn point is followed by its mirror through the center of the arena, so the first two
+    /// entities of a 1v1 game start in equivalent positions. If the only safe cells lie on the center
+    /// column, they are returned without pairs.
+    ///
+    /// # Parameters
+    /// - `seed`: The seed used to shuffle the spawn points.
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    ///
+    /// # Returns
+    /// The (x, y) spawn points.
+    pub fn symmetric_spawn_points(&self, seed: u64, arena_width: f32, arena_height: f32) -> Vec<(f32, f32)> {
+        let spawn_points = self.spawn_points(seed);
+        let mirrored: Vec<(f32, f32)> = spawn_points.iter()
+            .filter(|&&(x, _)| x < arena_width / 2.0)
+            .flat_map(|&(x, y)| [(x, y), (arena_width - x, arena_height - y)])
+            .collect();
+        if mirrored.is_empty() { spawn_points } else { mirrored }
+    }
+
+    /// Iterates over the (column, row) coordinates of every cell.
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.rows).flat_map(move |row| (0..self.columns).map(move |column| (column, row)))
+    }
+
+    /// Checks whether a cell and its eight neighbours are all open and inside the grid.
+    fn is_clear_around(&self, column: usize, row: usize) -> bool {
+        if column == 0 || row == 0 || column + 1 >= self.columns || row + 1 >= self.rows {
+            return false;
+        }
+        (row - 1..=row + 1).all(|r| (column - 1..=column + 1).all(|c| !self.is_blocked(c, r)))
+    }
+
+    /// Lists the 4-connected neighbours of a cell.
+    ///
+    /// # Parameters
+    /// - `index`: The index of the cell.
+    ///
+    /// # Returns
+    /// The indices of the neighbours inside the grid.
+    fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> {
+        let (column, row) = (index % self.columns, index / self.columns);
+        let columns = self.columns;
+        [
+            (column > 0).then(|| index - 1),
+            (column + 1 < self.columns).then(|| index + 1),
+            (row > 0).then(|| index - columns),
+            (row + 1 < self.rows).then(|| index + columns),
+        ].into_iter().flatten()
+    }
+
+    /// Labels the 4-connected open areas of the grid in a single flood fill.
+    ///
+    /// # Returns
+    /// The area of each cell, `None` for the blocked cells, and the cell indices of each area.
+    fn open_areas(&self) -> (Vec<Option<usize>>, Vec<Vec<usize>>) {
+        let mut labels = vec![None; self.blocked.len()];
+        let mut areas: Vec<Vec<usize>> = Vec::new();
+
+        for start in 0..self.blocked.len() {
+            if self.blocked[start] || labels[start].is_some() {
+                continue;
+            }
+
+            let label = areas.len();
+            let mut area = vec![start];
+            labels[start] = Some(label);
+            let mut next = 0;
+            while next < area.len() {
+                for neighbour in self.neighbours(area[next]) {
+                    if !self.blocked[neighbour] && labels[neighbour].is_none() {
+                        labels[neighbour] = Some(label);
+                        area.push(neighbour);
+                    }
+                }
+                next += 1;
+            }
+            areas.push(area);
+        }
+
+        (labels, areas)
+    }
+
+    /// Carves corridors until all the open areas form a single connected area.
+    ///
+    /// A breadth-first search grows from the largest area through every cell. Whenever it reaches an area
+    /// that is not connected yet, the cells leading back to the connected ones are opened and the search
+    /// continues from the newly joined area, so each corridor is as short as possible.
+    fn connect_open_areas(&mut self) {
+        let (labels, areas) = self.open_areas();
+        if areas.len() <= 1 {
+            return;
+        }
+
+        // The first of the largest areas, so the same grid is always connected the same way
+        let main_label = (0..areas.len()).min_by_key(|&label| std::cmp::Reverse(areas[label].len())).unwrap();
+        let mut connected = vec![false; areas.len()];
+        connected[main_label] = true;
+
+        let mut visited = vec![false; self.blocked.len()];
+        let mut previous = vec![None; self.blocked.len()];
+        let mut queue = VecDeque::new();
+        for &cell in &areas[main_label] {
+            visited[cell] = true;
+            queue.push_back(cell);
+        }
+
+        while let Some(cell) = queue.pop_front() {
+            let neighbours: Vec<usize> = self.neighbours(cell).collect();
+            for neighbour in neighbours {
+                if visited[neighbour] {
+                    continue;
+                }
+                visited[neighbour] = true;
+                previous[neighbour] = Some(cell);
+
+                match labels[neighbour] {
+                    Some(label) if !connected[label] => {
+                        connected[label] = true;
+                        // Open the blocked cells between the new area and the connected ones
+                        let mut corridor = previous[neighbour];
+                        while let Some(corridor_cell) = corridor.filter(|&corridor_cell| self.blocked[corridor_cell]) {
+                            self.blocked[corridor_cell] = false;
+                            corridor = previous[corridor_cell];
+                        }
+                        for &area_cell in &areas[label] {
+                            visited[area_cell] = true;
+                            queue.push_back(area_cell);
+                        }
+                    }
+                    _ => queue.push_back(neighbour),
+                }
+            }
+        }
+    }
+
+    /// Opens the cells around the center of the grid if no cell is safe to spawn on.
+    ///
+    /// This keeps at least one spawn area on very dense grids, even with an obstacle probability of 1.
+    fn ensure_spawn_area(&mut self) {
+        if self.cells().any(|(column, row)| self.is_clear_around(column, row)) {
+            return;
+        }
+
+        let (column, row) = (self.columns / 2, self.rows / 2);
+        for r in row.saturating_sub(1)..(row + 2).min(self.rows) {
+            for c in column.saturating_sub(1)..(column + 2).min(self.columns) {
+                self.blocked[r * self.columns + c] = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finds the cell containing a position of the arena.
+    fn cell_at(grid: &ObstacleGrid, (x, y): (f32, f32)) -> (usize, usize) {
+        (((x - grid.origin.0) / grid.cell_size) as usize, ((y - grid.origin.1) / grid.cell_size) as usize)
+    }
+
+    #[test]
+    fn scattered_grid_is_connected() {
+        for seed in 0..20 {
+            for probability in [0.2, 0.5, 0.8] {
+                let grid = ObstacleGrid::generate(seed, 1200.0, 1000.0, 40.0, probability);
+                let (_, areas) = grid.open_areas();
+                assert_eq!(areas.len(), 1, "seed {} with probability {}", seed, probability);
+            }
+        }
+    }
+
+    #[test]
+    fn full_probability_keeps_a_spawn_area() {
+        let grid = ObstacleGrid::generate(7, 1200.0, 1000.0, 40.0, 1.0);
+        let spawn_points = grid.spawn_points(7);
+        assert!(!spawn_points.is_empty());
+        for position in spawn_points {
+            let (column, row) = cell_at(&grid, position);
+            assert!(grid.is_clear_around(column, row));
+        }
+    }
+
+    #[test]
+    fn spawn_points_are_open_cells() {
+        let grid = ObstacleGrid::generate(3, 800.0, 600.0, 40.0, 0.6);
+        for position in grid.spawn_points(3) {
+            let (column, row) = cell_at(&grid, position);
+            assert!(!grid.is_blocked(column, row));
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_map() {
+        let first = MapGenerator::Scattered.generate(42, 1200.0, 1000.0, 0.3);
+        let second = MapGenerator::Scattered.generate(42, 1200.0, 1000.0, 0.3);
+        assert_eq!(first.walls, second.walls);
+        assert_eq!(first.spawn_points, second.spawn_points);
+    }
+
+    #[test]
+    fn narrow_rooms_map_falls_back_to_an_open_map() {
+        let map = MapGenerator::Rooms.generate(1, 200.0, 1000.0, 0.3);
+        assert!(map.walls.is_empty());
+        assert!(!map.spawn_points.is_empty());
+    }
+
+    #[test]
+    fn symmetric_map_is_mirrored_in_the_arena() {
+        // 1020 / 40 leaves a partial cell, and 25 columns is odd
+        let (arena_width, arena_height) = (1020.0, 860.0);
+        let map = MapGenerator::Symmetric.generate(5, arena_width, arena_height, 0.4);
+        let mirrored = |(x, y): (f32, f32)| (arena_width - x, arena_height - y);
+        for &(center, size) in &map.walls {
+            let mirror = mirrored(center);
+            assert!(
+                map.walls.iter().any(|&(other, other_size)| {
+                    other_size == size && (other.0 - mirror.0).abs() < 1e-3 && (other.1 - mirror.1).abs() < 1e-3
+                }),
+                "no mirror for the wall at {:?}", center
+            );
+        }
+        for pair in map.spawn_points.chunks(2) {
+            let mirror = mirrored(pair[0]);
+            assert!((pair[1].0 - mirror.0).abs() < 1e-3 && (pair[1].1 - mirror.1).abs() < 1e-3);
+        }
+    }
+}
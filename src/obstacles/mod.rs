@@ -1,26 +1,261 @@
+use std::time::{Duration, Instant};
+
 use rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::physics::PhysicsEngine;
+
+pub mod generator;
+
+/// Represents the shape of an obstacle, centered on the obstacle position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum ObstacleShape {
+    /// A rectangle, axis-aligned before rotation.
+    Rectangle {
+        half_width: f32,
+        half_height: f32,
+    },
+    /// A circle.
+    Circle {
+        radius: f32,
+    },
+    /// A convex polygon, its points being relative to the obstacle position.
+    /// Concave point lists are replaced by their convex hull.
+    Polygon {
+        points: Vec<(f32, f32)>,
+    },
+    /// A thin wall, horizontal before rotation.
+    Line {
+        length: f32,
+    },
+}
+
+impl ObstacleShape {
+    /// Checks that the shape has a positive size and, for polygons, enough points to enclose an area.
+    ///
+    /// # Returns
+    /// `Ok(())` if the shape can be built, or an error message describing the problem.
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = match self {
+            ObstacleShape::Rectangle { half_width, half_height } => *half_width > 0.0 && *half_height > 0.0,
+            ObstacleShape::Circle { radius } => *radius > 0.0,
+            ObstacleShape::Polygon { .. } => true,
+            ObstacleShape::Line { length } => *length > 0.0,
+        };
+        if !positive {
+            return Err("must have a positive size".to_string());
+        }
+        if self.collider_builder().is_none() {
+            return Err("polygon needs at least 3 points that are not aligned".to_string());
+        }
+        Ok(())
+    }
+
+    /// Builds a collider for the shape.
+    ///
+    /// # Returns
+    /// The collider builder, or `None` if the points of a polygon do not form a convex hull.
+    fn collider_builder(&self) -> Option<ColliderBuilder> {
+        match self {
+            ObstacleShape::Rectangle { half_width, half_height } => Some(ColliderBuilder::cuboid(*half_width, *half_height)),
+            ObstacleShape::Circle { radius } => Some(ColliderBuilder::ball(*radius)),
+            ObstacleShape::Polygon { points } => {
+                let points: Vec<Point<Real>> = points.iter().map(|&(x, y)| point![x, y]).collect();
+                ColliderBuilder::convex_hull(&points)
+            }
+            ObstacleShape::Line { length } => Some(ColliderBuilder::segment(
+                point![-length / 2.0, 0.0],
+                point![length / 2.0, 0.0],
+            )),
+        }
+    }
+}
+
+/// Represents how an obstacle reacts to the bullets hitting it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObstacleMaterial {
+    /// Bullets lose half of their speed when bouncing off the obstacle.
+    #[default]
+    Solid,
+    /// Bullets are reflected without losing any speed.
+    Bouncy,
+    /// Bullets are destroyed when they hit the obstacle.
+    Absorbing,
+}
+
+impl ObstacleMaterial {
+    /// Applies the material to a collider.
+    ///
+    /// # Parameters
+    /// - `builder`: The collider builder of the obstacle.
+    ///
+    /// # Returns
+    /// The collider builder with the restitution and events of the material.
+    fn apply(&self, builder: ColliderBuilder) -> ColliderBuilder {
+        match self {
+            ObstacleMaterial::Solid => builder.restitution(0.0),
+            ObstacleMaterial::Bouncy => builder
+                .restitution(1.0)
+                .restitution_combine_rule(CoefficientCombineRule::Max),
+            // Absorbing obstacles report their collisions so the game logic can remove the bullets
+            ObstacleMaterial::Absorbing => builder
+                .restitution(0.0)
+                .restitution_combine_rule(CoefficientCombineRule::Min)
+                .active_events(ActiveEvents::COLLISION_EVENTS),
+        }
+    }
+}
 
 /// Represents an obstacle in the game.
 ///
-/// An obstacle has a position and a handle to its collider.
+/// An obstacle has a position, a rotation, a shape, a material and a handle to its collider.
+/// Destructible obstacles also have hit points, and their collider is disabled while they are destroyed.
 pub struct Obstacle {
     pub position: (f64, f64),
+    /// The rotation of the obstacle, in radians.
+    pub rotation: f32,
+    pub shape: ObstacleShape,
+    pub material: ObstacleMaterial,
     pub collider_handle: ColliderHandle,
+    /// The hit points of a fully repaired obstacle, `None` if the obstacle is indestructible.
+    pub max_health: Option<f32>,
+    /// The remaining hit points of the obstacle.
+    pub health: f32,
+    /// The delay before a destroyed obstacle comes back, `None` if it stays destroyed.
+    pub regeneration_delay: Option<Duration>,
+    /// The time at which the obstacle was destroyed.
+    pub destroyed_at: Option<Instant>,
+    /// Whether the obstacle was repaired while a body stood in its way, and comes back as soon as it leaves.
+    pub repair_pending: bool,
 }
 
 impl Obstacle {
-    /// Creates a new obstacle.
+    /// Creates a new obstacle and adds its collider to the physics engine.
     ///
     /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
     /// - `position`: A tuple representing the (x, y) position of the obstacle.
-    /// - `collider_handle`: The handle to the collider associated with this obstacle.
+    /// - `rotation`: The rotation of the obstacle, in radians.
+    /// - `shape`: The shape of the obstacle.
+    /// - `material`: The material of the obstacle.
     ///
     /// # Returns
-    /// A new instance of `Obstacle`.
-    pub fn new(position: (f64, f64), collider_handle: ColliderHandle) -> Self {
-        Self {
-            position,
+    /// A new instance of `Obstacle`, or `None` if the shape is a polygon without a convex hull.
+    pub fn new(
+        physics_engine: &mut PhysicsEngine,
+        position: (f32, f32),
+        rotation: f32,
+        shape: ObstacleShape,
+        material: ObstacleMaterial,
+    ) -> Option<Self> {
+        let collider = material.apply(shape.collider_builder()?)
+            .translation(vector![position.0, position.1])
+            .rotation(rotation)
+            .build();
+        let collider_handle = physics_engine.colliders.insert(collider);
+
+        Some(Self {
+            position: (position.0 as f64, position.1 as f64),
+            rotation,
+            shape,
+            material,
             collider_handle,
+            max_health: None,
+            health: 0.0,
+            regeneration_delay: None,
+            destroyed_at: None,
+            repair_pending: false,
+        })
+    }
+
+    /// Makes the obstacle destructible.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    /// - `max_health`: The hit points of the obstacle.
+    /// - `regeneration_delay`: The delay before the obstacle comes back once destroyed, `None` if it stays destroyed.
+    ///
+    /// # Returns
+    /// The destructible obstacle.
+    pub fn with_health(mut self, physics_engine: &mut PhysicsEngine, max_health: f32, regeneration_delay: Option<Duration>) -> Self {
+        // Collisions are reported so the game logic can apply the bullet damage
+        physics_engine.colliders[self.collider_handle].set_active_events(ActiveEvents::COLLISION_EVENTS);
+        self.max_health = Some(max_health);
+        self.health = max_health;
+        self.regeneration_delay = regeneration_delay;
+        self
+    }
+
+    /// Checks whether the obstacle is currently destroyed.
+    ///
+    /// # Returns
+    /// `true` if the obstacle was destroyed and has not come back yet.
+    pub fn is_destroyed(&self) -> bool {
+        self.destroyed_at.is_some()
+    }
+
+    /// Checks whether paths must go around the obstacle.
+    ///
+    /// A destroyed obstacle still blocks the paths while it is due to come back.
+    ///
+    /// # Returns
+    /// `true` unless the obstacle was destroyed for good.
+    pub fn blocks_paths(&self) -> bool {
+        !self.is_destroyed() || self.regeneration_delay.is_some()
+    }
+
+    /// Applies damage to a destructible obstacle, destroying it when it runs out of hit points.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    /// - `damage`: The hit points to remove.
+    pub fn take_damage(&mut self, physics_engine: &mut PhysicsEngine, damage: f32) {
+        if self.max_health.is_none() || self.is_destroyed() {
+            return;
+        }
+
+        self.health = (self.health - damage).max(0.0);
+        if self.health == 0.0 {
+            self.destroyed_at = Some(Instant::now());
+            physics_engine.colliders[self.collider_handle].set_enabled(false);
+        }
+    }
+
+    /// Brings a destroyed obstacle back once its regeneration delay has elapsed, or once the bodies
+    /// standing in the way of a pending repair have left.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    pub fn update(&mut self, physics_engine: &mut PhysicsEngine) {
+        let Some(destroyed_at) = self.destroyed_at else {
+            return;
+        };
+        if self.repair_pending || self.regeneration_delay.is_some_and(|delay| destroyed_at.elapsed() >= delay) {
+            self.repair(physics_engine);
         }
     }
+
+    /// Restores all the hit points of the obstacle, bringing it back if it was destroyed.
+    ///
+    /// A destroyed obstacle does not come back while an entity or a bullet overlaps it, so bodies never
+    /// end up stuck inside it. The repair is postponed until the area is free instead.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    pub fn repair(&mut self, physics_engine: &mut PhysicsEngine) {
+        let Some(max_health) = self.max_health else {
+            return;
+        };
+        if self.is_destroyed() && physics_engine.is_collider_occupied(self.collider_handle) {
+            self.repair_pending = true;
+            return;
+        }
+
+        self.health = max_health;
+        self.destroyed_at = None;
+        self.repair_pending = false;
+        physics_engine.colliders[self.collider_handle].set_enabled(true);
+    }
 }
@@ -1,9 +1,13 @@
-use std::time::{Duration, Instant};
-
 // physics/mod.rs
 use rapier2d::prelude::*;
 
-use crate::app_defines::AppDefines;
+/// Keeps the enabled colliders in scene queries.
+///
+/// The query pipeline keeps disabled colliders, such as destroyed obstacles and eliminated entities,
+/// so every query filters them out.
+fn is_enabled(_handle: ColliderHandle, collider: &Collider) -> bool {
+    collider.is_enabled()
+}
 
 /// Represents the physics engine and its components.
 pub struct PhysicsEngine {
@@ -19,9 +23,8 @@ pub struct PhysicsEngine {
     pub impulse_joints: ImpulseJointSet,
     pub multibody_joints: MultibodyJointSet,
     pub query_pipeline: QueryPipeline,
-    pub start_time: Instant,
-    pub loop_duration: Duration,
     pub collision_events: Vec<CollisionEvent>,
+    pub boundary_handles: Vec<ColliderHandle>,
 }
 
 impl Default for PhysicsEngine {
@@ -43,9 +46,8 @@ impl Default for PhysicsEngine {
             impulse_joints: ImpulseJointSet::new(),
             multibody_joints: MultibodyJointSet::new(),
             query_pipeline: QueryPipeline::new(),
-            start_time: Instant::now(),
-            loop_duration: Duration::new(5, 0),
             collision_events: Vec::new(),
+            boundary_handles: Vec::new(),
         }
     }
 }
@@ -56,9 +58,9 @@ impl PhysicsEngine {
     /// Clears previous collision events and updates the physics world.
     pub fn step(&mut self) {
         self.collision_events.clear(); // Clear previous collision events
-        let mut collision_event_handler = |event: &CollisionEvent| {
-            self.collision_events.push(event.clone());
-        };
+        let (collision_send, collision_recv) = rapier2d::crossbeam::channel::unbounded();
+        let (contact_force_send, _contact_force_recv) = rapier2d::crossbeam::channel::unbounded();
+        let event_handler = ChannelEventCollector::new(collision_send, contact_force_send);
 
         self.physics_pipeline.step(
             &self.gravity,
@@ -73,15 +75,102 @@ impl PhysicsEngine {
             &mut self.ccd_solver,
             Some(&mut self.query_pipeline),
             &(),
-            &(),
+            &event_handler,
         );
+
+        self.collision_events.extend(collision_recv.try_iter());
+    }
+
+    /// Casts a ray and finds the first collider it hits.
+    ///
+    /// The query pipeline is the one updated by the last call to `step`.
+    ///
+    /// # Parameters
+    /// - `origin`: A tuple representing the (x, y) start of the ray.
+    /// - `angle`: The direction of the ray, in radians.
+    /// - `max_range`: The maximum distance the ray travels.
+    /// - `exclude`: The rigid body ignored by the ray, usually the one casting it.
+    ///
+    /// # Returns
+    /// The handle of the collider hit and its distance, or `None` if nothing is within range.
+    pub fn cast_ray(&self, origin: (f32, f32), angle: f32, max_range: f32, exclude: RigidBodyHandle) -> Option<(ColliderHandle, f32)> {
+        let ray = Ray::new(point![origin.0, origin.1], vector![angle.cos(), angle.sin()]);
+        let filter = QueryFilter::default().exclude_rigid_body(exclude).exclude_sensors().predicate(&is_enabled);
+        self.query_pipeline.cast_ray(&self.bodies, &self.colliders, &ray, max_range, true, filter)
+    }
+
+    /// Casts a ray against the arena boundaries and the obstacles only.
+    ///
+    /// # Parameters
+    /// - `origin`: A tuple representing the (x, y) start of the ray.
+    /// - `angle`: The direction of the ray, in radians.
+    /// - `max_range`: The maximum distance the ray travels.
+    ///
+    /// # Returns
+    /// The distance to the first wall or obstacle, or `None` if there is none within range.
+    pub fn cast_ray_against_walls(&self, origin: (f32, f32), angle: f32, max_range: f32) -> Option<f32> {
+        let ray = Ray::new(point![origin.0, origin.1], vector![angle.cos(), angle.sin()]);
+        let filter = QueryFilter::only_fixed().exclude_sensors().predicate(&is_enabled);
+        self.query_pipeline.cast_ray(&self.bodies, &self.colliders, &ray, max_range, true, filter)
+            .map(|(_, distance)| distance)
+    }
+
+    /// Updates the scene queries after bodies were moved outside of a simulation step.
+    pub fn refresh_queries(&mut self) {
+        self.bodies.propagate_modified_body_positions_to_colliders(&mut self.colliders);
+        self.query_pipeline.update(&self.bodies, &self.colliders);
+    }
+
+    /// Checks whether a collider overlaps an entity or a bullet.
+    ///
+    /// The collider itself is ignored, so this also works for disabled colliders about to be enabled again.
+    ///
+    /// # Parameters
+    /// - `handle`: The handle of the collider.
+    ///
+    /// # Returns
+    /// `true` if a moving body intersects the shape of the collider.
+    pub fn is_collider_occupied(&self, handle: ColliderHandle) -> bool {
+        let Some(collider) = self.colliders.get(handle) else {
+            return false;
+        };
+        let filter = QueryFilter::only_dynamic().exclude_sensors().exclude_collider(handle).predicate(&is_enabled);
+        self.query_pipeline
+            .intersection_with_shape(&self.bodies, &self.colliders, collider.position(), collider.shape(), filter)
+            .is_some()
+    }
+
+    /// Checks whether a circular area does not overlap any collider.
+    ///
+    /// # Parameters
+    /// - `center`: A tuple representing the (x, y) center of the area.
+    /// - `radius`: The radius of the area.
+    ///
+    /// # Returns
+    /// `true` if no wall, obstacle, entity, bullet or sensor is in the area.
+    pub fn is_area_free(&self, center: (f32, f32), radius: f32) -> bool {
+        let shape_position = Isometry::translation(center.0, center.1);
+        self.query_pipeline
+            .intersection_with_shape(&self.bodies, &self.colliders, &shape_position, &Ball::new(radius), QueryFilter::default().predicate(&is_enabled))
+            .is_none()
     }
 
     /// Sets up the boundary colliders for the simulation area.
-    pub fn setup_boundaries(&mut self) {
-        let half_extents = vector![AppDefines::ARENA_WIDTH / 2.0, AppDefines::ARENA_HEIGHT / 2.0];
+    ///
+    /// Any previously created boundaries are removed first, so this can be called again
+    /// to resize the arena at runtime.
+    ///
+    /// # Parameters
+    /// - `arena_width`: The width of the arena.
+    /// - `arena_height`: The height of the arena.
+    pub fn setup_boundaries(&mut self, arena_width: f32, arena_height: f32) {
+        for handle in self.boundary_handles.drain(..) {
+            self.colliders.remove(handle, &mut self.islands, &mut self.bodies, true);
+        }
+
+        let half_extents = vector![arena_width / 2.0, arena_height / 2.0];
         let top_boundary = ColliderBuilder::cuboid(half_extents.x, 1.0)
-            .translation(vector![half_extents.x, AppDefines::ARENA_HEIGHT])
+            .translation(vector![half_extents.x, arena_height])
             .build();
         let bottom_boundary = ColliderBuilder::cuboid(half_extents.x, 1.0)
             .translation(vector![half_extents.x, 0.0])
@@ -90,17 +179,14 @@ impl PhysicsEngine {
             .translation(vector![0.0, half_extents.y])
             .build();
         let right_boundary = ColliderBuilder::cuboid(1.0, half_extents.y)
-            .translation(vector![AppDefines::ARENA_WIDTH, half_extents.y])
+            .translation(vector![arena_width, half_extents.y])
             .build();
 
-        self.colliders.insert(top_boundary);
-        self.colliders.insert(bottom_boundary);
-        self.colliders.insert(left_boundary);
-        self.colliders.insert(right_boundary);
-    }
-
-    /// Sets up the physics simulation, including boundaries.
-    pub fn setup_physics(&mut self) {
-        self.setup_boundaries();
+        self.boundary_handles = vec![
+            self.colliders.insert(top_boundary),
+            self.colliders.insert(bottom_boundary),
+            self.colliders.insert(left_boundary),
+            self.colliders.insert(right_boundary),
+        ];
     }
 }
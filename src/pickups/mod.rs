@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+
+use rapier2d::prelude::*;
+
+use crate::app_defines::AppDefines;
+use crate::physics::physics::PhysicsEngine;
+
+/// Represents the kinds of items entities can collect in the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Restores some health at once.
+    Health,
+    /// Makes the entity move faster for a while.
+    SpeedBoost,
+    /// Protects the entity from bullet damage for a while.
+    Shield,
+    /// Shortens the delay between two shots for a while.
+    RapidFire,
+    /// Makes the bullets of the entity deal more damage for a while.
+    ExtraDamage,
+}
+
+impl PickupKind {
+    /// All the kinds of pickups.
+    pub const ALL: [PickupKind; 5] = [
+        PickupKind::Health,
+        PickupKind::SpeedBoost,
+        PickupKind::Shield,
+        PickupKind::RapidFire,
+        PickupKind::ExtraDamage,
+    ];
+
+    /// Returns the name of the pickup, as used by the protocol.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PickupKind::Health => "HEALTH",
+            PickupKind::SpeedBoost => "SPEED",
+            PickupKind::Shield => "SHIELD",
+            PickupKind::RapidFire => "RAPIDFIRE",
+            PickupKind::ExtraDamage => "DAMAGE",
+        }
+    }
+
+    /// Returns how long the effect of the pickup lasts once collected.
+    ///
+    /// # Returns
+    /// The duration of the effect, zero for the pickups applied at once.
+    pub fn effect_duration(&self) -> Duration {
+        match self {
+            PickupKind::Health => Duration::ZERO,
+            _ => Duration::from_secs_f32(AppDefines::PICKUP_EFFECT_DURATION),
+        }
+    }
+}
+
+/// Represents an item lying in the arena until an entity collects it.
+///
+/// The item is a sensor collider, so it detects the entities touching it without blocking anything.
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub position: (f32, f32),
+    pub collider_handle: ColliderHandle,
+}
+
+impl Pickup {
+    /// Creates a new pickup and adds its sensor collider to the physics engine.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A mutable reference to the physics engine.
+    /// - `kind`: The kind of the pickup.
+    /// - `position`: A tuple representing the (x, y) position of the pickup.
+    ///
+    /// # Returns
+    /// A new instance of `Pickup`.
+    pub fn new(physics_engine: &mut PhysicsEngine, kind: PickupKind, position: (f32, f32)) -> Self {
+        let collider = ColliderBuilder::ball(AppDefines::PICKUP_RADIUS)
+            .translation(vector![position.0, position.1])
+            .sensor(true)
+            .build();
+        let collider_handle = physics_engine.colliders.insert(collider);
+
+        Self {
+            kind,
+            position,
+            collider_handle,
+        }
+    }
+
+    /// Finds the rigid bodies currently touching the pickup.
+    ///
+    /// # Parameters
+    /// - `physics_engine`: A reference to the physics engine, updated by its last step.
+    ///
+    /// # Returns
+    /// The handles of the rigid bodies intersecting the sensor of the pickup.
+    pub fn touching_bodies(&self, physics_engine: &PhysicsEngine) -> Vec<RigidBodyHandle> {
+        physics_engine.narrow_phase.intersection_pairs_with(self.collider_handle)
+            .filter(|&(_, _, intersecting)| intersecting)
+            .map(|(collider1, collider2, _)| if collider1 == self.collider_handle { collider2 } else { collider1 })
+            .filter_map(|collider| physics_engine.colliders.get(collider)?.parent())
+            .collect()
+    }
+}
+
+/// Represents a timed effect of a collected pickup on an entity.
+pub struct ActiveEffect {
+    pub kind: PickupKind,
+    /// The time at which the effect ends.
+    pub expires_at: Instant,
+}
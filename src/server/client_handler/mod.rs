@@ -1,13 +1,16 @@
-use crate::app_defines::AppDefines;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
 use std::net::{Shutdown, TcpStream};
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant};
 
+use crate::game_logic::GameLogic;
 use crate::server::server_thread::ServerSettings;
+use crate::server::session::Session;
 use crate::types::{add_message, MessageType, StyledMessage};
 
 /// A struct representing a client handler, responsible for communicating with a client via a TCP socket.
+///
+/// Each line received is a command line of the text protocol, and each reply is sent back as one line.
 pub(crate) struct ClientHandler {
     /// The TCP socket associated with the client.
     pub(crate) socket: TcpStream,
@@ -15,74 +18,81 @@ pub(crate) struct ClientHandler {
     pub(crate) buf_writer: BufWriter<TcpStream>,
     /// A buffer for reading data from the socket.
     pub(crate) buf_reader: BufReader<TcpStream>,
-    /// The time in seconds since the Unix epoch of the client's last activity.
-    pub(crate) previous_time: u64,
-    /// A thread-safe, shared vector of styled messages.
-    pub(crate) messages: Arc<Mutex<Vec<StyledMessage>>>,
-    /// Thread-safe, shared server settings.
-    pub(crate) settings: Arc<Mutex<ServerSettings>>,
+    /// The session of the client, processing its commands.
+    pub(crate) session: Session,
 }
 
 impl ClientHandler {
-    /// Creates a new client handler with the specified socket, messages, and server settings.
+    /// Creates a new client handler with the specified socket, messages, server settings, and game logic.
     ///
     /// # Arguments
     ///
     /// * `socket` - The client's TCP socket.
     /// * `messages` - A thread-safe, shared vector of styled messages.
     /// * `settings` - Thread-safe, shared server settings.
+    /// * `game_logic` - Thread-safe, shared game logic.
     ///
     /// # Returns
     ///
     /// A new `ClientHandler`.
     ///
-    pub fn new(socket: TcpStream, messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>) -> Self {
+    pub fn new(socket: TcpStream, messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>, game_logic: Arc<Mutex<GameLogic>>) -> Self {
         let buf_writer = BufWriter::new(socket.try_clone().unwrap());
         let buf_reader = BufReader::new(socket.try_clone().unwrap());
         ClientHandler {
             socket,
             buf_writer,
             buf_reader,
-            previous_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-            messages,
-            settings,
+            session: Session::new(messages, settings, game_logic),
         }
     }
 
     /// Starts the client handler, reading messages from the client and processing them until disconnection or timeout.
+    ///
+    /// A client asking to spectate is then only streamed the world state until it disconnects.
+    /// The bot of the client is removed from the game once the connection ends.
     pub fn run(&mut self) {
         let mut received_message = String::new();
-        let mut running = true;
-        while running {
+        loop {
             if self.check_timeout() {
                 break;
             }
 
             if let Ok(message_length) = self.buf_reader.read_line(&mut received_message) {
                 if message_length > 1 {
-                    self.handle_received_message(&received_message);
+                    let replies = self.session.handle_received_message(&received_message);
                     received_message.clear();
+                    for reply in replies {
+                        self.send_reply(&reply);
+                    }
+                    if self.session.quit {
+                        self.handle_disconnection();
+                        break;
+                    }
+                    if let Some(interval) = self.session.spectate_interval {
+                        self.spectate(interval);
+                        break;
+                    }
                 } else {
                     self.handle_disconnection();
-                    running = false;
                     break;
                 }
             }
         }
+
+        self.session.close();
     }
 
-    /// Checks if the client has exceeded the inactivity timeout.
+    /// Checks if the client has exceeded the inactivity timeout, closing the connection if so.
     ///
     /// # Returns
     ///
     /// `true` if the client has exceeded the inactivity timeout, `false` otherwise.
     ///
     fn check_timeout(&mut self) -> bool {
-        let now = SystemTime::now();
-        let current_time = now.duration_since(UNIX_EPOCH).unwrap().as_secs();
-        if current_time - self.previous_time > AppDefines::CONNECTION_TIMEOUT_DELAY as u64 {
+        if self.session.check_timeout() {
             add_message(
-                &self.messages,
+                &self.session.messages,
                 format!("[WARNING] Connection timeout: {}", self.socket.peer_addr().unwrap()),
                 MessageType::Warning,
             );
@@ -93,110 +103,84 @@ impl ClientHandler {
         }
     }
 
-    /// Handles a message received from the client.
+    /// Sends a reply to the client, as one line.
     ///
     /// # Arguments
     ///
-    /// * `received_message` - The received message as a string.
+    /// * `reply` - The reply to send.
     ///
-    fn handle_received_message(&mut self, received_message: &str) {
-        let all_messages: Vec<&str> = received_message.trim().split(AppDefines::COMMAND_SEP).collect();
-        for message in all_messages {
-            // println!("Message {:?}", message);
-            match message {
-                AppDefines::QUIT => {
-                    self.handle_disconnection();
-                    return;
-                }
-                _ => self.process_message(message),
-            };
-            self.previous_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    fn send_reply(&mut self, reply: &str) {
+        if let Err(e) = writeln!(self.buf_writer, "{}", reply) {
+            println!("Failed to send response: {}", e);
+        }
+
+        if let Err(e) = self.buf_writer.flush() {
+            println!("Failed to flush response: {}", e);
         }
     }
 
-    /// Processes an individual message from the client.
+    /// Streams the world state to the client, one line per interval, until it disconnects.
+    ///
+    /// Spectators are not subject to the inactivity timeout, and any command other than `EXIT` is ignored.
     ///
     /// # Arguments
     ///
-    /// * `received` - The received message as a string.
-    ///
-    fn process_message(&mut self, received: &str) {
-        let mut message_values = received.split(AppDefines::ARGUMENT_SEP).collect::<Vec<&str>>();
-        let code_message = message_values[0];
-        println!("Processing message: {:?}", received);
-        println!("Values: {:?}", message_values);
-        println!("Commande values: {:?}", code_message);
-        println!("\n");
-        let response = match code_message {
-            AppDefines::SET_NAME => {
-                "TODO SET NAME".to_string()
-            }
-            AppDefines::SET_COLOR => {
-                "TODO SET COLOR".to_string()
-            }
-            AppDefines::ALIVE => {
-                "TODO ALIVE".to_string()
-            }
-            AppDefines::MESSAGE => {
-                "TODO MESSAGE".to_string()
-            }
-            AppDefines::QUERY_CLOSEST_BOT => {
-                "TODO QUERY CLOSEST BOT".to_string()
-            }
-            AppDefines::QUERY_CLOSEST_PROJECTILE => {
-                "TODO QUERY CLOSEST PROJECTILE".to_string()
-            }
-            AppDefines::QUERY_BY_NAME => {
-                "TODO QUERY BY NAME".to_string()
-            }
-            AppDefines::QUERY_NAME_LIST => {
-                "TODO QUERY NAME LIST".to_string()
-            }
-            AppDefines::QUERY_ORIENTATION => {
-                "TODO QUERY ORIENTATION".to_string()
-            }
-            AppDefines::QUERY_MESSAGES_FROM_USER => {
-                "TODO QUERY MESSAGES FROM USER".to_string()
+    /// * `interval` - The delay between two world states.
+    ///
+    fn spectate(&mut self, interval: Duration) {
+        add_message(
+            &self.session.messages,
+            format!("[INFO] Spectator connected: {:?}", Result::unwrap(self.socket.peer_addr())),
+            MessageType::Info,
+        );
+
+        let mut received_message = String::new();
+        let mut next_frame = Instant::now();
+        loop {
+            let now = Instant::now();
+            if now >= next_frame {
+                let state = self.session.world_state();
+                if writeln!(self.buf_writer, "{}", state).and_then(|_| self.buf_writer.flush()).is_err() {
+                    break;
+                }
+                // Skip the frames missed while the game logic was busy rather than bursting them
+                next_frame += interval;
+                if next_frame <= now {
+                    next_frame = now + interval;
+                }
+                continue;
             }
-            AppDefines::EMPTY_REPLY => {
-                "TODO EMPTY REPLY".to_string()
+
+            // Wait for the next frame while still listening for the client leaving
+            if self.socket.set_read_timeout(Some(next_frame - now)).is_err() {
+                break;
             }
-            _ => {
-                "ERROR".to_string()
+            match self.buf_reader.read_line(&mut received_message) {
+                Ok(0) => {
+                    self.handle_disconnection();
+                    break;
+                }
+                Ok(_) => {
+                    if self.session.is_quit_request(&received_message) {
+                        self.handle_disconnection();
+                        break;
+                    }
+                    received_message.clear();
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => break,
             }
-        };
-        if let Err(e) = self.buf_writer.write_all(response.as_bytes()) {
-            println!("Failed to send response: {}", e);
-        }
-
-        if let Err(e) = self.buf_writer.flush() {
-            println!("Failed to flush response: {}", e);
         }
     }
+
+
+    /// Handles the disconnection of the client.
     fn handle_disconnection(&mut self) {
         add_message(
-            &self.messages,
+            &self.session.messages,
             format!("[INFO] Client disconnected: {:?}", Result::unwrap(self.socket.peer_addr())),
             MessageType::Info,
         );
         self.socket.shutdown(Shutdown::Both).expect("Failed to shutdown socket");
     }
-
-    /// Adds a message to the response string.
-    ///
-    /// # Arguments
-    ///
-    /// * `response` - The existing response string.
-    /// * `message` - The message to add to the response.
-    ///
-    /// # Returns
-    ///
-    /// The updated response string with the new message appended.
-    ///
-    pub fn add_to_reponse(mut reponse: String, message: String) {
-        if reponse != "" {
-            reponse += AppDefines::COMMAND_SEP;
-            return reponse += &*message;
-        }
-    }
 }
@@ -1,2 +1,4 @@
 pub(crate) mod client_handler;
+pub(crate) mod session;
+pub(crate) mod websocket_handler;
 pub(crate) mod server_thread;
\ No newline at end of file
@@ -1,15 +1,20 @@
 use std::sync::{Arc, Mutex};
-use std::net::TcpListener;
+use std::net::{TcpListener, TcpStream};
 use std::thread;
 use std::time::Duration;
-use egui::Key::A;
+use serde::{Deserialize, Serialize};
 use crate::app_defines::AppDefines;
 
+use crate::game_logic::GameLogic;
 use crate::server::client_handler::ClientHandler;
+use crate::server::websocket_handler::WebSocketHandler;
 use crate::types::{add_message, MessageType, StyledMessage};
 
 /// A struct representing server settings.
-#[derive(Debug)]
+///
+/// Missing fields are filled with the defaults from `AppDefines` when deserializing, and unknown fields are rejected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub(crate) struct ServerSettings {
     /// The width of the arena.
     pub arena_width: f32,
@@ -17,12 +22,26 @@ pub(crate) struct ServerSettings {
     pub arena_height: f32,
     /// The probability of obstacles appearing in the arena.
     pub obstacle_probability: f64,
-    /// The available game modes.
-    pub game_modes: Vec<&'static str>,
+    /// The hit points of generated obstacles, 0 for indestructible obstacles.
+    pub obstacle_health: f32,
+    /// The delay in seconds before a destroyed obstacle comes back, 0 for no regeneration.
+    pub obstacle_regeneration_delay: f32,
+    /// The maximum speed of the entities, in units per second.
+    pub max_speed: f32,
+    /// The maximum acceleration of the entities, in units per second squared.
+    pub acceleration: f32,
+    /// The maximum speed at which the entities turn their body, in degrees per second.
+    pub turn_rate: f32,
+    /// The delay in seconds between two pickups spawning in the arena, 0 to disable pickups.
+    pub pickup_spawn_interval: f32,
+    /// The maximum number of pickups lying in the arena at the same time.
+    pub pickup_max_count: usize,
     /// The rate of fire for bots.
     pub bot_rate_of_fire: i32,
-    /// The penalty time for infractions.
+    /// The time in milliseconds a penalized bot is not allowed to fire.
     pub penalty_time: i64,
+    /// Whether firing with an empty weapon penalizes the bot, instead of only replying `NO_AMMO`.
+    pub empty_fire_penalty: bool,
     /// The delay before a connection times out.
     pub connection_timeout_delay: i32,
     /// The duration messages are displayed.
@@ -31,6 +50,16 @@ pub(crate) struct ServerSettings {
     pub message_length: i32,
     /// The score limit for the game.
     pub score_limit: i32,
+    /// Whether the queries of the bots are restricted to what they can see.
+    pub fog_of_war: bool,
+    /// The distance a bot can see when the fog of war is enabled.
+    pub view_range: f32,
+    /// The angle of the view cone of a bot in degrees, centered on its heading.
+    pub view_angle: f32,
+    /// The port of the raw TCP server, used when the server starts.
+    pub server_port: u16,
+    /// The port of the WebSocket server, used when the server starts.
+    pub websocket_port: u16,
 }
 
 impl ServerSettings {
@@ -45,15 +74,158 @@ impl ServerSettings {
             arena_width: AppDefines::ARENA_WIDTH,
             arena_height: AppDefines::ARENA_HEIGHT,
             obstacle_probability: AppDefines::OBSTACLE_PROBABILITY,
-            game_modes: AppDefines::GAME_MODES.to_vec(),
+            obstacle_health: AppDefines::OBSTACLE_HEALTH,
+            obstacle_regeneration_delay: AppDefines::OBSTACLE_REGENERATION_DELAY,
+            max_speed: AppDefines::ENTITY_MAX_SPEED,
+            acceleration: AppDefines::ENTITY_ACCELERATION,
+            turn_rate: AppDefines::ENTITY_TURN_RATE,
+            pickup_spawn_interval: AppDefines::PICKUP_SPAWN_INTERVAL,
+            pickup_max_count: AppDefines::PICKUP_MAX_COUNT,
             bot_rate_of_fire: AppDefines::BOT_RATE_OF_FIRE,
             penalty_time: AppDefines::PENALTY_TIME,
+            empty_fire_penalty: AppDefines::EMPTY_FIRE_PENALTY,
             connection_timeout_delay: AppDefines::CONNECTION_TIMEOUT_DELAY,
             message_duration: AppDefines::MESSAGE_DURATION,
             message_length: AppDefines::MESSAGE_LENGTH,
             score_limit: AppDefines::SCORE_LIMIT,
+            fog_of_war: AppDefines::FOG_OF_WAR,
+            view_range: AppDefines::VIEW_RANGE,
+            view_angle: AppDefines::VIEW_ANGLE,
+            server_port: AppDefines::SERVER_PORT,
+            websocket_port: AppDefines::WEBSOCKET_PORT,
         }
     }
+
+    /// Overrides a single setting from its name and textual value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the setting, as in the configuration file (e.g. `arena_width`).
+    /// * `value` - The new value of the setting.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the setting was overridden, or an error message if the name or value is invalid.
+    ///
+    pub fn set_from_str(&mut self, name: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| format!("Invalid value '{}' for setting '{}'", value, name))
+        }
+
+        match name {
+            "arena_width" => self.arena_width = parse(name, value)?,
+            "arena_height" => self.arena_height = parse(name, value)?,
+            "obstacle_probability" => self.obstacle_probability = parse(name, value)?,
+            "obstacle_health" => self.obstacle_health = parse(name, value)?,
+            "obstacle_regeneration_delay" => self.obstacle_regeneration_delay = parse(name, value)?,
+            "max_speed" => self.max_speed = parse(name, value)?,
+            "acceleration" => self.acceleration = parse(name, value)?,
+            "turn_rate" => self.turn_rate = parse(name, value)?,
+            "pickup_spawn_interval" => self.pickup_spawn_interval = parse(name, value)?,
+            "pickup_max_count" => self.pickup_max_count = parse(name, value)?,
+            "bot_rate_of_fire" => self.bot_rate_of_fire = parse(name, value)?,
+            "penalty_time" => self.penalty_time = parse(name, value)?,
+            "empty_fire_penalty" => self.empty_fire_penalty = parse(name, value)?,
+            "connection_timeout_delay" => self.connection_timeout_delay = parse(name, value)?,
+            "message_duration" => self.message_duration = parse(name, value)?,
+            "message_length" => self.message_length = parse(name, value)?,
+            "score_limit" => self.score_limit = parse(name, value)?,
+            "fog_of_war" => self.fog_of_war = parse(name, value)?,
+            "view_range" => self.view_range = parse(name, value)?,
+            "view_angle" => self.view_angle = parse(name, value)?,
+            "server_port" => self.server_port = parse(name, value)?,
+            "websocket_port" => self.websocket_port = parse(name, value)?,
+            _ => return Err(format!("Unknown setting '{}'", name)),
+        }
+        Ok(())
+    }
+
+    /// Checks that every setting is within its allowed range.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the settings are valid, or the list of error messages describing each bad value.
+    ///
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if !(AppDefines::ARENA_MIN_SIZE..=AppDefines::ARENA_MAX_SIZE).contains(&self.arena_width) {
+            errors.push(format!(
+                "Arena width must be between {} and {}, got {}",
+                AppDefines::ARENA_MIN_SIZE, AppDefines::ARENA_MAX_SIZE, self.arena_width
+            ));
+        }
+        if !(AppDefines::ARENA_MIN_SIZE..=AppDefines::ARENA_MAX_SIZE).contains(&self.arena_height) {
+            errors.push(format!(
+                "Arena height must be between {} and {}, got {}",
+                AppDefines::ARENA_MIN_SIZE, AppDefines::ARENA_MAX_SIZE, self.arena_height
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.obstacle_probability) {
+            errors.push(format!("Obstacle probability must be between 0 and 1, got {}", self.obstacle_probability));
+        }
+        if self.obstacle_health < 0.0 {
+            errors.push(format!("Obstacle health cannot be negative, got {}", self.obstacle_health));
+        }
+        if self.obstacle_regeneration_delay < 0.0 {
+            errors.push(format!("Obstacle regeneration delay cannot be negative, got {}", self.obstacle_regeneration_delay));
+        }
+        if self.max_speed <= 0.0 {
+            errors.push(format!("Max speed must be positive, got {}", self.max_speed));
+        }
+        if self.acceleration <= 0.0 {
+            errors.push(format!("Acceleration must be positive, got {}", self.acceleration));
+        }
+        if self.turn_rate <= 0.0 {
+            errors.push(format!("Turn rate must be positive, got {}", self.turn_rate));
+        }
+        if self.pickup_spawn_interval < 0.0 {
+            errors.push(format!("Pickup spawn interval cannot be negative, got {}", self.pickup_spawn_interval));
+        }
+        if self.bot_rate_of_fire <= 0 {
+            errors.push(format!("Bot rate of fire must be positive, got {}", self.bot_rate_of_fire));
+        }
+        if self.penalty_time < 0 {
+            errors.push(format!("Penalty time cannot be negative, got {}", self.penalty_time));
+        }
+        if self.connection_timeout_delay <= 0 {
+            errors.push(format!("Connection timeout delay must be positive, got {}", self.connection_timeout_delay));
+        }
+        if self.message_duration <= 0 {
+            errors.push(format!("Message duration must be positive, got {}", self.message_duration));
+        }
+        if self.message_length <= 0 {
+            errors.push(format!("Message length must be positive, got {}", self.message_length));
+        }
+        if self.score_limit == 0 || self.score_limit < -1 {
+            errors.push(format!("Score limit must be positive or -1 for no limit, got {}", self.score_limit));
+        }
+        if self.view_range <= 0.0 {
+            errors.push(format!("View range must be positive, got {}", self.view_range));
+        }
+        if self.view_angle <= 0.0 || self.view_angle > 360.0 {
+            errors.push(format!("View angle must be between 0 and 360 degrees, got {}", self.view_angle));
+        }
+        if self.server_port == 0 || self.websocket_port == 0 {
+            errors.push(format!("Ports must be positive, got {} and {}", self.server_port, self.websocket_port));
+        }
+        if self.server_port == self.websocket_port {
+            errors.push(format!("Server and WebSocket ports must differ, got {} for both", self.server_port));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for ServerSettings {
+    /// Creates a new default `ServerSettings` instance, see `ServerSettings::new`.
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// A struct representing a server thread.
@@ -66,10 +238,12 @@ pub(crate) struct ServerThread {
     pub(crate) messages: Arc<Mutex<Vec<StyledMessage>>>,
     /// Thread-safe, shared server settings.
     pub(crate) settings: Arc<Mutex<ServerSettings>>,
+    /// Thread-safe, shared game logic.
+    pub(crate) game_logic: Arc<Mutex<GameLogic>>,
 }
 
 impl ServerThread {
-    /// Creates a new server thread with the specified address, port, messages, and settings.
+    /// Creates a new server thread with the specified address, port, messages, settings, and game logic.
     ///
     /// # Arguments
     ///
@@ -77,27 +251,66 @@ impl ServerThread {
     /// * `port` - The port on which the server listens.
     /// * `messages` - A thread-safe, shared vector of styled messages.
     /// * `settings` - Thread-safe, shared server settings.
+    /// * `game_logic` - Thread-safe, shared game logic.
     ///
     /// # Returns
     ///
     /// A new `ServerThread` instance.
     ///
-    pub fn new(address: String, port: u16, messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>) -> Self {
+    pub fn new(address: String, port: u16, messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>, game_logic: Arc<Mutex<GameLogic>>) -> Self {
         ServerThread {
             address,
             port,
             messages,
             settings,
+            game_logic,
         }
     }
 
     /// Starts the server thread, listening for incoming connections and spawning a new client handler for each connection.
     pub(crate) fn start(&self) {
-        let listener = TcpListener::bind((self.address.to_string(), self.port)).expect("Could not bind to port");
+        self.listen("Server", |stream, messages, settings, game_logic| {
+            ClientHandler::new(stream, messages, settings, game_logic).run();
+        });
+    }
+
+    /// Starts the server thread, listening for incoming WebSocket connections and spawning a new WebSocket handler
+    /// for each connection.
+    pub(crate) fn start_websocket(&self) {
+        self.listen("WebSocket server", |stream, messages, settings, game_logic| {
+            match WebSocketHandler::new(stream, Arc::clone(&messages), settings, game_logic) {
+                Ok(mut handler) => handler.run(),
+                Err(e) => add_message(&messages, format!("[ERROR] {}", e), MessageType::Error),
+            }
+        });
+    }
+
+    /// Listens for incoming connections, running a handler in a new thread for each connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The name of the server in the start messages.
+    /// * `handle` - The function serving a connection until it ends.
+    ///
+    fn listen<F>(&self, label: &str, handle: F)
+    where
+        F: Fn(TcpStream, Arc<Mutex<Vec<StyledMessage>>>, Arc<Mutex<ServerSettings>>, Arc<Mutex<GameLogic>>) + Copy + Send + 'static,
+    {
+        let listener = match TcpListener::bind((self.address.to_string(), self.port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                add_message(
+                    &self.messages,
+                    format!("[ERROR] {} could not bind to port {}: {}", label, self.port, e),
+                    MessageType::Error,
+                );
+                return;
+            }
+        };
 
         add_message(
             &self.messages,
-            format!("\n[START] Server address: {:?}", listener.local_addr().unwrap()),
+            format!("\n[START] {} address: {:?}", label, listener.local_addr().unwrap()),
             MessageType::Default,
         );
         add_message(
@@ -116,9 +329,10 @@ impl ServerThread {
                     );
                     let messages = Arc::clone(&self.messages);
                     let settings = Arc::clone(&self.settings);
+                    let game_logic = Arc::clone(&self.game_logic);
                     stream.set_read_timeout(Some(Duration::from_millis(100))).unwrap(); // Set timeout
                     thread::spawn(move || {
-                        ClientHandler::new(stream, messages, settings).run();
+                        handle(stream, messages, settings, game_logic);
                     });
                 }
                 Err(e) => {
@@ -133,3 +347,39 @@ impl ServerThread {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ports_are_read_from_the_settings() {
+        let mut settings = ServerSettings::new();
+        settings.set_from_str("server_port", "7000").unwrap();
+        settings.set_from_str("websocket_port", "7001").unwrap();
+        assert_eq!((settings.server_port, settings.websocket_port), (7000, 7001));
+        assert!(settings.validate().is_ok());
+        assert!(settings.set_from_str("server_port", "70000").is_err());
+    }
+
+    #[test]
+    fn servers_cannot_share_a_port() {
+        let mut settings = ServerSettings::new();
+        settings.websocket_port = settings.server_port;
+        assert_eq!(settings.validate().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn port_in_use_is_reported_instead_of_panicking() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let settings = Arc::new(Mutex::new(ServerSettings::new()));
+        let game_logic = Arc::new(Mutex::new(GameLogic::new(Arc::clone(&settings))));
+
+        ServerThread::new("127.0.0.1".to_string(), port, Arc::clone(&messages), settings, game_logic).start();
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].text.starts_with("[ERROR] Server could not bind to port"));
+    }
+}
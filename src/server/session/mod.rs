@@ -0,0 +1,865 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rapier2d::prelude::RigidBodyHandle;
+use urss_protocol::Keywords;
+
+use crate::app_defines::AppDefines;
+use crate::game_logic::world_state::{ObstacleState, WorldState, ZoneState};
+use crate::game_logic::{GameLogic, RayHit};
+use crate::weapons::{ShotError, WeaponKind};
+use crate::server::server_thread::ServerSettings;
+use crate::types::StyledMessage;
+
+use self::protocol::{PickupSighting, Protocol, Reply, Request};
+
+pub(crate) mod protocol;
+
+/// A struct representing the session of a client, whatever the transport it is connected with.
+///
+/// The session parses the commands of the client, applies them to the game and builds their replies.
+/// Both protocols go through the same dispatcher: the text protocol is spoken by default, and a client
+/// switches to JSON lines with the `PROTOCOL` command.
+/// It owns the bot of the client, which must be removed from the game with `close` once the client leaves.
+pub(crate) struct Session {
+    /// The time in seconds since the Unix epoch of the client's last activity.
+    pub(crate) previous_time: u64,
+    /// A thread-safe, shared vector of styled messages.
+    pub(crate) messages: Arc<Mutex<Vec<StyledMessage>>>,
+    /// Thread-safe, shared server settings.
+    pub(crate) settings: Arc<Mutex<ServerSettings>>,
+    /// Thread-safe, shared game logic.
+    pub(crate) game_logic: Arc<Mutex<GameLogic>>,
+    /// The handle of the bot controlled by the client, once it has sent its name.
+    pub(crate) entity_handle: Option<RigidBodyHandle>,
+    /// The delay between two world states streamed to the client, once it has asked to spectate.
+    pub(crate) spectate_interval: Option<Duration>,
+    /// The time of the last path query of the client, to limit how often it plans paths.
+    pub(crate) last_path_query: Option<Instant>,
+    /// Whether the client has asked to quit.
+    pub(crate) quit: bool,
+    /// The protocol the client speaks.
+    pub(crate) protocol: Protocol,
+}
+
+impl Session {
+    /// Creates a new session with the specified messages, server settings, and game logic.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - A thread-safe, shared vector of styled messages.
+    /// * `settings` - Thread-safe, shared server settings.
+    /// * `game_logic` - Thread-safe, shared game logic.
+    ///
+    /// # Returns
+    ///
+    /// A new `Session` without any bot.
+    ///
+    pub fn new(messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>, game_logic: Arc<Mutex<GameLogic>>) -> Self {
+        Session {
+            previous_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            messages,
+            settings,
+            game_logic,
+            entity_handle: None,
+            spectate_interval: None,
+            last_path_query: None,
+            quit: false,
+            protocol: Protocol::Text,
+        }
+    }
+
+    /// Checks if the client has exceeded the inactivity timeout.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the client has exceeded the inactivity timeout, `false` otherwise.
+    ///
+    pub fn check_timeout(&self) -> bool {
+        let current_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let timeout_delay = self.settings.lock().unwrap().connection_timeout_delay as u64;
+        current_time.saturating_sub(self.previous_time) > timeout_delay
+    }
+
+    /// Handles a line of commands received from the client.
+    ///
+    /// The commands are processed in order until the client asks to quit, which sets `quit`.
+    /// The replies are encoded in the protocol the line was sent in, so a protocol switch applies from the next line.
+    ///
+    /// # Arguments
+    ///
+    /// * `received_message` - The received line, with the commands separated by `#` in the text protocol
+    ///   and a single request object in the JSON protocol.
+    ///
+    /// # Returns
+    ///
+    /// The replies to send back to the client, one for each processed command.
+    ///
+    pub fn handle_received_message(&mut self, received_message: &str) -> Vec<String> {
+        let protocol = self.protocol;
+        let mut replies = Vec::new();
+        match protocol {
+            Protocol::Text => {
+                let all_messages: Vec<&str> = received_message.trim().split(Keywords::COMMAND_SEP).collect();
+                for message in all_messages {
+                    // println!("Message {:?}", message);
+                    match message {
+                        Keywords::QUIT => {
+                            self.quit = true;
+                            break;
+                        }
+                        _ => replies.push(self.process_message(message).encode(protocol, None)),
+                    };
+                    self.previous_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                }
+            }
+            Protocol::Json => match serde_json::from_str::<Request>(received_message) {
+                Ok(request) if request.command == Keywords::QUIT => self.quit = true,
+                Ok(request) => {
+                    let reply = match request.arguments() {
+                        Some(arguments) => {
+                            let arguments = arguments.iter().map(String::as_str).collect::<Vec<_>>();
+                            self.dispatch(&request.command, &arguments)
+                        }
+                        None => Reply::error(Keywords::INVALID_REQUEST_REPLY),
+                    };
+                    replies.push(reply.encode(protocol, request.id));
+                    self.previous_time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                }
+                Err(_) => replies.push(Reply::error(Keywords::INVALID_REQUEST_REPLY).encode(protocol, None)),
+            },
+        }
+        replies
+    }
+
+    /// Checks whether a line received from the client asks to quit, without processing its other commands.
+    ///
+    /// # Arguments
+    ///
+    /// * `received_message` - The received line.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the line holds the `EXIT` command in the protocol of the client, `false` otherwise.
+    ///
+    pub fn is_quit_request(&self, received_message: &str) -> bool {
+        match self.protocol {
+            Protocol::Text => received_message.trim().split(Keywords::COMMAND_SEP).any(|message| message == Keywords::QUIT),
+            Protocol::Json => serde_json::from_str::<Request>(received_message)
+                .is_ok_and(|request| request.command == Keywords::QUIT),
+        }
+    }
+
+    /// Encodes the current world state, as streamed to spectators.
+    ///
+    /// # Returns
+    ///
+    /// The world state as one line of the protocol of the client.
+    ///
+    pub fn world_state(&self) -> String {
+        Reply::State(WorldState::capture(&self.game_logic.lock().unwrap())).encode(self.protocol, None)
+    }
+
+    /// Ends the session, removing the bot of the client from the game.
+    pub fn close(&mut self) {
+        if let Some(handle) = self.entity_handle.take() {
+            self.game_logic.lock().unwrap().remove_entity(handle);
+        }
+    }
+
+    /// Processes an individual message from the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `received` - The received message as a string.
+    ///
+    /// # Returns
+    ///
+    /// The reply to send back to the client.
+    ///
+    fn process_message(&mut self, received: &str) -> Reply {
+        let message_values = received.split(Keywords::ARGUMENT_SEP).collect::<Vec<&str>>();
+        let code_message = message_values[0];
+        self.dispatch(code_message, &message_values[1..])
+    }
+
+    /// Runs a command, whatever the protocol it was received in.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command keyword.
+    /// * `arguments` - The arguments of the command.
+    ///
+    /// # Returns
+    ///
+    /// The reply to send back to the client.
+    ///
+    fn dispatch(&mut self, command: &str, arguments: &[&str]) -> Reply {
+        match command {
+            Keywords::SET_NAME => {
+                self.set_name(arguments)
+            }
+            Keywords::SET_COLOR => {
+                Reply::Todo { message: "TODO SET COLOR" }
+            }
+            Keywords::ALIVE => {
+                Reply::Todo { message: "TODO ALIVE" }
+            }
+            Keywords::MESSAGE => {
+                Reply::Todo { message: "TODO MESSAGE" }
+            }
+            Keywords::MOVE => {
+                self.move_bot(arguments)
+            }
+            Keywords::TURN => {
+                self.turn(arguments)
+            }
+            Keywords::AIM => {
+                self.aim(arguments)
+            }
+            Keywords::FIRE => {
+                self.fire()
+            }
+            Keywords::WEAPON => {
+                self.weapon(arguments)
+            }
+            Keywords::RELOAD => {
+                self.reload()
+            }
+            Keywords::SPECTATE => {
+                self.start_spectating(arguments)
+            }
+            Keywords::PROTOCOL => {
+                self.set_protocol(arguments)
+            }
+            Keywords::QUERY_CLOSEST_BOT => {
+                self.query_closest_bot()
+            }
+            Keywords::QUERY_CLOSEST_PROJECTILE => {
+                self.query_closest_projectile()
+            }
+            Keywords::QUERY_BY_NAME => {
+                self.query_bot_by_name(arguments)
+            }
+            Keywords::QUERY_NAME_LIST => {
+                Reply::Todo { message: "TODO QUERY NAME LIST" }
+            }
+            Keywords::QUERY_ORIENTATION => {
+                self.query_orientation()
+            }
+            Keywords::QUERY_MESSAGES_FROM_USER => {
+                Reply::Todo { message: "TODO QUERY MESSAGES FROM USER" }
+            }
+            Keywords::QUERY_STATUS => {
+                self.query_status()
+            }
+            Keywords::QUERY_PICKUPS => {
+                self.query_pickups()
+            }
+            Keywords::QUERY_PATH => {
+                self.query_path(arguments)
+            }
+            Keywords::QUERY_ZONE => {
+                self.query_zone()
+            }
+            Keywords::QUERY_OBSTACLES => {
+                self.query_obstacles()
+            }
+            Keywords::QUERY_RAY => {
+                self.query_ray(arguments)
+            }
+            Keywords::QUERY_LIDAR => {
+                self.query_lidar(arguments)
+            }
+            Keywords::EMPTY_REPLY => {
+                Reply::Todo { message: "TODO EMPTY REPLY" }
+            }
+            _ => {
+                Reply::ERROR
+            }
+        }
+    }
+
+    /// Names the bot of the client, adding it to the game on the first call.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the name of the bot.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the bot was named, `ERROR` if the name is missing or the client is a spectator.
+    ///
+    fn set_name(&mut self, arguments: &[&str]) -> Reply {
+        let Some(name) = arguments.first().map(|name| name.trim()).filter(|name| !name.is_empty()) else {
+            return Reply::ERROR;
+        };
+        // A spectator has no bot, and must not leave one behind in the arena
+        if self.spectate_interval.is_some() {
+            return Reply::ERROR;
+        }
+
+        let mut game_logic = self.game_logic.lock().unwrap();
+        match self.entity_handle.and_then(|handle| game_logic.entity_mut(handle)) {
+            Some(entity) => entity.name = name.to_string(),
+            None => self.entity_handle = Some(game_logic.add_entity(name.to_string())),
+        }
+        Reply::Ok
+    }
+
+    /// Turns the client into a spectator, streamed the world state instead of controlling a bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the optional delay between two world states in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the client is now a spectator, `ERROR` if the interval is invalid or the client already has a bot.
+    ///
+    fn start_spectating(&mut self, arguments: &[&str]) -> Reply {
+        let interval = match arguments.first() {
+            Some(interval) => interval.parse::<u64>().ok().filter(|interval| *interval >= AppDefines::SPECTATE_MIN_INTERVAL),
+            None => Some(AppDefines::SPECTATE_INTERVAL),
+        };
+        let (None, Some(interval)) = (self.entity_handle, interval) else {
+            return Reply::ERROR;
+        };
+
+        self.spectate_interval = Some(Duration::from_millis(interval));
+        Reply::Ok
+    }
+
+    /// Switches the protocol the client speaks, from the next line on.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the name of the protocol, `TEXT` or `JSON`.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the protocol was switched, `ERROR` if the protocol is unknown.
+    ///
+    fn set_protocol(&mut self, arguments: &[&str]) -> Reply {
+        match arguments.first().and_then(|name| Protocol::from_name(name)) {
+            Some(protocol) => {
+                self.protocol = protocol;
+                Reply::Ok
+            }
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Sets the speed the client's bot accelerates to along its heading.
+    ///
+    /// The speed is limited to the max speed of the bot, and reached no faster than its acceleration allows.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the speed in units per second, negative to move backwards.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the bot is accelerating, `ERROR` if the speed is invalid or the client has no bot.
+    ///
+    fn move_bot(&mut self, arguments: &[&str]) -> Reply {
+        let speed = arguments.first().and_then(|speed| speed.parse::<f32>().ok()).filter(|speed| speed.is_finite());
+        let (Some(handle), Some(speed)) = (self.entity_handle, speed) else {
+            return Reply::ERROR;
+        };
+
+        match self.game_logic.lock().unwrap().entity_mut(handle) {
+            Some(entity) => {
+                entity.target_speed = speed;
+                Reply::Ok
+            }
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Turns the body of the client's bot towards a heading, at the turn rate of the bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the absolute angle in radians.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the bot is turning, `ERROR` if the angle is invalid or the client has no bot.
+    ///
+    fn turn(&mut self, arguments: &[&str]) -> Reply {
+        let angle = arguments.first().and_then(|angle| angle.parse::<f64>().ok()).filter(|angle| angle.is_finite());
+        let (Some(handle), Some(angle)) = (self.entity_handle, angle) else {
+            return Reply::ERROR;
+        };
+
+        match self.game_logic.lock().unwrap().entity_mut(handle) {
+            Some(entity) => {
+                entity.target_orientation = angle.rem_euclid(std::f64::consts::TAU);
+                Reply::Ok
+            }
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Turns the gun turret of the client's bot towards an orientation, at the turret turn rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the absolute angle in radians.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the turret is turning, `ERROR` if the angle is invalid or the client has no bot.
+    ///
+    fn aim(&mut self, arguments: &[&str]) -> Reply {
+        let angle = arguments.first().and_then(|angle| angle.parse::<f64>().ok()).filter(|angle| angle.is_finite());
+        let (Some(handle), Some(angle)) = (self.entity_handle, angle) else {
+            return Reply::ERROR;
+        };
+
+        match self.game_logic.lock().unwrap().entity_mut(handle) {
+            Some(entity) => {
+                entity.target_gun_orientation = angle.rem_euclid(std::f64::consts::TAU);
+                Reply::Ok
+            }
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Fires a bullet from the client's bot, in the direction of its gun turret.
+    ///
+    /// Firing with an empty weapon blocks firing for the penalty time when the empty fire penalty is enabled.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if a bullet was fired, `ERROR=reason` if the bot cannot fire (`NO_AMMO`, `RELOADING`, `OVERHEATED`,
+    /// `COOLDOWN`, `PENALTY` or `DEAD`), and `ERROR` if the client has no bot.
+    ///
+    fn fire(&mut self) -> Reply {
+        let mut game_logic = self.game_logic.lock().unwrap();
+        let Some(index) = self.entity_handle
+            .and_then(|handle| game_logic.entities.iter().position(|entity| entity.handle == handle)) else {
+            return Reply::ERROR;
+        };
+
+        match game_logic.shoot_ball(index) {
+            Ok(()) => Reply::Ok,
+            Err(error) => {
+                if error == ShotError::NoAmmo {
+                    let settings = self.settings.lock().unwrap();
+                    if settings.empty_fire_penalty {
+                        let penalty = Duration::from_millis(settings.penalty_time.max(0) as u64);
+                        game_logic.entities[index].penalty_until = Some(Instant::now() + penalty);
+                    }
+                }
+                Reply::error(error.name())
+            }
+        }
+    }
+
+    /// Starts reloading the weapon of the client's bot.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the weapon is being reloaded, `ERROR` if the client has no bot.
+    ///
+    fn reload(&mut self) -> Reply {
+        let mut game_logic = self.game_logic.lock().unwrap();
+        match self.entity_handle.and_then(|handle| game_logic.entity_mut(handle)) {
+            Some(entity) => {
+                entity.reload();
+                Reply::Ok
+            }
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Builds the reply to a status query.
+    ///
+    /// # Returns
+    ///
+    /// `health=weapon=ammo=magazine=reload=heat=overheated` for the client's bot, where `reload` is the time
+    /// left before the reload ends in seconds, `heat` goes from 0 to 1 and `overheated` is 1 while the weapon
+    /// cools down after overheating, or `ERROR` if the client has no bot.
+    ///
+    fn query_status(&self) -> Reply {
+        let game_logic = self.game_logic.lock().unwrap();
+        match self.entity_handle.and_then(|handle| game_logic.entities.iter().find(|entity| entity.handle == handle)) {
+            Some(entity) => Reply::Status {
+                health: entity.health,
+                weapon: entity.weapon.name(),
+                ammo: entity.ammo,
+                magazine: entity.weapon.weapon().magazine,
+                reload: entity.reload_remaining().as_secs_f32(),
+                heat: entity.heat,
+                overheated: entity.overheated,
+            },
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Switches the weapon of the client's bot, or queries it when no weapon is given.
+    ///
+    /// A new weapon starts empty and is reloaded right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the name of the new weapon, if any.
+    ///
+    /// # Returns
+    ///
+    /// `OK` if the weapon was switched, the name of the current weapon when queried, and `ERROR`
+    /// if the weapon is unknown or the client has no bot.
+    ///
+    fn weapon(&mut self, arguments: &[&str]) -> Reply {
+        let Some(handle) = self.entity_handle else {
+            return Reply::ERROR;
+        };
+
+        let mut game_logic = self.game_logic.lock().unwrap();
+        let Some(entity) = game_logic.entity_mut(handle) else {
+            return Reply::ERROR;
+        };
+        match arguments.first() {
+            None => Reply::Weapon { weapon: entity.weapon.name() },
+            Some(name) => match WeaponKind::from_name(name) {
+                Some(weapon) => {
+                    if weapon != entity.weapon {
+                        entity.switch_weapon(weapon);
+                    }
+                    Reply::Ok
+                }
+                None => Reply::ERROR,
+            },
+        }
+    }
+
+    /// Builds the reply to an orientation query.
+    ///
+    /// # Returns
+    ///
+    /// `heading=gun` with the orientations of the body and of the gun turret of the client's bot in radians,
+    /// or `ERROR` if the client has no bot.
+    ///
+    fn query_orientation(&self) -> Reply {
+        let game_logic = self.game_logic.lock().unwrap();
+        match self.entity_handle.and_then(|handle| game_logic.entities.iter().find(|entity| entity.handle == handle)) {
+            Some(entity) => Reply::Orientation {
+                heading: entity.self_orientation,
+                gun: entity.gun_orientation,
+            },
+            None => Reply::ERROR,
+        }
+    }
+
+    /// Builds the reply to a closest bot query.
+    ///
+    /// With the fog of war enabled, only the bots the client's bot can see are considered.
+    ///
+    /// # Returns
+    ///
+    /// `name=x=y=distance` of the closest bot, `EMPTY` if there is none, and `ERROR` if the client has no bot.
+    ///
+    fn query_closest_bot(&self) -> Reply {
+        let Some(handle) = self.entity_handle else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        match game_logic.visible_entities(handle).into_iter().min_by(|a, b| a.2.total_cmp(&b.2)) {
+            Some((entity, position, distance)) => Reply::Bot {
+                name: entity.name.clone(),
+                x: position.0,
+                y: position.1,
+                distance,
+            },
+            None => Reply::Empty,
+        }
+    }
+
+    /// Builds the reply to a closest projectile query.
+    ///
+    /// With the fog of war enabled, only the bullets the client's bot can see are considered.
+    ///
+    /// # Returns
+    ///
+    /// `x=y=vx=vy=distance` of the closest bullet, `EMPTY` if there is none, and `ERROR` if the client has no bot.
+    ///
+    fn query_closest_projectile(&self) -> Reply {
+        let Some(handle) = self.entity_handle else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        match game_logic.visible_bullets(handle).into_iter().min_by(|a, b| a.distance.total_cmp(&b.distance)) {
+            Some(bullet) => Reply::Projectile {
+                x: bullet.position.0,
+                y: bullet.position.1,
+                vx: bullet.velocity.0,
+                vy: bullet.velocity.1,
+                distance: bullet.distance,
+            },
+            None => Reply::Empty,
+        }
+    }
+
+    /// Builds the reply to a bot by name query.
+    ///
+    /// With the fog of war enabled, the bot must be visible to the client's bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the name of the bot.
+    ///
+    /// # Returns
+    ///
+    /// `x=y=distance` of the bot, `EMPTY` if it is not visible or does not exist, and `ERROR`
+    /// if the name is missing or the client has no bot.
+    ///
+    fn query_bot_by_name(&self, arguments: &[&str]) -> Reply {
+        let (Some(handle), Some(name)) = (self.entity_handle, arguments.first()) else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        match game_logic.visible_entities(handle).into_iter().find(|(entity, _, _)| entity.name == name.trim()) {
+            Some((_, position, distance)) => Reply::Position {
+                x: position.0,
+                y: position.1,
+                distance,
+            },
+            None => Reply::Empty,
+        }
+    }
+
+    /// Parses the optional max range argument of the ray queries.
+    ///
+    /// # Arguments
+    ///
+    /// * `argument` - The max range argument, if given.
+    ///
+    /// # Returns
+    ///
+    /// The max range, capped at `AppDefines::RAY_MAX_RANGE`, or `None` if it is not a positive number.
+    ///
+    fn parse_range(argument: Option<&&str>) -> Option<f32> {
+        match argument {
+            Some(range) => range.parse::<f32>().ok()
+                .filter(|range| *range > 0.0)
+                .map(|range| range.min(AppDefines::RAY_MAX_RANGE)),
+            None => Some(AppDefines::RAY_MAX_RANGE),
+        }
+    }
+
+    /// Builds the reply to a ray query, cast from the bot of the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the angle relative to the heading in radians,
+    ///   and optionally the max range.
+    ///
+    /// # Returns
+    ///
+    /// `distance=WALL`, `distance=OBSTACLE`, `distance=BOT=name` or `distance=BULLET` for the first hit,
+    /// `EMPTY` if nothing is within range, and `ERROR` if the arguments are invalid or the client has no bot.
+    ///
+    fn query_ray(&self, arguments: &[&str]) -> Reply {
+        let angle = arguments.first().and_then(|angle| angle.parse::<f32>().ok());
+        let (Some(handle), Some(angle), Some(max_range)) = (self.entity_handle, angle, Self::parse_range(arguments.get(1))) else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        match game_logic.cast_ray(handle, angle, max_range) {
+            Some((distance, hit)) => match hit {
+                RayHit::Wall => Reply::Ray { distance, hit: Keywords::RAY_HIT_WALL, bot: None },
+                RayHit::Obstacle => Reply::Ray { distance, hit: Keywords::RAY_HIT_OBSTACLE, bot: None },
+                RayHit::Bot(name) => Reply::Ray { distance, hit: Keywords::RAY_HIT_BOT, bot: Some(name) },
+                RayHit::Bullet => Reply::Ray { distance, hit: Keywords::RAY_HIT_BULLET, bot: None },
+            },
+            None => Reply::Empty,
+        }
+    }
+
+    /// Builds the reply to a lidar query, cast from the bot of the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the number of rays, at most `AppDefines::LIDAR_MAX_RAYS`,
+    ///   and optionally the max range.
+    ///
+    /// # Returns
+    ///
+    /// The distances of the rays joined by `=`, starting from the heading and turning counterclockwise,
+    /// the max range for the rays hitting nothing. `ERROR` if the arguments are invalid or the client has no bot.
+    ///
+    fn query_lidar(&self, arguments: &[&str]) -> Reply {
+        let ray_count = arguments.first()
+            .and_then(|count| count.parse::<usize>().ok())
+            .filter(|count| (1..=AppDefines::LIDAR_MAX_RAYS).contains(count));
+        let (Some(handle), Some(ray_count), Some(max_range)) = (self.entity_handle, ray_count, Self::parse_range(arguments.get(1))) else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        Reply::Lidar { distances: game_logic.lidar(handle, ray_count, max_range) }
+    }
+
+    /// Builds the reply to a zone query.
+    ///
+    /// # Returns
+    ///
+    /// `CONTROL=x=y=radius=owner` in King of the Hill mode, `SAFE=x=y=radius` in Battle Royale mode,
+    /// and an empty reply in the other modes. The owner is `EMPTY` when nobody holds the zone alone.
+    ///
+    fn query_zone(&self) -> Reply {
+        match ZoneState::capture(&self.game_logic.lock().unwrap()) {
+            Some(zone) => Reply::Zone { zone },
+            None => Reply::Empty,
+        }
+    }
+
+    /// Builds the reply to an obstacles query.
+    ///
+    /// # Returns
+    ///
+    /// The number of obstacles followed by `x=y=radius=health` for each of them, the radius being the one
+    /// of a circle enclosing the obstacle. The health is `-1` for indestructible obstacles and `0` for
+    /// destroyed ones.
+    ///
+    fn query_obstacles(&self) -> Reply {
+        Reply::Obstacles { obstacles: ObstacleState::capture_all(&self.game_logic.lock().unwrap()) }
+    }
+
+    /// Builds the reply to a pickups query.
+    ///
+    /// With the fog of war enabled, only the pickups the client's bot can see are listed.
+    ///
+    /// # Returns
+    ///
+    /// The number of pickups followed by `kind=x=y=distance` for each of them, closest first,
+    /// or `ERROR` if the client has no bot.
+    ///
+    fn query_pickups(&self) -> Reply {
+        let Some(handle) = self.entity_handle else {
+            return Reply::ERROR;
+        };
+
+        let game_logic = self.game_logic.lock().unwrap();
+        let mut pickups = game_logic.visible_pickups(handle);
+        pickups.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        Reply::Pickups {
+            pickups: pickups.into_iter()
+                .map(|(pickup, distance)| PickupSighting {
+                    kind: pickup.kind.name(),
+                    x: pickup.position.0,
+                    y: pickup.position.1,
+                    distance,
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds the reply to a path query, planned from the bot of the client around the obstacles.
+    ///
+    /// # Arguments
+    ///
+    /// * `arguments` - The arguments of the command: the x and y position to reach.
+    ///
+    /// # Returns
+    ///
+    /// The number of waypoints followed by the x and y position of each waypoint, the last one being the
+    /// position to reach, all joined by `=`. `EMPTY` if the position cannot be reached, and `ERROR` if the
+    /// arguments are invalid, the position is outside the arena, the client has no bot or it already asked
+    /// for a path less than `AppDefines::PATH_QUERY_INTERVAL` seconds ago.
+    ///
+    fn query_path(&mut self, arguments: &[&str]) -> Reply {
+        let x = arguments.first().and_then(|x| x.parse::<f32>().ok()).filter(|x| x.is_finite());
+        let y = arguments.get(1).and_then(|y| y.parse::<f32>().ok()).filter(|y| y.is_finite());
+        let (Some(handle), Some(x), Some(y)) = (self.entity_handle, x, y) else {
+            return Reply::ERROR;
+        };
+        if self.last_path_query.is_some_and(|time| time.elapsed().as_secs_f32() < AppDefines::PATH_QUERY_INTERVAL) {
+            return Reply::ERROR;
+        }
+        self.last_path_query = Some(Instant::now());
+
+        let game_logic = self.game_logic.lock().unwrap();
+        if x < 0.0 || y < 0.0 || x > game_logic.arena_width || y > game_logic.arena_height {
+            return Reply::ERROR;
+        }
+        match game_logic.find_path(handle, (x, y)) {
+            Some(waypoints) => Reply::Path { waypoints },
+            None => Reply::Empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a session on a new game without any entity.
+    fn session() -> Session {
+        let settings = Arc::new(Mutex::new(ServerSettings::new()));
+        let game_logic = Arc::new(Mutex::new(GameLogic::new(Arc::clone(&settings))));
+        Session::new(Arc::new(Mutex::new(Vec::new())), settings, game_logic)
+    }
+
+    #[test]
+    fn text_commands_are_split_into_their_arguments() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("NAME=Rusty#MOVE=50#AIM=1.5\n"), vec!["OK", "OK", "OK"]);
+        let game_logic = session.game_logic.lock().unwrap();
+        assert_eq!(game_logic.entities[0].name, "Rusty");
+        assert_eq!(game_logic.entities[0].target_gun_orientation, 1.5);
+    }
+
+    #[test]
+    fn missing_or_invalid_text_arguments_are_errors() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("NAME#NAME=#NAME=Rusty"), vec!["ERROR", "ERROR", "OK"]);
+        assert_eq!(session.handle_received_message("MOVE#MOVE=fast#AIM=NaN#PATH=1"), vec!["ERROR"; 4]);
+        assert_eq!(session.handle_received_message("JUMP=1"), vec!["ERROR"]);
+    }
+
+    #[test]
+    fn exit_stops_the_line() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("NAME=Rusty#EXIT#MOVE=50"), vec!["OK"]);
+        assert!(session.quit);
+    }
+
+    #[test]
+    fn json_requests_go_through_the_same_commands() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("PROTOCOL=JSON"), vec!["OK"]);
+        assert_eq!(
+            session.handle_received_message(r#"{"id": "a", "command": "NAME", "args": ["Rusty"]}"#),
+            vec![r#"{"id":"a","type":"ok"}"#],
+        );
+        assert_eq!(
+            session.handle_received_message(r#"{"command": "NAME", "args": ["a=b"]}"#),
+            vec![r#"{"type":"error","reason":"INVALID_REQUEST"}"#],
+        );
+        assert_eq!(session.handle_received_message("NAME=Rusty"), vec![r#"{"type":"error","reason":"INVALID_REQUEST"}"#]);
+    }
+
+    #[test]
+    fn spectator_cannot_add_a_bot() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("SPECTATE#NAME=ghost"), vec!["OK", "ERROR"]);
+        assert_eq!(session.entity_handle, None);
+        assert!(session.game_logic.lock().unwrap().entities.is_empty());
+    }
+
+    #[test]
+    fn named_bot_cannot_spectate() {
+        let mut session = session();
+        assert_eq!(session.handle_received_message("NAME=Rusty#SPECTATE"), vec!["OK", "ERROR"]);
+        assert_eq!(session.game_logic.lock().unwrap().entities.len(), 1);
+        assert_eq!(session.spectate_interval, None);
+    }
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use urss_protocol::Keywords;
+
+use crate::game_logic::world_state::{ObstacleState, WorldState, ZoneState};
+
+/// Represents the protocols a client can speak with the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    /// Commands separated by `#` with their arguments separated by `=`, and one reply line per command.
+    Text,
+    /// One typed JSON object per line, for both the requests and the replies.
+    Json,
+}
+
+impl Protocol {
+    /// Finds a protocol from its name, as given to the `PROTOCOL` command.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the protocol.
+    ///
+    /// # Returns
+    ///
+    /// The protocol, or `None` if the name is unknown.
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            Keywords::PROTOCOL_TEXT => Some(Protocol::Text),
+            Keywords::PROTOCOL_JSON => Some(Protocol::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a request of the JSON protocol.
+///
+/// For example `{"id": 7, "command": "MOVE", "args": [50]}`, the `id` being optional and echoed in the reply.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Request {
+    /// Any value identifying the request, echoed in the reply.
+    #[serde(default)]
+    pub id: Option<Value>,
+    /// The command keyword, as in the text protocol.
+    pub command: String,
+    /// The arguments of the command, as strings or numbers.
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+impl Request {
+    /// Converts the arguments of the request to the arguments of the text protocol.
+    ///
+    /// Strings containing a separator or a control character are refused, so that every value accepted in JSON
+    /// can still be shown to the clients speaking the text protocol.
+    ///
+    /// # Returns
+    ///
+    /// The arguments as strings, or `None` if one of them is neither a number nor a valid string.
+    ///
+    pub fn arguments(&self) -> Option<Vec<String>> {
+        self.args.iter()
+            .map(|argument| match argument {
+                Value::Number(number) => Some(number.to_string()),
+                Value::String(text) if !text.contains(Keywords::COMMAND_SEP)
+                    && !text.contains(Keywords::ARGUMENT_SEP)
+                    && !text.chars().any(char::is_control) => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Represents a pickup seen by the bot of a client.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct PickupSighting {
+    pub kind: &'static str,
+    pub x: f32,
+    pub y: f32,
+    pub distance: f32,
+}
+
+/// Represents the reply to a command, before it is encoded for the protocol of the client.
+///
+/// In JSON, the reply is an object whose `type` field names the variant.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Reply {
+    /// The command succeeded.
+    Ok,
+    /// The command is unknown or invalid, with the reason when the server gives one.
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'static str>,
+    },
+    /// The query found nothing.
+    Empty,
+    /// The command is not implemented yet.
+    Todo { message: &'static str },
+    /// The current weapon of the bot.
+    Weapon { weapon: &'static str },
+    /// The health and weapon state of the bot.
+    Status {
+        health: f32,
+        weapon: &'static str,
+        ammo: u32,
+        magazine: u32,
+        /// The time left before the reload ends, in seconds.
+        reload: f32,
+        heat: f32,
+        overheated: bool,
+    },
+    /// The orientations of the body and of the gun turret of the bot, in radians.
+    Orientation { heading: f64, gun: f64 },
+    /// Another bot, with its distance to the bot of the client.
+    Bot { name: String, x: f32, y: f32, distance: f32 },
+    /// The position of a bot looked up by name, with its distance to the bot of the client.
+    Position { x: f32, y: f32, distance: f32 },
+    /// A bullet, with its distance to the bot of the client.
+    Projectile { x: f32, y: f32, vx: f32, vy: f32, distance: f32 },
+    /// The first hit of a ray, with the name of the bot when a bot was hit.
+    Ray {
+        distance: f32,
+        hit: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bot: Option<String>,
+    },
+    /// The distances of lidar rays, starting from the heading and turning counterclockwise.
+    Lidar { distances: Vec<f32> },
+    /// The zone of the current game mode.
+    Zone { zone: ZoneState },
+    /// The obstacles of the arena.
+    Obstacles { obstacles: Vec<ObstacleState> },
+    /// The pickups seen by the bot, closest first.
+    Pickups { pickups: Vec<PickupSighting> },
+    /// The waypoints of a path, the last one being the position to reach.
+    Path { waypoints: Vec<(f32, f32)> },
+    /// The world state streamed to spectators.
+    State(WorldState),
+}
+
+/// Represents a reply of the JSON protocol, with the identifier of its request.
+#[derive(Serialize)]
+struct JsonReply<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    #[serde(flatten)]
+    reply: &'a Reply,
+}
+
+impl Reply {
+    /// The reply to an unknown or invalid command, without reason.
+    pub const ERROR: Reply = Reply::Error { reason: None };
+
+    /// Creates an error reply with a reason.
+    ///
+    /// # Arguments
+    ///
+    /// * `reason` - The reason of the error.
+    ///
+    /// # Returns
+    ///
+    /// A new `Reply::Error`.
+    ///
+    pub fn error(reason: &'static str) -> Self {
+        Reply::Error { reason: Some(reason) }
+    }
+
+    /// Encodes the reply for a protocol.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol` - The protocol of the client.
+    /// * `id` - The identifier of the JSON request, echoed in the JSON reply.
+    ///
+    /// # Returns
+    ///
+    /// The encoded reply, without line ending.
+    ///
+    pub fn encode(&self, protocol: Protocol, id: Option<Value>) -> String {
+        match protocol {
+            Protocol::Text => self.to_text(),
+            Protocol::Json => serde_json::to_string(&JsonReply { id, reply: self }).unwrap_or_default(),
+        }
+    }
+
+    /// Encodes the reply as a line of the text protocol.
+    ///
+    /// # Returns
+    ///
+    /// The fields of the reply joined by `=`, as documented for each command in `AppDefines`.
+    ///
+    pub fn to_text(&self) -> String {
+        match self {
+            Reply::Ok => Keywords::OK_REPLY.to_string(),
+            Reply::Error { reason: None } => Keywords::ERROR_REPLY.to_string(),
+            Reply::Error { reason: Some(reason) } => [Keywords::ERROR_REPLY, reason].join(Keywords::ARGUMENT_SEP),
+            Reply::Empty => Keywords::EMPTY_REPLY.to_string(),
+            Reply::Todo { message } => message.to_string(),
+            Reply::Weapon { weapon } => weapon.to_string(),
+            Reply::Status { health, weapon, ammo, magazine, reload, heat, overheated } => [
+                health.to_string(),
+                weapon.to_string(),
+                ammo.to_string(),
+                magazine.to_string(),
+                reload.to_string(),
+                heat.to_string(),
+                (*overheated as u8).to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+            Reply::Orientation { heading, gun } => [
+                heading.to_string(),
+                gun.to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+            Reply::Bot { name, x, y, distance } => [
+                name.clone(),
+                x.to_string(),
+                y.to_string(),
+                distance.to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+            Reply::Position { x, y, distance } => [
+                x.to_string(),
+                y.to_string(),
+                distance.to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+            Reply::Projectile { x, y, vx, vy, distance } => [
+                x.to_string(),
+                y.to_string(),
+                vx.to_string(),
+                vy.to_string(),
+                distance.to_string(),
+            ].join(Keywords::ARGUMENT_SEP),
+            Reply::Ray { distance, hit, bot } => std::iter::once(distance.to_string())
+                .chain(std::iter::once(hit.to_string()))
+                .chain(bot.clone())
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP),
+            Reply::Lidar { distances } => distances.iter()
+                .map(|distance| distance.to_string())
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP),
+            Reply::Zone { zone } => zone.to_text(),
+            Reply::Obstacles { obstacles } => std::iter::once(obstacles.len().to_string())
+                .chain(obstacles.iter().flat_map(ObstacleState::to_fields))
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP),
+            Reply::Pickups { pickups } => std::iter::once(pickups.len().to_string())
+                .chain(pickups.iter().flat_map(|pickup| [
+                    pickup.kind.to_string(),
+                    pickup.x.to_string(),
+                    pickup.y.to_string(),
+                    pickup.distance.to_string(),
+                ]))
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP),
+            Reply::Path { waypoints } => std::iter::once(waypoints.len().to_string())
+                .chain(waypoints.iter().flat_map(|(x, y)| [x.to_string(), y.to_string()]))
+                .collect::<Vec<_>>()
+                .join(Keywords::ARGUMENT_SEP),
+            Reply::State(state) => state.to_line(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(line: &str) -> Request {
+        serde_json::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn json_arguments_become_text_arguments() {
+        let arguments = request(r#"{"command": "NBOT", "args": ["Rusty", 12, 0.5]}"#).arguments();
+        assert_eq!(arguments, Some(vec!["Rusty".to_string(), "12".to_string(), "0.5".to_string()]));
+        assert_eq!(request(r#"{"command": "FIRE"}"#).arguments(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn json_arguments_breaking_the_text_protocol_are_refused() {
+        for args in [r#"["a=b"]"#, r#"["a#b"]"#, r#"["a\nb"]"#, "[true]", "[null]", "[[1]]"] {
+            let line = format!(r#"{{"command": "MSG", "args": {}}}"#, args);
+            assert_eq!(request(&line).arguments(), None, "{}", args);
+        }
+    }
+
+    #[test]
+    fn replies_are_joined_with_the_argument_separator() {
+        assert_eq!(Reply::Ok.to_text(), "OK");
+        assert_eq!(Reply::ERROR.to_text(), "ERROR");
+        assert_eq!(Reply::error("NO_AMMO").to_text(), "ERROR=NO_AMMO");
+        assert_eq!(Reply::Empty.to_text(), "EMPTY");
+        assert_eq!(Reply::Bot { name: "Rusty".to_string(), x: 1.5, y: 2.0, distance: 3.25 }.to_text(), "Rusty=1.5=2=3.25");
+        assert_eq!(Reply::Orientation { heading: 0.5, gun: -1.0 }.to_text(), "0.5=-1");
+        assert_eq!(
+            Reply::Status { health: 80.0, weapon: "PISTOL", ammo: 5, magazine: 12, reload: 0.0, heat: 0.5, overheated: true }.to_text(),
+            "80=PISTOL=5=12=0=0.5=1",
+        );
+    }
+
+    #[test]
+    fn list_replies_start_with_their_length() {
+        assert_eq!(Reply::Path { waypoints: vec![(10.0, 20.0), (30.0, 40.0)] }.to_text(), "2=10=20=30=40");
+        assert_eq!(Reply::Lidar { distances: vec![1.0, 2.5] }.to_text(), "1=2.5");
+        assert_eq!(Reply::Ray { distance: 4.0, hit: "BOT", bot: Some("Rusty".to_string()) }.to_text(), "4=BOT=Rusty");
+        assert_eq!(Reply::Ray { distance: 4.0, hit: "WALL", bot: None }.to_text(), "4=WALL");
+    }
+
+    #[test]
+    fn json_replies_are_tagged_and_echo_the_request_id() {
+        let reply = Reply::error("NO_AMMO").encode(Protocol::Json, Some(Value::from(7)));
+        assert_eq!(reply, r#"{"id":7,"type":"error","reason":"NO_AMMO"}"#);
+        assert_eq!(Reply::Ok.encode(Protocol::Json, None), r#"{"type":"ok"}"#);
+    }
+}
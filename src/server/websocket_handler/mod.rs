@@ -0,0 +1,205 @@
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tungstenite::{Error, Message, WebSocket};
+
+use crate::game_logic::GameLogic;
+use crate::server::server_thread::ServerSettings;
+use crate::server::session::Session;
+use crate::types::{add_message, MessageType, StyledMessage};
+
+/// A struct representing a WebSocket handler, responsible for communicating with a client via a WebSocket.
+///
+/// Each text frame received is a command line of the text protocol, and each reply is sent back as one text frame,
+/// so browser-based bots and dashboards share the same session logic as the TCP clients.
+pub(crate) struct WebSocketHandler {
+    /// The WebSocket associated with the client.
+    pub(crate) socket: WebSocket<TcpStream>,
+    /// The address of the client.
+    pub(crate) peer_addr: SocketAddr,
+    /// The session of the client, processing its commands.
+    pub(crate) session: Session,
+}
+
+impl WebSocketHandler {
+    /// Performs the WebSocket handshake on a new connection and creates its handler.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The client's TCP stream, before the handshake.
+    /// * `messages` - A thread-safe, shared vector of styled messages.
+    /// * `settings` - Thread-safe, shared server settings.
+    /// * `game_logic` - Thread-safe, shared game logic.
+    ///
+    /// # Returns
+    ///
+    /// A new `WebSocketHandler`, or an error message if the handshake failed or did not complete before the
+    /// connection timeout.
+    ///
+    pub fn new(stream: TcpStream, messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>, game_logic: Arc<Mutex<GameLogic>>) -> Result<Self, String> {
+        let peer_addr = stream.peer_addr().map_err(|e| e.to_string())?;
+        let timeout_delay = settings.lock().unwrap().connection_timeout_delay.max(1) as u64;
+        stream.set_read_timeout(Some(Duration::from_secs(timeout_delay))).map_err(|e| e.to_string())?;
+        let socket = tungstenite::accept(stream).map_err(|e| format!("WebSocket handshake failed with {}: {}", peer_addr, e))?;
+        socket.get_ref().set_read_timeout(Some(Duration::from_millis(100))).map_err(|e| e.to_string())?;
+
+        Ok(WebSocketHandler {
+            socket,
+            peer_addr,
+            session: Session::new(messages, settings, game_logic),
+        })
+    }
+
+    /// Starts the WebSocket handler, reading frames from the client and processing them until disconnection or timeout.
+    ///
+    /// A client asking to spectate is then only streamed the world state until it disconnects.
+    /// The bot of the client is removed from the game once the connection ends.
+    pub fn run(&mut self) {
+        loop {
+            if self.check_timeout() {
+                break;
+            }
+
+            match self.socket.read() {
+                Ok(Message::Text(received_message)) => {
+                    let replies = self.session.handle_received_message(&received_message);
+                    if !replies.into_iter().all(|reply| self.send_reply(reply)) {
+                        break;
+                    }
+                    if self.session.quit {
+                        self.handle_disconnection();
+                        break;
+                    }
+                    if let Some(interval) = self.session.spectate_interval {
+                        self.spectate(interval);
+                        break;
+                    }
+                }
+                Ok(Message::Close(_)) => {
+                    self.handle_disconnection();
+                    break;
+                }
+                // Pings are answered by the WebSocket itself, and binary frames are not part of the protocol
+                Ok(_) => {}
+                Err(Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => {
+                    self.handle_disconnection();
+                    break;
+                }
+            }
+        }
+
+        self.session.close();
+    }
+
+    /// Checks if the client has exceeded the inactivity timeout, closing the connection if so.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the client has exceeded the inactivity timeout, `false` otherwise.
+    ///
+    fn check_timeout(&mut self) -> bool {
+        if self.session.check_timeout() {
+            add_message(
+                &self.session.messages,
+                format!("[WARNING] Connection timeout: {}", self.peer_addr),
+                MessageType::Warning,
+            );
+            let _ = self.socket.close(None);
+            let _ = self.socket.flush();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sends a reply to the client, as one text frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `reply` - The reply to send.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the reply was sent, `false` if the connection is broken.
+    ///
+    fn send_reply(&mut self, reply: String) -> bool {
+        match self.socket.send(Message::text(reply)) {
+            Ok(()) => true,
+            Err(e) => {
+                add_message(
+                    &self.session.messages,
+                    format!("[ERROR] Failed to send response to {}: {}", self.peer_addr, e),
+                    MessageType::Error,
+                );
+                false
+            }
+        }
+    }
+
+    /// Streams the world state to the client, one text frame per interval, until it disconnects.
+    ///
+    /// Spectators are not subject to the inactivity timeout, and any command other than `EXIT` is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The delay between two world states.
+    ///
+    fn spectate(&mut self, interval: Duration) {
+        add_message(
+            &self.session.messages,
+            format!("[INFO] Spectator connected: {:?}", self.peer_addr),
+            MessageType::Info,
+        );
+
+        let mut next_frame = Instant::now();
+        loop {
+            let now = Instant::now();
+            if now >= next_frame {
+                let state = self.session.world_state();
+                if self.socket.send(Message::text(state)).is_err() {
+                    break;
+                }
+                // Skip the frames missed while the game logic was busy rather than bursting them
+                next_frame += interval;
+                if next_frame <= now {
+                    next_frame = now + interval;
+                }
+                continue;
+            }
+
+            // Wait for the next frame while still listening for the client leaving
+            if self.socket.get_ref().set_read_timeout(Some(next_frame - now)).is_err() {
+                break;
+            }
+            match self.socket.read() {
+                Ok(Message::Text(received_message)) => {
+                    if self.session.is_quit_request(&received_message) {
+                        self.handle_disconnection();
+                        break;
+                    }
+                }
+                Ok(Message::Close(_)) => {
+                    self.handle_disconnection();
+                    break;
+                }
+                Ok(_) => {}
+                Err(Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Handles the disconnection of the client, completing the closing handshake if the client started it.
+    fn handle_disconnection(&mut self) {
+        add_message(
+            &self.session.messages,
+            format!("[INFO] Client disconnected: {:?}", self.peer_addr),
+            MessageType::Info,
+        );
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
@@ -1,53 +1,248 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
 use eframe::egui;
 use egui::{Align2, Context, TopBottomPanel};
 use egui_extras::*;
 use egui_plot::*;
+use rapier2d::prelude::{nalgebra, point, Point};
 
-use crate::game_logic::GameLogic;
+use crate::ai::aiming::Difficulty;
+use crate::ai::script::ScriptBrain;
+use crate::ai::{BotBrain, BrainKind};
+use crate::app_defines::AppDefines;
+use crate::game_logic::{GameLogic, GameMode};
+use crate::maps::MapFile;
+use crate::obstacles::generator::MapGenerator;
+use crate::obstacles::ObstacleMaterial;
+use crate::pickups::PickupKind;
+use crate::types::StyledMessage;
 
 /// Represents the user interface for the game.
 pub struct GameUI {
-    game_logic: GameLogic,
+    game_logic: Arc<Mutex<GameLogic>>,
+    /// A thread-safe, shared vector of styled messages, receiving the messages of the bot scripts.
+    messages: Arc<Mutex<Vec<StyledMessage>>>,
     line_thickness: f32,
     show_names: bool,
     show_background: bool,
+    show_fov: bool,
+    seed_input: u64,
+    map_path: String,
+    map_status: String,
+    script_path: String,
+    script_status: String,
 }
 
 impl GameUI {
+    /// Creates a new `GameUI` instance displaying the given game logic.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The thread-safe, shared game logic.
+    /// - `messages`: The thread-safe, shared vector of styled messages shown by the server UI.
+    ///
+    /// # Returns
+    /// A new instance of `GameUI` with default settings.
+    pub fn new(game_logic: Arc<Mutex<GameLogic>>, messages: Arc<Mutex<Vec<StyledMessage>>>) -> Self {
+        let seed_input = game_logic.lock().unwrap().map_seed;
+        Self {
+            game_logic,
+            messages,
+            line_thickness: 4.0,
+            show_names: true,
+            show_background: true,
+            show_fov: false,
+            seed_input,
+            map_path: AppDefines::MAP_FILE_PATH.to_string(),
+            map_status: String::new(),
+            script_path: AppDefines::SCRIPT_FILE_PATH.to_string(),
+            script_status: String::new(),
+        }
+    }
+
     /// Draws the obstacles on the plot.
     ///
     /// # Parameters
+    /// - `game_logic`: The game logic holding the obstacles.
     /// - `plot_ui`: The `PlotUi` instance where obstacles will be drawn.
-    fn draw_obstacles(&self, plot_ui: &mut PlotUi) {
-        for obstacle in &self.game_logic.obstacles {
-            let position = obstacle.position;
-            let line_thickness = self.line_thickness / 2.0;
-
-            let diamond_points = vec![
-                [position.0, position.1 - 10.0],
-                [position.0 - 10.0, position.1],
-                [position.0, position.1 + 10.0],
-                [position.0 + 10.0, position.1],
-                [position.0, position.1 - 10.0], // close the diamond shape
-            ];
+    fn draw_obstacles(&self, game_logic: &GameLogic, plot_ui: &mut PlotUi) {
+        for obstacle in &game_logic.obstacles {
+            let Some(collider) = game_logic.physics_engine.colliders.get(obstacle.collider_handle) else {
+                continue;
+            };
+
+            // Outline of the collider in its local frame, closed for every shape but the line walls
+            let shape = collider.shape();
+            let mut outline: Vec<Point<f32>> = if let Some(cuboid) = shape.as_cuboid() {
+                let half_extents = cuboid.half_extents;
+                vec![
+                    point![-half_extents.x, -half_extents.y],
+                    point![half_extents.x, -half_extents.y],
+                    point![half_extents.x, half_extents.y],
+                    point![-half_extents.x, half_extents.y],
+                ]
+            } else if let Some(ball) = shape.as_ball() {
+                let segments = 32;
+                (0..segments)
+                    .map(|i| {
+                        let angle = i as f32 * std::f32::consts::TAU / segments as f32;
+                        point![ball.radius * angle.cos(), ball.radius * angle.sin()]
+                    })
+                    .collect()
+            } else if let Some(polygon) = shape.as_convex_polygon() {
+                polygon.points().to_vec()
+            } else if let Some(segment) = shape.as_segment() {
+                vec![segment.a, segment.b]
+            } else {
+                continue;
+            };
+            if outline.len() > 2 {
+                outline.push(outline[0]);
+            }
 
+            let points: Vec<[f64; 2]> = outline.iter()
+                .map(|local_point| collider.position() * local_point)
+                .map(|world_point| [world_point.x as f64, world_point.y as f64])
+                .collect();
+
+            let (color, line_thickness) = match obstacle.material {
+                ObstacleMaterial::Solid => (egui::Color32::GREEN, self.line_thickness / 2.0),
+                ObstacleMaterial::Bouncy => (egui::Color32::LIGHT_BLUE, self.line_thickness / 2.0),
+                ObstacleMaterial::Absorbing => (egui::Color32::DARK_RED, self.line_thickness),
+            };
+
+            // Destroyed obstacles waiting to come back are dashed, damaged ones fade towards orange
+            let line = match obstacle.max_health {
+                Some(_) if obstacle.is_destroyed() => {
+                    if obstacle.regeneration_delay.is_none() {
+                        continue;
+                    }
+                    Line::new(PlotPoints::new(points))
+                        .color(egui::Color32::DARK_GRAY)
+                        .style(LineStyle::dashed_loose())
+                }
+                Some(max_health) => {
+                    let damage = 1.0 - obstacle.health / max_health;
+                    let fade = |from: u8, to: u8| egui::lerp(from as f32..=to as f32, damage) as u8;
+                    Line::new(PlotPoints::new(points))
+                        .color(egui::Color32::from_rgb(fade(color.r(), 255), fade(color.g(), 128), fade(color.b(), 0)))
+                }
+                None => Line::new(PlotPoints::new(points)).color(color),
+            };
+
+            plot_ui.line(line.width(line_thickness));
+        }
+    }
+
+    /// Draws the zones of the current game mode on the plot.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic holding the zones.
+    /// - `plot_ui`: The `PlotUi` instance where zones will be drawn.
+    fn draw_zones(&self, game_logic: &GameLogic, plot_ui: &mut PlotUi) {
+        if let Some(control_zone) = &game_logic.control_zone {
+            let color = if control_zone.owner.is_some() {
+                egui::Color32::GOLD
+            } else {
+                egui::Color32::GRAY
+            };
             plot_ui.line(
-                Line::new(PlotPoints::new(diamond_points))
-                    .color(egui::Color32::GREEN)
-                    .width(line_thickness),
+                Line::new(Self::circle_points(control_zone.center, control_zone.radius))
+                    .color(color)
+                    .name("Control Zone")
+                    .width(self.line_thickness / 2.0),
+            );
+        }
+
+        if let Some(safe_zone) = &game_logic.safe_zone {
+            plot_ui.line(
+                Line::new(Self::circle_points(safe_zone.center, safe_zone.radius))
+                    .color(egui::Color32::RED)
+                    .name("Safe Zone")
+                    .width(self.line_thickness / 2.0),
             );
         }
     }
 
+    /// Draws the area each living entity can see when the fog of war is enabled.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic holding the entities.
+    /// - `plot_ui`: The `PlotUi` instance where the fields of view will be drawn.
+    fn draw_fields_of_view(&self, game_logic: &GameLogic, plot_ui: &mut PlotUi) {
+        for entity in game_logic.entities.iter().filter(|entity| entity.is_alive()) {
+            let Some(outline) = game_logic.view_outline(entity) else {
+                continue;
+            };
+            let points: Vec<[f64; 2]> = outline.iter().map(|&(x, y)| [x as f64, y as f64]).collect();
+            plot_ui.line(
+                Line::new(PlotPoints::new(points))
+                    .color(egui::Color32::YELLOW)
+                    .width(1.0),
+            );
+        }
+    }
+
+    /// Draws the pickups lying in the arena, with a color and a letter for each kind.
+    ///
+    /// # Parameters
+    /// - `game_logic`: The game logic holding the pickups.
+    /// - `plot_ui`: The `PlotUi` instance where the pickups will be drawn.
+    fn draw_pickups(&self, game_logic: &GameLogic, plot_ui: &mut PlotUi) {
+        for pickup in &game_logic.pickups {
+            let (color, letter) = match pickup.kind {
+                PickupKind::Health => (egui::Color32::from_rgb(0, 200, 0), "H"),
+                PickupKind::SpeedBoost => (egui::Color32::from_rgb(0, 200, 255), "S"),
+                PickupKind::Shield => (egui::Color32::from_rgb(200, 200, 255), "P"),
+                PickupKind::RapidFire => (egui::Color32::from_rgb(255, 200, 0), "R"),
+                PickupKind::ExtraDamage => (egui::Color32::from_rgb(255, 60, 60), "D"),
+            };
+            plot_ui.polygon(
+                Polygon::new(Self::circle_points(pickup.position, AppDefines::PICKUP_RADIUS))
+                    .fill_color(color.gamma_multiply(0.5))
+                    .stroke(egui::Stroke::new(2.0, color)),
+            );
+            plot_ui.text(
+                Text::new(PlotPoint::new(pickup.position.0, pickup.position.1), letter)
+                    .color(egui::Color32::WHITE)
+                    .anchor(Align2::CENTER_CENTER),
+            );
+        }
+    }
+
+    /// Builds the points of a closed circle.
+    ///
+    /// # Parameters
+    /// - `center`: A tuple representing the (x, y) center of the circle.
+    /// - `radius`: The radius of the circle.
+    ///
+    /// # Returns
+    /// The `PlotPoints` approximating the circle.
+    fn circle_points(center: (f32, f32), radius: f32) -> PlotPoints {
+        let segments = 64;
+        PlotPoints::new(
+            (0..=segments)
+                .map(|i| {
+                    let angle = i as f64 * std::f64::consts::TAU / segments as f64;
+                    [
+                        center.0 as f64 + radius as f64 * angle.cos(),
+                        center.1 as f64 + radius as f64 * angle.sin(),
+                    ]
+                })
+                .collect(),
+        )
+    }
+
     /// Displays the entities on the plot.
     ///
     /// # Parameters
+    /// - `game_logic`: The game logic holding the entities.
     /// - `plot_ui`: The `PlotUi` instance where entities will be displayed.
-    fn display_entities(&self, plot_ui: &mut PlotUi) {
-        for entity in &self.game_logic.entities {
-            let body = &self.game_logic.physics_engine.bodies[entity.handle];
+    fn display_entities(&self, game_logic: &GameLogic, plot_ui: &mut PlotUi) {
+        for entity in &game_logic.entities {
+            let body = &game_logic.physics_engine.bodies[entity.handle];
             let pos = [body.translation().x as f64, body.translation().y as f64];
-            let angle = body.rotation().angle();
+            let angle = entity.self_orientation as f32;
 
             let points = vec![
                 [
@@ -64,12 +259,43 @@ impl GameUI {
                 ],
             ];
 
+            // Eliminated entities are greyed out
+            let color = if entity.is_alive() {
+                egui::Color32::LIGHT_BLUE
+            } else {
+                egui::Color32::DARK_GRAY
+            };
+
             plot_ui.line(
                 Line::new(PlotPoints::new(points))
-                    .color(egui::Color32::LIGHT_BLUE)
+                    .color(color)
                     .width(self.line_thickness),
             );
 
+            // Shielded entities are surrounded by a ring
+            if entity.has_effect(PickupKind::Shield) {
+                plot_ui.line(
+                    Line::new(Self::circle_points((pos[0] as f32, pos[1] as f32), AppDefines::ENTITY_HALF_SIZE * 1.8))
+                        .color(egui::Color32::from_rgb(200, 200, 255))
+                        .width(1.5),
+                );
+            }
+
+            // The gun turret turns independently from the body
+            let turret_length = self.line_thickness as f64 * 3.0;
+            let turret_points = vec![
+                pos,
+                [
+                    pos[0] + turret_length * entity.gun_orientation.cos(),
+                    pos[1] + turret_length * entity.gun_orientation.sin(),
+                ],
+            ];
+            plot_ui.line(
+                Line::new(PlotPoints::new(turret_points))
+                    .color(egui::Color32::from_rgb(255, 128, 0))
+                    .width(self.line_thickness / 2.0),
+            );
+
             if self.show_names {
                 let pos_with_offset = [pos[0], pos[1] + 20.0]; // Add 20 to the y-coordinate
                 plot_ui.text(
@@ -86,59 +312,99 @@ impl GameUI {
     /// # Parameters
     /// - `ctx`: The `Context` instance for rendering the menu.
     fn show_menu(&mut self, ctx: &Context) {
+        let game_logic = Arc::clone(&self.game_logic);
+        let mut game_logic = game_logic.lock().unwrap();
         TopBottomPanel::top("menu_bar").show(ctx, |ui| {
-            ui.horizontal(|ui| {
+            ui.horizontal_wrapped(|ui| {
                 if ui.button("Toggle Names").clicked() {
                     self.show_names = !self.show_names;
                 }
-                if ui.button("T+").clicked() {
-                    if self.line_thickness < 20.0 {
-                        self.line_thickness += 1.0;
-                    }
+                if ui.button("Toggle FOV").clicked() {
+                    self.show_fov = !self.show_fov;
                 }
-                if ui.button("T-").clicked() {
-                    if self.line_thickness > 1.0 {
-                        self.line_thickness -= 1.0;
-                    }
+                if ui.button("T+").clicked() && self.line_thickness < 20.0 {
+                    self.line_thickness += 1.0;
+                }
+                if ui.button("T-").clicked() && self.line_thickness > 1.0 {
+                    self.line_thickness -= 1.0;
                 }
                 if ui.button("Reset Simulation").clicked() {
-                    self.game_logic.reset_simulation();
+                    game_logic.reset_simulation();
                 }
+                egui::ComboBox::from_label("Generator")
+                    .selected_text(game_logic.map_generator.name())
+                    .show_ui(ui, |ui| {
+                        for generator in MapGenerator::ALL {
+                            ui.selectable_value(&mut game_logic.map_generator, generator, generator.name());
+                        }
+                    });
                 if ui.button("Generate Map").clicked() {
-                    self.game_logic.generate_map();
+                    game_logic.generate_map();
+                    self.seed_input = game_logic.map_seed;
+                }
+                ui.add(egui::DragValue::new(&mut self.seed_input).prefix("Seed: "));
+                if ui.button("Load Seed").clicked() {
+                    game_logic.generate_map_with_seed(self.seed_input);
+                }
+                ui.add(egui::TextEdit::singleline(&mut self.map_path).desired_width(120.0));
+                if ui.button("Load Map").clicked() {
+                    self.map_status = match MapFile::load(Path::new(&self.map_path)) {
+                        Ok(map) => {
+                            game_logic.load_map(&map);
+                            format!("Loaded {}", self.map_path)
+                        }
+                        Err(e) => e,
+                    };
+                }
+                if ui.button("Export Map").clicked() {
+                    self.map_status = match game_logic.to_map_file().save(Path::new(&self.map_path)) {
+                        Ok(()) => format!("Exported {}", self.map_path),
+                        Err(e) => e,
+                    };
+                }
+                if !self.map_status.is_empty() {
+                    ui.label(&self.map_status);
                 }
                 if ui.button("Show Background").clicked() {
                     self.show_background = !self.show_background;
                 }
                 if ui.button("Add Entity").clicked() {
-                    self.game_logic.add_entity("Player".to_string());
+                    game_logic.add_entity("Player".to_string());
                 }
                 if ui.button("Add AI").clicked() { // New AI button
-                    self.game_logic.add_ai("AI Bot".to_string());
+                    game_logic.add_ai("AI Bot".to_string());
+                }
+                ui.add(egui::TextEdit::singleline(&mut self.script_path).desired_width(120.0));
+                if ui.button("Add Script").clicked() {
+                    self.script_status = match ScriptBrain::load(Path::new(&self.script_path), Arc::clone(&self.messages)) {
+                        Ok(brain) => {
+                            let name = brain.name().to_string();
+                            game_logic.add_ai_with_brain(name, Box::new(brain));
+                            format!("Loaded {}", self.script_path)
+                        }
+                        Err(e) => e,
+                    };
+                }
+                if !self.script_status.is_empty() {
+                    ui.label(&self.script_status);
+                }
+
+                let mut game_mode = game_logic.game_mode;
+                egui::ComboBox::from_label("Game Mode")
+                    .selected_text(game_mode.name())
+                    .show_ui(ui, |ui| {
+                        for mode in GameMode::ALL {
+                            ui.selectable_value(&mut game_mode, mode, mode.name());
+                        }
+                    });
+                if game_mode != game_logic.game_mode {
+                    game_logic.set_game_mode(game_mode);
                 }
             });
         });
     }
 }
 
-impl Default for GameUI {
-    /// Creates a new default `GameUI` instance.
-    ///
-    /// # Returns
-    /// A new instance of `GameUI` with default settings.
-    fn default() -> Self {
-        let mut game_logic = GameLogic::new();
-        game_logic.generate_map();
-
-        Self {
-            game_logic,
-            line_thickness: 4.0,
-            show_names: true,
-            show_background: true,
-        }
-    }
-}
-
 impl eframe::App for GameUI {
     /// Updates the game UI.
     ///
@@ -148,17 +414,25 @@ impl eframe::App for GameUI {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.show_menu(ctx);
 
+        let game_logic = Arc::clone(&self.game_logic);
+        let mut game_logic = game_logic.lock().unwrap();
+
         // Update AI movement
-        self.game_logic.update_ai();
+        game_logic.update_ai();
 
         // Update the physics
-        self.game_logic.step();
+        game_logic.step();
 
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::SidePanel::left("entity_list").show_inside(ui, |ui| {
                 TableBuilder::new(ui)
                     .column(Column::exact(200.0).resizable(false))
                     .column(Column::exact(100.0).resizable(false))
+                    .column(Column::exact(100.0).resizable(false))
+                    .column(Column::exact(120.0).resizable(false))
+                    .column(Column::exact(120.0).resizable(false))
+                    .column(Column::exact(120.0).resizable(false))
+                    .column(Column::exact(120.0).resizable(false))
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             ui.heading("Player Name");
@@ -166,11 +440,26 @@ impl eframe::App for GameUI {
                         header.col(|ui| {
                             ui.heading("Score");
                         });
+                        header.col(|ui| {
+                            ui.heading("Health");
+                        });
+                        header.col(|ui| {
+                            ui.heading("Weapon");
+                        });
+                        header.col(|ui| {
+                            ui.heading("Ammo");
+                        });
+                        header.col(|ui| {
+                            ui.heading("Brain");
+                        });
+                        header.col(|ui| {
+                            ui.heading("Difficulty");
+                        });
                     })
                     .body(|mut body| {
                         let padding = 10.0;
 
-                        for (index, entity) in self.game_logic.entities.iter().enumerate() {
+                        for (index, entity) in game_logic.entities.iter_mut().enumerate() {
                             body.row(30.0, |mut row| {
                                 let bg_color = if index % 2 == 0 {
                                     egui::Color32::from_gray(20)
@@ -188,7 +477,80 @@ impl eframe::App for GameUI {
                                     ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
                                     ui.horizontal_centered(|ui| {
                                         ui.add_space(padding);
-                                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), &entity.score.to_string());
+                                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), entity.score.to_string());
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
+                                    ui.horizontal_centered(|ui| {
+                                        ui.add_space(padding);
+                                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), format!("{:.0}", entity.health));
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
+                                    ui.horizontal_centered(|ui| {
+                                        ui.add_space(padding);
+                                        ui.colored_label(egui::Color32::from_rgb(255, 255, 255), entity.weapon.name());
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
+                                    ui.horizontal_centered(|ui| {
+                                        ui.add_space(padding);
+                                        if entity.reload_started.is_some() {
+                                            ui.colored_label(egui::Color32::YELLOW, format!("Reloading {:.1}s", entity.reload_remaining().as_secs_f32()));
+                                        } else if entity.overheated {
+                                            ui.colored_label(egui::Color32::RED, "Overheated");
+                                        } else {
+                                            let magazine = entity.weapon.weapon().magazine;
+                                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), format!("{}/{}", entity.ammo, magazine));
+                                            if entity.heat > 0.0 {
+                                                ui.colored_label(egui::Color32::from_rgb(255, 165, 0), format!("{:.0}%", entity.heat * 100.0));
+                                            }
+                                        }
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
+                                    ui.horizontal_centered(|ui| {
+                                        ui.add_space(padding);
+                                        // Network bots have no brain to choose
+                                        let Some(brain) = &mut entity.brain else {
+                                            ui.colored_label(egui::Color32::from_rgb(255, 255, 255), "Network");
+                                            return;
+                                        };
+                                        egui::ComboBox::from_id_source(("brain", index))
+                                            .selected_text(brain.name().to_string())
+                                            .show_ui(ui, |ui| {
+                                                for kind in BrainKind::ALL {
+                                                    if ui.selectable_label(brain.name() == kind.name(), kind.name()).clicked() {
+                                                        *brain = kind.create(brain.difficulty().unwrap_or_default());
+                                                    }
+                                                }
+                                            });
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.painter().rect_filled(ui.max_rect(), 0.0, bg_color);
+                                    ui.horizontal_centered(|ui| {
+                                        ui.add_space(padding);
+                                        // Only the brains with a configurable aiming have a difficulty
+                                        let Some(brain) = &mut entity.brain else {
+                                            return;
+                                        };
+                                        let Some(difficulty) = brain.difficulty() else {
+                                            return;
+                                        };
+                                        egui::ComboBox::from_id_source(("difficulty", index))
+                                            .selected_text(difficulty.name())
+                                            .show_ui(ui, |ui| {
+                                                for level in Difficulty::ALL {
+                                                    if ui.selectable_label(difficulty == level, level.name()).clicked() {
+                                                        brain.set_difficulty(level);
+                                                    }
+                                                }
+                                            });
                                     });
                                 });
                             });
@@ -205,12 +567,15 @@ impl eframe::App for GameUI {
                     .show_y(false)
                     .data_aspect(1.0)
                     .show(ui, |plot_ui| {
+                        let arena_width = game_logic.arena_width as f64;
+                        let arena_height = game_logic.arena_height as f64;
+
                         if self.show_background {
-                            let x_lines: Vec<f64> = (0..=1200).step_by(50).map(|x| x as f64).collect();
-                            let y_lines: Vec<f64> = (0..=1000).step_by(50).map(|y| y as f64).collect();
+                            let x_lines: Vec<f64> = (0..=arena_width as i32).step_by(50).map(|x| x as f64).collect();
+                            let y_lines: Vec<f64> = (0..=arena_height as i32).step_by(50).map(|y| y as f64).collect();
 
                             for &x in &x_lines {
-                                let vertical_line = Line::new(PlotPoints::new(vec![[x, 0.0], [x, 1000.0]]))
+                                let vertical_line = Line::new(PlotPoints::new(vec![[x, 0.0], [x, arena_height]]))
                                     .color(egui::Color32::from_rgb(0, 40, 0))
                                     .width(self.line_thickness / 3.0)
                                     .style(LineStyle::Solid);
@@ -218,7 +583,7 @@ impl eframe::App for GameUI {
                             }
 
                             for &y in &y_lines {
-                                let horizontal_line = Line::new(PlotPoints::new(vec![[0.0, y], [1200.0, y]]))
+                                let horizontal_line = Line::new(PlotPoints::new(vec![[0.0, y], [arena_width, y]]))
                                     .color(egui::Color32::from_rgb(0, 40, 0))
                                     .width(self.line_thickness / 3.0)
                                     .style(LineStyle::Solid);
@@ -227,10 +592,10 @@ impl eframe::App for GameUI {
                         }
 
                         let plot_points = Points::new(
-                            self.game_logic.bullets
+                            game_logic.bullets
                                 .iter()
                                 .map(|bullet| {
-                                    let pos = self.game_logic.physics_engine.bodies[bullet.handle].translation();
+                                    let pos = game_logic.physics_engine.bodies[bullet.handle].translation();
                                     [pos.x as f64, pos.y as f64]
                                 })
                                 .collect::<Vec<_>>(),
@@ -239,15 +604,23 @@ impl eframe::App for GameUI {
                             .name("Bullets");
                         plot_ui.points(plot_points);
 
-                        self.display_entities(plot_ui);
+                        self.draw_zones(&game_logic, plot_ui);
+
+                        if self.show_fov {
+                            self.draw_fields_of_view(&game_logic, plot_ui);
+                        }
+
+                        self.draw_pickups(&game_logic, plot_ui);
+
+                        self.display_entities(&game_logic, plot_ui);
 
-                        self.draw_obstacles(plot_ui);
+                        self.draw_obstacles(&game_logic, plot_ui);
 
                         let world_boundary = Line::new(PlotPoints::new(vec![
                             [0.0, 0.0],
-                            [1200.0, 0.0],
-                            [1200.0, 1000.0],
-                            [0.0, 1000.0],
+                            [arena_width, 0.0],
+                            [arena_width, arena_height],
+                            [0.0, arena_height],
                             [0.0, 0.0],
                         ]))
                             .color(egui::Color32::GREEN)
@@ -0,0 +1,52 @@
+use eframe::egui;
+use egui::{ViewportBuilder, ViewportCommand, ViewportId};
+
+use crate::ui::game_ui::GameUI;
+use crate::ui::server_ui::ServerUi;
+
+/// Represents the application windows: the game in the main window and the server in a second one.
+///
+/// Both windows run in the same event loop, so settings applied from the server window are
+/// picked up by the running simulation.
+pub struct MainUi {
+    game_ui: GameUI,
+    server_ui: ServerUi,
+}
+
+impl MainUi {
+    /// Creates a new `MainUi` instance.
+    ///
+    /// # Parameters
+    /// - `game_ui`: The game user interface, shown in the main window.
+    /// - `server_ui`: The server user interface, shown in a second window.
+    ///
+    /// # Returns
+    /// A new instance of `MainUi`.
+    pub fn new(game_ui: GameUI, server_ui: ServerUi) -> Self {
+        Self { game_ui, server_ui }
+    }
+}
+
+impl eframe::App for MainUi {
+    /// Updates both windows.
+    ///
+    /// # Parameters
+    /// - `ctx`: The `Context` instance for rendering.
+    /// - `frame`: The `Frame` instance for the application window.
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.game_ui.update(ctx, frame);
+
+        ctx.show_viewport_immediate(
+            ViewportId::from_hash_of("server_ui"),
+            ViewportBuilder::default().with_title("Server GUI"),
+            |ctx, _class| {
+                self.server_ui.show(ctx);
+
+                // Closing the server window closes the whole application
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    ctx.send_viewport_cmd_to(ViewportId::ROOT, ViewportCommand::Close);
+                }
+            },
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub(crate) mod game_ui;
+pub(crate) mod main_ui;
 pub(crate) mod server_ui;
@@ -1,9 +1,11 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use eframe::egui;
 
 use eframe::egui::{CentralPanel, Context, RichText, TopBottomPanel, Window};
-use crate::app_defines::AppDefines;
+use crate::config::ConfigFile;
 use crate::server::server_thread::ServerSettings;
+use crate::types::{add_message, MessageType};
 use crate::StyledMessage;
 
 /// A struct representing the server's user interface.
@@ -14,52 +16,44 @@ pub struct ServerUi {
     show_about: bool,
     /// Whether the 'Options' dialog is currently shown.
     show_options: bool,
-    /// The width of the arena.
-    arena_width: f32,
-    /// The height of the arena.
-    arena_height: f32,
-    /// The probability of obstacles appearing in the arena.
-    obstacle_probability: f64,
-    /// The available game modes.
-    game_modes: [&'static str; 1],
-    /// The rate of fire for bots.
-    bot_rate_of_fire: i32,
-    /// The penalty time for infractions.
-    penalty_time: i64,
-    /// The delay before a connection times out.
-    connection_timeout_delay: i32,
-    /// The duration messages are displayed.
-    message_duration: i32,
-    /// The maximum length of a message.
-    message_length: i32,
-    /// The score limit for the game.
-    score_limit: i32,
+    /// Thread-safe, shared server settings.
+    settings: Arc<Mutex<ServerSettings>>,
+    /// The settings being edited in the 'Options' dialog, written back on Apply.
+    draft_settings: ServerSettings,
+    /// The validation errors of the last Apply attempt.
+    options_errors: Vec<String>,
+    /// The configuration file holding the settings profiles.
+    config: ConfigFile,
+    /// The path the configuration file is saved to.
+    config_path: PathBuf,
+    /// The name of the profile being edited.
+    profile: String,
 }
 
 impl ServerUi {
-    /// Creates a new `ServerUi` instance with the specified messages and settings.
+    /// Creates a new `ServerUi` instance with the specified messages, settings and configuration.
     ///
     /// # Arguments
     ///
     /// * `messages` - A thread-safe, shared vector of styled messages.
     /// * `settings` - Thread-safe, shared server settings.
+    /// * `config` - The configuration file holding the settings profiles.
+    /// * `config_path` - The path the configuration file is saved to.
+    /// * `profile` - The name of the profile the settings were loaded from.
     ///
     /// # Returns
     ///
     /// A new `ServerUi` instance.
     ///
-    pub fn new(messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>) -> Self {
+    pub fn new(messages: Arc<Mutex<Vec<StyledMessage>>>, settings: Arc<Mutex<ServerSettings>>, config: ConfigFile, config_path: PathBuf, profile: String) -> Self {
+        let draft_settings = settings.lock().unwrap().clone();
         ServerUi { messages, show_about: false, show_options: false,
-            arena_width: AppDefines::ARENA_WIDTH,
-            arena_height: AppDefines::ARENA_HEIGHT,
-            obstacle_probability: AppDefines::OBSTACLE_PROBABILITY,
-            game_modes: AppDefines::GAME_MODES,
-            bot_rate_of_fire: AppDefines::BOT_RATE_OF_FIRE,
-            penalty_time: AppDefines::PENALTY_TIME,
-            connection_timeout_delay: AppDefines::CONNECTION_TIMEOUT_DELAY,
-            message_duration: AppDefines::MESSAGE_DURATION,
-            message_length: AppDefines::MESSAGE_LENGTH,
-            score_limit: AppDefines::SCORE_LIMIT, }
+            settings,
+            draft_settings,
+            options_errors: Vec::new(),
+            config,
+            config_path,
+            profile, }
     }
 
     /// Displays the main menu bar with options for general settings and help.
@@ -73,6 +67,9 @@ impl ServerUi {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("General", |ui| {
                     if ui.button("Options").clicked() {
+                        // Start editing from the settings currently in use
+                        self.draft_settings = self.settings.lock().unwrap().clone();
+                        self.options_errors.clear();
                         self.show_options = true;
                         ui.close_menu();
                     }
@@ -129,70 +126,192 @@ impl ServerUi {
             .open(&mut show_options)
             .show(ctx, |ui| {
 
+                ui.horizontal(|ui| {
+                    ui.label("Profile:");
+                    let previous_profile = self.profile.clone();
+                    egui::ComboBox::from_id_source("profile")
+                        .selected_text(&self.profile)
+                        .show_ui(ui, |ui| {
+                            for name in self.config.profiles.keys() {
+                                ui.selectable_value(&mut self.profile, name.clone(), name);
+                            }
+                        });
+                    // Switching to another profile loads its values into the dialog
+                    if self.profile != previous_profile {
+                        if let Ok(settings) = self.config.profile(&self.profile) {
+                            self.draft_settings = settings;
+                            self.options_errors.clear();
+                        }
+                    }
+                });
+
+                let draft = &mut self.draft_settings;
+
                 ui.horizontal(|ui| {
                     ui.label("Connection Timeout Delay:");
-                    ui.add(egui::DragValue::new(&mut self.connection_timeout_delay));
+                    ui.add(egui::DragValue::new(&mut draft.connection_timeout_delay));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Server Port (on restart):");
+                    ui.add(egui::DragValue::new(&mut draft.server_port));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("WebSocket Port (on restart):");
+                    ui.add(egui::DragValue::new(&mut draft.websocket_port));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Bot Rate of Fire:");
-                    ui.add(egui::DragValue::new(&mut self.bot_rate_of_fire));
+                    ui.add(egui::DragValue::new(&mut draft.bot_rate_of_fire));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Penalty Time:");
-                    ui.add(egui::DragValue::new(&mut self.penalty_time));
+                    ui.add(egui::DragValue::new(&mut draft.penalty_time));
                 });
 
+                ui.checkbox(&mut draft.empty_fire_penalty, "Penalize Firing While Empty");
+
 
                 ui.horizontal(|ui| {
                     ui.label("Message Duration:");
-                    ui.add(egui::DragValue::new(&mut self.message_duration));
+                    ui.add(egui::DragValue::new(&mut draft.message_duration));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Message Length:");
-                    ui.add(egui::DragValue::new(&mut self.message_length));
+                    ui.add(egui::DragValue::new(&mut draft.message_length));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Score Limit:");
-                    ui.add(egui::DragValue::new(&mut self.score_limit));
+                    ui.add(egui::DragValue::new(&mut draft.score_limit));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Arena Width:");
-                    ui.add(egui::DragValue::new(&mut self.arena_width));
+                    ui.add(egui::DragValue::new(&mut draft.arena_width));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Arena Height:");
-                    ui.add(egui::DragValue::new(&mut self.arena_height));
+                    ui.add(egui::DragValue::new(&mut draft.arena_height));
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Obstacle Probability:");
-                    ui.add(egui::DragValue::new(&mut self.obstacle_probability));
+                    ui.add(egui::DragValue::new(&mut draft.obstacle_probability).speed(0.01));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Obstacle Health:");
+                    ui.add(egui::DragValue::new(&mut draft.obstacle_health));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Obstacle Regeneration Delay:");
+                    ui.add(egui::DragValue::new(&mut draft.obstacle_regeneration_delay).speed(0.1));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Max Speed:");
+                    ui.add(egui::DragValue::new(&mut draft.max_speed));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Acceleration:");
+                    ui.add(egui::DragValue::new(&mut draft.acceleration));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Turn Rate:");
+                    ui.add(egui::DragValue::new(&mut draft.turn_rate).suffix("°/s"));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Pickup Spawn Interval:");
+                    ui.add(egui::DragValue::new(&mut draft.pickup_spawn_interval).speed(0.1));
                 });
 
-                if ui.button("Apply").clicked() {
-                    // Apply changes to server settings here
-                    self.show_options = false;
+                ui.horizontal(|ui| {
+                    ui.label("Max Pickups:");
+                    ui.add(egui::DragValue::new(&mut draft.pickup_max_count));
+                });
+
+                ui.checkbox(&mut draft.fog_of_war, "Fog of War");
+
+                ui.horizontal(|ui| {
+                    ui.label("View Range:");
+                    ui.add(egui::DragValue::new(&mut draft.view_range));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("View Angle:");
+                    ui.add(egui::DragValue::new(&mut draft.view_angle).suffix("°"));
+                });
+
+                for error in &self.options_errors {
+                    ui.colored_label(egui::Color32::RED, error);
                 }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() && self.apply_draft_settings() {
+                        self.show_options = false;
+                    }
+                    if ui.button("Save to Profile").clicked() && self.apply_draft_settings() {
+                        self.save_profile();
+                        self.show_options = false;
+                    }
+                });
             });
-        self.show_options = show_options;
+        // Closing the window discards the draft, but an Apply click must close it too
+        self.show_options &= show_options;
+    }
+
+    /// Validates the settings edited in the 'Options' dialog and writes them to the shared settings.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the settings were valid and applied, `false` if errors are shown in the dialog instead.
+    ///
+    fn apply_draft_settings(&mut self) -> bool {
+        match self.draft_settings.validate() {
+            Ok(()) => {
+                *self.settings.lock().unwrap() = self.draft_settings.clone();
+                self.options_errors.clear();
+                add_message(&self.messages, "[INFO] Server settings updated".to_string(), MessageType::Info);
+                true
+            }
+            Err(errors) => {
+                self.options_errors = errors;
+                false
+            }
+        }
     }
-}
 
-impl eframe::App for ServerUi {
-    /// Updates the server UI, showing the menu, about dialog, options dialog, and central panel with messages.
+    /// Stores the edited settings in the current profile and saves the configuration file.
+    fn save_profile(&mut self) {
+        self.config.profiles.insert(self.profile.clone(), self.draft_settings.clone());
+        self.config.active_profile = self.profile.clone();
+        match self.config.save(&self.config_path) {
+            Ok(()) => add_message(
+                &self.messages,
+                format!("[INFO] Profile '{}' saved to {}", self.profile, self.config_path.display()),
+                MessageType::Info,
+            ),
+            Err(e) => add_message(&self.messages, format!("[ERROR] {}", e), MessageType::Error),
+        }
+    }
+
+    /// Shows the server UI: the menu, about dialog, options dialog, and central panel with messages.
     ///
     /// # Arguments
     ///
-    /// * `ctx` - The Egui context.
-    /// * `_frame` - The Eframe frame.
+    /// * `ctx` - The Egui context of the viewport the server UI is drawn in.
     ///
-    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+    pub fn show(&mut self, ctx: &Context) {
         self.show_menu(ctx);
         self.show_about_dialog(ctx);
         self.show_options_dialog(ctx);
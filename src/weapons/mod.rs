@@ -0,0 +1,195 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// Represents the characteristics of the shots of a weapon.
+pub struct Weapon {
+    /// The speed of the bullets.
+    pub speed: f32,
+    /// The radius of the bullets.
+    pub radius: f32,
+    /// The damage dealt by a bullet to entities and destructible obstacles.
+    pub damage: f32,
+    /// The time after which a bullet disappears.
+    pub lifetime: Duration,
+    /// The minimum delay between two shots.
+    pub cooldown: Duration,
+    /// The total angle in radians the bullets are randomly spread over, centered on the gun orientation.
+    pub spread: f32,
+    /// The number of times a bullet bounces off walls and obstacles before disappearing.
+    pub bounces: u32,
+    /// The number of bullets fired by a single shot.
+    pub pellets: u32,
+    /// The number of shots before the weapon must be reloaded.
+    pub magazine: u32,
+    /// The time needed to reload the weapon.
+    pub reload_time: Duration,
+    /// The heat added by each shot, the weapon overheats when its heat reaches 1.
+    pub heat_per_shot: f32,
+}
+
+impl Weapon {
+    /// The pistol preset.
+    pub const PISTOL: Weapon = Weapon {
+        speed: 500.0,
+        radius: 5.0,
+        damage: 10.0,
+        lifetime: Duration::from_secs(2),
+        cooldown: Duration::from_secs(1),
+        spread: 0.0,
+        bounces: 2,
+        pellets: 1,
+        magazine: 12,
+        reload_time: Duration::from_millis(1500),
+        heat_per_shot: 0.0,
+    };
+
+    /// The shotgun preset.
+    pub const SHOTGUN: Weapon = Weapon {
+        speed: 450.0,
+        radius: 3.0,
+        damage: 6.0,
+        lifetime: Duration::from_millis(600),
+        cooldown: Duration::from_millis(1500),
+        spread: PI / 6.0,
+        bounces: 0,
+        pellets: 6,
+        magazine: 4,
+        reload_time: Duration::from_millis(2500),
+        heat_per_shot: 0.0,
+    };
+
+    /// The rifle preset.
+    pub const RIFLE: Weapon = Weapon {
+        speed: 1200.0,
+        radius: 3.0,
+        damage: 40.0,
+        lifetime: Duration::from_millis(1500),
+        cooldown: Duration::from_millis(2500),
+        spread: 0.0,
+        bounces: 0,
+        pellets: 1,
+        magazine: 5,
+        reload_time: Duration::from_secs(3),
+        heat_per_shot: 0.0,
+    };
+
+    /// The machine gun preset.
+    pub const MACHINE_GUN: Weapon = Weapon {
+        speed: 600.0,
+        radius: 3.0,
+        damage: 4.0,
+        lifetime: Duration::from_millis(1500),
+        cooldown: Duration::from_millis(150),
+        spread: PI / 24.0,
+        bounces: 0,
+        pellets: 1,
+        magazine: 60,
+        reload_time: Duration::from_secs(3),
+        heat_per_shot: 0.1,
+    };
+
+    /// The ricochet preset.
+    pub const RICOCHET: Weapon = Weapon {
+        speed: 400.0,
+        radius: 6.0,
+        damage: 12.0,
+        lifetime: Duration::from_secs(4),
+        cooldown: Duration::from_millis(1200),
+        spread: 0.0,
+        bounces: 6,
+        pellets: 1,
+        magazine: 6,
+        reload_time: Duration::from_secs(2),
+        heat_per_shot: 0.0,
+    };
+}
+
+/// Represents the weapon presets entities can switch between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeaponKind {
+    /// A balanced weapon, firing one bullet per second.
+    Pistol,
+    /// A short range weapon firing a spread of pellets.
+    Shotgun,
+    /// A slow firing weapon with fast and deadly bullets.
+    Rifle,
+    /// A fast firing weapon with weak and imprecise bullets, which overheats when firing continuously.
+    MachineGun,
+    /// A weapon whose slow bullets bounce many times.
+    Ricochet,
+}
+
+impl WeaponKind {
+    /// All the weapon presets.
+    pub const ALL: [WeaponKind; 5] = [
+        WeaponKind::Pistol,
+        WeaponKind::Shotgun,
+        WeaponKind::Rifle,
+        WeaponKind::MachineGun,
+        WeaponKind::Ricochet,
+    ];
+
+    /// Returns the name of the weapon, as used by the protocol.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::Pistol => "PISTOL",
+            WeaponKind::Shotgun => "SHOTGUN",
+            WeaponKind::Rifle => "RIFLE",
+            WeaponKind::MachineGun => "MACHINEGUN",
+            WeaponKind::Ricochet => "RICOCHET",
+        }
+    }
+
+    /// Finds a weapon preset from its name.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the weapon, case insensitive.
+    ///
+    /// # Returns
+    /// The weapon preset, or `None` if no preset has this name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Returns the characteristics of the weapon.
+    pub fn weapon(&self) -> &'static Weapon {
+        match self {
+            WeaponKind::Pistol => &Weapon::PISTOL,
+            WeaponKind::Shotgun => &Weapon::SHOTGUN,
+            WeaponKind::Rifle => &Weapon::RIFLE,
+            WeaponKind::MachineGun => &Weapon::MACHINE_GUN,
+            WeaponKind::Ricochet => &Weapon::RICOCHET,
+        }
+    }
+}
+
+/// Represents the reasons why an entity cannot fire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShotError {
+    /// The entity has been eliminated.
+    Eliminated,
+    /// The entity fired with an empty weapon and is not allowed to fire until its penalty ends.
+    Penalized,
+    /// The weapon is being reloaded.
+    Reloading,
+    /// The weapon has no ammunition left and must be reloaded.
+    NoAmmo,
+    /// The weapon is too hot and must cool down completely.
+    Overheated,
+    /// The delay between two shots of the weapon has not elapsed yet.
+    CoolingDown,
+}
+
+impl ShotError {
+    /// Returns the name of the error, as used by the protocol.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShotError::Eliminated => "DEAD",
+            ShotError::Penalized => "PENALTY",
+            ShotError::Reloading => "RELOADING",
+            ShotError::NoAmmo => "NO_AMMO",
+            ShotError::Overheated => "OVERHEATED",
+            ShotError::CoolingDown => "COOLDOWN",
+        }
+    }
+}
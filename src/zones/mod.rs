@@ -0,0 +1,107 @@
+use std::time::{Duration, Instant};
+
+use rapier2d::prelude::*;
+
+/// Represents a circular control zone (King of the Hill).
+///
+/// The zone awards points over time to the entity occupying it alone.
+pub struct ControlZone {
+    pub center: (f32, f32),
+    pub radius: f32,
+    pub owner: Option<RigidBodyHandle>,
+    pub last_award: Instant,
+}
+
+impl ControlZone {
+    /// Creates a new control zone.
+    ///
+    /// # Parameters
+    /// - `center`: A tuple representing the (x, y) center of the zone.
+    /// - `radius`: The radius of the zone.
+    ///
+    /// # Returns
+    /// A new instance of `ControlZone` without any owner.
+    pub fn new(center: (f32, f32), radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            owner: None,
+            last_award: Instant::now(),
+        }
+    }
+
+    /// Checks whether a point lies inside the zone.
+    ///
+    /// # Parameters
+    /// - `x`: The x coordinate of the point.
+    /// - `y`: The y coordinate of the point.
+    ///
+    /// # Returns
+    /// `true` if the point is inside the zone, `false` otherwise.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let dx = x - self.center.0;
+        let dy = y - self.center.1;
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+/// Represents the shrinking safe zone (Battle Royale).
+///
+/// The radius decreases linearly from `initial_radius` to `min_radius` over `shrink_duration`.
+pub struct SafeZone {
+    pub center: (f32, f32),
+    pub initial_radius: f32,
+    pub min_radius: f32,
+    pub radius: f32,
+    pub shrink_duration: Duration,
+    pub started_at: Instant,
+}
+
+impl SafeZone {
+    /// Creates a new safe zone.
+    ///
+    /// # Parameters
+    /// - `center`: A tuple representing the (x, y) center of the zone.
+    /// - `initial_radius`: The radius of the zone when the round starts.
+    /// - `min_radius`: The radius of the zone once fully shrunk.
+    /// - `shrink_duration`: The time it takes to shrink from `initial_radius` to `min_radius`.
+    ///
+    /// # Returns
+    /// A new instance of `SafeZone`.
+    pub fn new(center: (f32, f32), initial_radius: f32, min_radius: f32, shrink_duration: Duration) -> Self {
+        Self {
+            center,
+            initial_radius,
+            min_radius,
+            radius: initial_radius,
+            shrink_duration,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Updates the radius of the zone according to the elapsed time.
+    pub fn update(&mut self) {
+        let progress = (self.started_at.elapsed().as_secs_f32() / self.shrink_duration.as_secs_f32()).min(1.0);
+        self.radius = self.initial_radius - (self.initial_radius - self.min_radius) * progress;
+    }
+
+    /// Restarts the shrinking from the initial radius.
+    pub fn restart(&mut self) {
+        self.radius = self.initial_radius;
+        self.started_at = Instant::now();
+    }
+
+    /// Checks whether a point lies inside the zone.
+    ///
+    /// # Parameters
+    /// - `x`: The x coordinate of the point.
+    /// - `y`: The y coordinate of the point.
+    ///
+    /// # Returns
+    /// `true` if the point is inside the zone, `false` otherwise.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let dx = x - self.center.0;
+        let dy = y - self.center.1;
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
@@ -0,0 +1,63 @@
+//! A bot wandering towards the most open direction, sweeping its gun and firing whenever it points at a bot.
+//!
+//! Usage: `cargo run -p urss_client --example simple_bot -- [address] [name]`
+
+use std::f64::consts::TAU;
+use std::thread;
+use std::time::Duration;
+
+use urss_client::{Client, ClientError, RayHit};
+
+/// The number of rays used to find the most open direction.
+const LIDAR_RAYS: usize = 16;
+/// The speed of the bot while wandering.
+const SPEED: f32 = 100.0;
+/// The angle in radians the gun turns by at each tick while no bot is in its line of fire.
+const SWEEP_STEP: f64 = 0.2;
+/// The delay between two decisions.
+const TICK: Duration = Duration::from_millis(50);
+
+fn main() -> Result<(), ClientError> {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| "127.0.0.1:6969".to_string());
+    let name = args.next().unwrap_or_else(|| "SimpleBot".to_string());
+
+    let client = Client::connect(&address, &name)?;
+    println!("{} connected to {}", client.name(), address);
+
+    loop {
+        match think(&client) {
+            // A refused command only costs this tick
+            Ok(()) | Err(ClientError::Rejected(_)) => {}
+            Err(e) => return Err(e),
+        }
+        thread::sleep(TICK);
+    }
+}
+
+/// Decides what the bot does for one tick.
+fn think(client: &Client) -> Result<(), ClientError> {
+    let status = client.status()?;
+    if status.ammo == 0 && status.reload == 0.0 {
+        client.reload()?;
+    }
+
+    // Walk towards the most open direction seen by the lidar
+    let orientation = client.orientation()?;
+    let distances = client.lidar(LIDAR_RAYS, None)?;
+    if let Some((best, _)) = distances.iter().enumerate().max_by(|(_, a), (_, b)| a.total_cmp(b)) {
+        let heading = orientation.heading + TAU * best as f64 / LIDAR_RAYS as f64;
+        client.turn(heading.rem_euclid(TAU))?;
+        client.set_speed(SPEED)?;
+    }
+
+    // Fire while the gun points at a bot, keep sweeping otherwise
+    let gun_angle = (orientation.gun - orientation.heading) as f32;
+    match client.ray(gun_angle, None)? {
+        Some((_, RayHit::Bot(name))) => {
+            println!("Firing at {}", name);
+            client.fire()
+        }
+        _ => client.aim((orientation.gun + SWEEP_STEP).rem_euclid(TAU)),
+    }
+}
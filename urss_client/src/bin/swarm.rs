@@ -0,0 +1,385 @@
+//! Load-testing tool connecting a swarm of simulated bots to a running server.
+//!
+//! Each bot sends commands picked from a weighted mix at a fixed rate, and the tool reports the latency
+//! percentiles, rejected replies and disconnections of each command once the test ends.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::TAU;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use urss_client::{Client, ClientError, Protocol};
+
+/// The usage message printed when the command line is invalid.
+const USAGE: &str = "Usage: swarm [--address <host:port>] [--bots <count>] [--rate <commands per second per bot>]
+             [--duration <seconds>] [--ramp-up <seconds>] [--mix <COMMAND:weight,...>]
+             [--arena-width <width>] [--arena-height <height>]
+Commands: NAME, LIVE, MSG, MOVE, TURN, AIM, FIRE, WEAPON, RELOAD, CBOT, CPROJ, NBOT, ORIENT, STATUS,
+          PICK, PATH, ZONE, OBST, RAY, LIDAR";
+
+/// The command mix used when none is given.
+const DEFAULT_MIX: &str = "NAME:1,LIVE:2,MSG:1,MOVE:2,TURN:2,AIM:2,FIRE:2,CBOT:4,STATUS:2,LIDAR:1";
+
+/// Represents the parameters of a load test.
+struct SwarmConfig {
+    address: String,
+    bots: usize,
+    /// The number of commands each bot sends per second.
+    rate: f64,
+    duration: Duration,
+    /// The time over which the bots are connected, to avoid a burst of connections.
+    ramp_up: Duration,
+    /// The commands the bots send and their relative weights.
+    mix: Vec<(&'static str, u32)>,
+    /// The width and height of the arena of the server, in which the `PATH` targets are picked.
+    arena_size: (f64, f64),
+}
+
+impl SwarmConfig {
+    /// Parses the command line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, without the program name.
+    ///
+    /// # Returns
+    ///
+    /// The configuration of the load test, or an error message if an argument is invalid.
+    ///
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = SwarmConfig {
+            address: "127.0.0.1:6969".to_string(),
+            bots: 10,
+            rate: 10.0,
+            duration: Duration::from_secs(30),
+            ramp_up: Duration::from_secs(1),
+            mix: parse_mix(DEFAULT_MIX)?,
+            arena_size: (1200.0, 1000.0),
+        };
+
+        let mut args = args.into_iter();
+        while let Some(option) = args.next() {
+            let value = args.next().ok_or_else(|| format!("Missing value for {}", option))?;
+            let invalid = || format!("Invalid value for {}: {}", option, value);
+            let seconds = || value.parse().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()).ok_or_else(invalid);
+            let length = || value.parse::<f64>().ok().filter(|length| *length > 0.0 && length.is_finite()).ok_or_else(invalid);
+            match option.as_str() {
+                "--address" => config.address = value.clone(),
+                "--bots" => config.bots = value.parse().map_err(|_| invalid())?,
+                "--rate" => config.rate = value.parse().map_err(|_| invalid())?,
+                "--duration" => config.duration = seconds()?,
+                "--ramp-up" => config.ramp_up = seconds()?,
+                "--mix" => config.mix = parse_mix(&value)?,
+                "--arena-width" => config.arena_size.0 = length()?,
+                "--arena-height" => config.arena_size.1 = length()?,
+                _ => return Err(format!("Unknown option: {}", option)),
+            }
+        }
+
+        if config.bots == 0 || !(config.rate > 0.0 && config.rate.is_finite()) {
+            return Err("The number of bots and the rate must be positive".to_string());
+        }
+        if Duration::try_from_secs_f64(1.0 / config.rate).is_err() {
+            return Err(format!("Invalid value for --rate: {}", config.rate));
+        }
+        Ok(config)
+    }
+}
+
+/// Parses a command mix such as `CBOT:4,MOVE:2`.
+///
+/// # Arguments
+///
+/// * `mix` - The commands and their weights, separated by commas.
+///
+/// # Returns
+///
+/// The commands and their weights, or an error message if a command is unknown or no weight is positive.
+///
+fn parse_mix(mix: &str) -> Result<Vec<(&'static str, u32)>, String> {
+    const COMMANDS: [&str; 20] = [
+        Protocol::SET_NAME, Protocol::ALIVE, Protocol::MESSAGE, Protocol::MOVE, Protocol::TURN, Protocol::AIM,
+        Protocol::FIRE, Protocol::WEAPON, Protocol::RELOAD, Protocol::QUERY_CLOSEST_BOT,
+        Protocol::QUERY_CLOSEST_PROJECTILE, Protocol::QUERY_BY_NAME, Protocol::QUERY_ORIENTATION,
+        Protocol::QUERY_STATUS, Protocol::QUERY_PICKUPS, Protocol::QUERY_PATH, Protocol::QUERY_ZONE,
+        Protocol::QUERY_OBSTACLES, Protocol::QUERY_RAY, Protocol::QUERY_LIDAR,
+    ];
+
+    let mix = mix.split(',')
+        .map(|entry| {
+            let (command, weight) = entry.split_once(':').unwrap_or((entry, "1"));
+            let command = COMMANDS.into_iter()
+                .find(|known| known.eq_ignore_ascii_case(command.trim()))
+                .ok_or_else(|| format!("Unknown command in mix: {}", command))?;
+            let weight = weight.trim().parse().map_err(|_| format!("Invalid weight in mix: {}", entry))?;
+            Ok((command, weight))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if mix.iter().all(|(_, weight)| *weight == 0) {
+        return Err("The command mix needs a positive weight".to_string());
+    }
+    Ok(mix)
+}
+
+/// Represents what a bot measured during the test.
+#[derive(Default)]
+struct SwarmStats {
+    /// The latency of each reply, by command.
+    latencies: BTreeMap<&'static str, Vec<Duration>>,
+    /// The number of `ERROR` replies, by command.
+    rejected: BTreeMap<&'static str, u32>,
+    /// The number of commands which lost the connection, by command.
+    disconnects: BTreeMap<&'static str, u32>,
+    /// The number of bots which could not connect at all.
+    connect_failures: u32,
+}
+
+impl SwarmStats {
+    /// Adds the measures of another bot.
+    fn merge(&mut self, other: SwarmStats) {
+        for (command, latencies) in other.latencies {
+            self.latencies.entry(command).or_default().extend(latencies);
+        }
+        for (command, count) in other.rejected {
+            *self.rejected.entry(command).or_default() += count;
+        }
+        for (command, count) in other.disconnects {
+            *self.disconnects.entry(command).or_default() += count;
+        }
+        self.connect_failures += other.connect_failures;
+    }
+
+    /// Prints the report of the test.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The actual duration of the test.
+    ///
+    fn report(&mut self, elapsed: Duration) {
+        let total: usize = self.latencies.values().map(Vec::len).sum();
+        println!(
+            "{:<8} {:>8} {:>9} {:>9} {:>9} {:>9} {:>9} {:>11}",
+            "command", "replies", "p50 ms", "p90 ms", "p99 ms", "max ms", "rejected", "disconnects"
+        );
+
+        let commands: BTreeSet<_> = self.latencies.keys().chain(self.disconnects.keys()).copied().collect();
+        for command in commands {
+            let latencies = self.latencies.entry(command).or_default();
+            latencies.sort();
+            println!(
+                "{:<8} {:>8} {:>9.2} {:>9.2} {:>9.2} {:>9.2} {:>9} {:>11}",
+                command,
+                latencies.len(),
+                percentile(latencies, 0.5),
+                percentile(latencies, 0.9),
+                percentile(latencies, 0.99),
+                percentile(latencies, 1.0),
+                self.rejected.get(command).copied().unwrap_or(0),
+                self.disconnects.get(command).copied().unwrap_or(0),
+            );
+        }
+
+        println!(
+            "{} replies in {:.1}s ({:.0} per second), {} rejected, {} disconnects, {} failed connections",
+            total,
+            elapsed.as_secs_f64(),
+            total as f64 / elapsed.as_secs_f64(),
+            self.rejected.values().sum::<u32>(),
+            self.disconnects.values().sum::<u32>(),
+            self.connect_failures,
+        );
+    }
+}
+
+/// Finds a percentile of sorted latencies.
+///
+/// # Arguments
+///
+/// * `latencies` - The latencies, sorted.
+/// * `fraction` - The percentile, from 0 to 1.
+///
+/// # Returns
+///
+/// The latency in milliseconds, zero if there is none.
+///
+fn percentile(latencies: &[Duration], fraction: f64) -> f64 {
+    if latencies.is_empty() {
+        return 0.0;
+    }
+    let index = ((latencies.len() as f64 * fraction).ceil() as usize).clamp(1, latencies.len()) - 1;
+    latencies[index].as_secs_f64() * 1000.0
+}
+
+/// A small xorshift generator, so each bot picks its commands independently without extra dependencies.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random number between 0 and 1.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Builds the arguments of a command of the mix.
+///
+/// # Arguments
+///
+/// * `command` - The command keyword.
+/// * `index` - The index of the bot sending the command.
+/// * `config` - The configuration of the test.
+/// * `rng` - The random generator of the bot.
+///
+fn arguments(command: &str, index: usize, config: &SwarmConfig, rng: &mut Xorshift) -> Vec<String> {
+    match command {
+        Protocol::SET_NAME => vec![format!("swarm-{}", index)],
+        Protocol::MESSAGE => vec![format!("hello from swarm-{}", index)],
+        Protocol::MOVE => vec![(rng.unit() * 200.0 - 50.0).to_string()],
+        Protocol::TURN | Protocol::AIM => vec![(rng.unit() * TAU).to_string()],
+        Protocol::QUERY_RAY => vec![(rng.unit() * TAU - TAU / 2.0).to_string()],
+        Protocol::QUERY_LIDAR => vec!["8".to_string()],
+        Protocol::QUERY_BY_NAME => vec![format!("swarm-{}", rng.next() as usize % config.bots)],
+        Protocol::QUERY_PATH => {
+            let (arena_width, arena_height) = config.arena_size;
+            vec![(rng.unit() * arena_width).to_string(), (rng.unit() * arena_height).to_string()]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Runs one bot of the swarm until the end of the test.
+///
+/// # Arguments
+///
+/// * `index` - The index of the bot.
+/// * `config` - The configuration of the test.
+/// * `end` - The time at which the test ends.
+///
+/// # Returns
+///
+/// The measures of the bot.
+///
+fn run_bot(index: usize, config: &SwarmConfig, end: Instant) -> SwarmStats {
+    let mut stats = SwarmStats::default();
+    let mut client = match Client::connect(&config.address, &format!("swarm-{}", index)) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("swarm-{} could not connect: {}", index, e);
+            stats.connect_failures += 1;
+            return stats;
+        }
+    };
+    // Disconnections are counted, the next command connects again
+    client.reconnect_attempts = 0;
+
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_nanos() as u64);
+    let mut rng = Xorshift(seed ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+    let total_weight: u32 = config.mix.iter().map(|(_, weight)| weight).sum();
+    let interval = Duration::from_secs_f64(1.0 / config.rate);
+    let mut next = Instant::now();
+
+    while next < end {
+        let mut pick = (rng.next() % total_weight as u64) as u32;
+        let &(command, _) = config.mix.iter()
+            .find(|(_, weight)| {
+                let found = pick < *weight;
+                pick = pick.saturating_sub(*weight);
+                found
+            })
+            .unwrap_or(&config.mix[0]);
+
+        let arguments = arguments(command, index, config, &mut rng);
+        let sent = Instant::now();
+        match client.request(command, &arguments) {
+            Ok(reply) => {
+                stats.latencies.entry(command).or_default().push(sent.elapsed());
+                if reply.split(Protocol::ARGUMENT_SEP).next() == Some(Protocol::ERROR_REPLY) {
+                    *stats.rejected.entry(command).or_default() += 1;
+                }
+            }
+            Err(ClientError::Io(_)) => *stats.disconnects.entry(command).or_default() += 1,
+            Err(_) => *stats.rejected.entry(command).or_default() += 1,
+        }
+
+        // Keep the rate steady, without catching up on the time lost to slow replies
+        next = (next + interval).max(Instant::now());
+        thread::sleep(next.saturating_duration_since(Instant::now()));
+    }
+
+    client.quit().ok();
+    stats
+}
+
+fn main() -> ExitCode {
+    let config = match SwarmConfig::parse(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!(
+        "Connecting {} bots to {} at {} commands per second each for {:.0}s",
+        config.bots, config.address, config.rate, config.duration.as_secs_f64()
+    );
+
+    let start = Instant::now();
+    let end = start + config.ramp_up + config.duration;
+    let mut stats = SwarmStats::default();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..config.bots)
+            .map(|index| {
+                let config = &config;
+                scope.spawn(move || {
+                    thread::sleep(config.ramp_up.mul_f64(index as f64 / config.bots as f64));
+                    run_bot(index, config, end)
+                })
+            })
+            .collect();
+        for handle in handles {
+            stats.merge(handle.join().unwrap_or_default());
+        }
+    });
+
+    stats.report(start.elapsed());
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<SwarmConfig, String> {
+        SwarmConfig::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn invalid_durations_are_usage_errors() {
+        for value in ["-1", "NaN", "inf", "1e300", "soon"] {
+            assert_eq!(parse(&["--duration", value]).err(), Some(format!("Invalid value for --duration: {}", value)));
+            assert!(parse(&["--ramp-up", value]).is_err());
+        }
+        assert_eq!(parse(&["--duration", "2.5"]).unwrap().duration, Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn path_targets_stay_in_the_given_arena() {
+        let config = parse(&["--arena-width", "300", "--arena-height", "200", "--bots", "1"]).unwrap();
+        let mut rng = Xorshift(42);
+        for _ in 0..100 {
+            let target: Vec<f64> = arguments(Protocol::QUERY_PATH, 0, &config, &mut rng).iter()
+                .map(|value| value.parse().unwrap())
+                .collect();
+            assert!((0.0..=300.0).contains(&target[0]) && (0.0..=200.0).contains(&target[1]));
+        }
+        assert!(parse(&["--arena-width", "0"]).is_err());
+    }
+}
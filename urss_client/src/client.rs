@@ -0,0 +1,606 @@
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::ClientError;
+use crate::protocol::{
+    BotInfo, BotPosition, Obstacle, Orientation, Pickup, Projectile, Protocol, RayHit, Reply, Status, Zone,
+};
+
+/// Represents an open connection to the server.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+    /// The time of the last line sent to the server, to know when a heartbeat is due.
+    last_sent: Instant,
+}
+
+impl Connection {
+    /// Connects to the server and names the bot, which adds it to the game.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the server, such as `127.0.0.1:6969`.
+    /// * `name` - The name of the bot.
+    /// * `timeout` - The time to wait for each reply.
+    ///
+    /// # Returns
+    ///
+    /// The connection, or the error which prevented it.
+    ///
+    fn open(address: &str, name: &str, timeout: Duration) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_nodelay(true)?;
+        let mut connection = Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+            last_sent: Instant::now(),
+        };
+
+        let reply = connection.exchange(&[Protocol::SET_NAME, name].join(Protocol::ARGUMENT_SEP))?;
+        if reply != Protocol::OK_REPLY {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Name refused: {}", reply)));
+        }
+        Ok(connection)
+    }
+
+    /// Leaves the game cleanly, so the server removes the bot of this connection before a new one registers.
+    ///
+    /// The connection may already be broken, in which case the server removes the bot once it notices it
+    /// or once the connection times out.
+    fn close(mut self) {
+        self.send(Protocol::QUIT).ok();
+        self.writer.get_ref().shutdown(Shutdown::Both).ok();
+    }
+
+    /// Sends a line to the server.
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// Sends a command to the server and waits for its reply.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The command and its arguments, without line ending.
+    ///
+    /// # Returns
+    ///
+    /// The reply without its line ending, or an error if the connection is closed or broken.
+    ///
+    fn exchange(&mut self, line: &str) -> io::Result<String> {
+        self.send(line)?;
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "Connection closed by the server"));
+        }
+        Ok(reply.trim_end().to_string())
+    }
+}
+
+/// Represents a bot connected to the server.
+///
+/// Each method sends one command and waits for its reply, so a `Client` can be shared between threads.
+/// A background thread sends `LIVE` whenever the bot has been silent for the heartbeat interval, so the
+/// server does not time the connection out while the bot thinks. When the connection breaks, the client
+/// quits the broken session, then connects again and names its bot again, up to the number of reconnection
+/// attempts. Only queries are sent again: a command which may have reached the server, such as `FIRE`,
+/// fails with the error instead of being applied twice.
+///
+/// # Examples
+///
+/// ```no_run
+/// use urss_client::Client;
+///
+/// let client = Client::connect("127.0.0.1:6969", "Rusty").unwrap();
+/// if let Some(bot) = client.closest_bot().unwrap() {
+///     let orientation = client.orientation().unwrap();
+///     println!("{} is {} away, my gun points at {}", bot.name, bot.distance, orientation.gun);
+///     client.fire().ok();
+/// }
+/// ```
+pub struct Client {
+    address: String,
+    name: String,
+    /// The connection, `None` while the client is disconnected.
+    connection: Arc<Mutex<Option<Connection>>>,
+    /// The number of times the client tries to connect again before a command fails.
+    pub reconnect_attempts: u32,
+    /// The delay between two reconnection attempts.
+    pub reconnect_delay: Duration,
+    /// The time to wait for each reply.
+    pub reply_timeout: Duration,
+}
+
+impl Client {
+    /// The default delay of silence after which a heartbeat is sent.
+    pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+    /// The default number of reconnection attempts.
+    pub const RECONNECT_ATTEMPTS: u32 = 3;
+    /// The default delay between two reconnection attempts.
+    pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+    /// The default time to wait for each reply.
+    pub const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Connects a bot to the server, sending heartbeats at the default interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the server, such as `127.0.0.1:6969`.
+    /// * `name` - The name of the bot.
+    ///
+    /// # Returns
+    ///
+    /// The connected client, or an error if the name is invalid or the server cannot be reached.
+    ///
+    pub fn connect(address: &str, name: &str) -> Result<Self, ClientError> {
+        Self::connect_with_heartbeat(address, name, Self::HEARTBEAT_INTERVAL)
+    }
+
+    /// Connects a bot to the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the server, such as `127.0.0.1:6969`.
+    /// * `name` - The name of the bot.
+    /// * `heartbeat_interval` - The delay of silence after which a heartbeat is sent.
+    ///
+    /// # Returns
+    ///
+    /// The connected client, or an error if the name is invalid or the server cannot be reached.
+    ///
+    pub fn connect_with_heartbeat(address: &str, name: &str, heartbeat_interval: Duration) -> Result<Self, ClientError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ClientError::InvalidArgument(name.to_string()));
+        }
+        check_argument(name)?;
+
+        let connection = Connection::open(address, name, Self::REPLY_TIMEOUT)?;
+        let client = Client {
+            address: address.to_string(),
+            name: name.to_string(),
+            connection: Arc::new(Mutex::new(Some(connection))),
+            reconnect_attempts: Self::RECONNECT_ATTEMPTS,
+            reconnect_delay: Self::RECONNECT_DELAY,
+            reply_timeout: Self::REPLY_TIMEOUT,
+        };
+        spawn_heartbeat(Arc::downgrade(&client.connection), heartbeat_interval);
+        Ok(client)
+    }
+
+    /// Returns the name of the bot.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sends a raw command, for commands this client does not wrap.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The command keyword.
+    /// * `arguments` - The arguments of the command.
+    ///
+    /// # Returns
+    ///
+    /// The reply of the server, or an error if an argument would break the framing or the connection is lost.
+    /// Queries are retried on a new connection, other commands are not sent again once they may have
+    /// reached the server.
+    ///
+    pub fn request(&self, command: &str, arguments: &[String]) -> Result<String, ClientError> {
+        check_argument(command)?;
+        for argument in arguments {
+            check_argument(argument)?;
+        }
+        let line = std::iter::once(command.to_string())
+            .chain(arguments.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(Protocol::ARGUMENT_SEP);
+
+        let retried = RETRIED_COMMANDS.contains(&command);
+        let mut connection = self.connection.lock().unwrap();
+        let mut last_error = None;
+        for attempt in 0..=self.reconnect_attempts {
+            if connection.is_none() {
+                if attempt > 0 {
+                    thread::sleep(self.reconnect_delay);
+                }
+                match Connection::open(&self.address, &self.name, self.reply_timeout) {
+                    Ok(reconnected) => *connection = Some(reconnected),
+                    Err(e) => {
+                        last_error = Some(e);
+                        continue;
+                    }
+                }
+            }
+            match connection.as_mut().map(|open| open.exchange(&line)) {
+                Some(Ok(reply)) => return Ok(reply),
+                Some(Err(e)) => {
+                    if let Some(broken) = connection.take() {
+                        broken.close();
+                    }
+                    if !retried {
+                        return Err(ClientError::Io(e));
+                    }
+                    last_error = Some(e);
+                }
+                None => {}
+            }
+        }
+        Err(ClientError::Io(last_error.unwrap_or_else(|| io::Error::from(io::ErrorKind::NotConnected))))
+    }
+
+    /// Sends a command expecting `OK`.
+    fn command(&self, command: &str, arguments: &[String]) -> Result<(), ClientError> {
+        let reply = check_reply(self.request(command, arguments)?)?;
+        if reply == Protocol::OK_REPLY {
+            Ok(())
+        } else {
+            Err(ClientError::Malformed(reply))
+        }
+    }
+
+    /// Sends a query whose reply may be `EMPTY`, and parses its reply.
+    fn query<T>(&self, command: &str, arguments: &[String], parse: impl FnOnce(&mut Reply) -> Result<T, ClientError>) -> Result<Option<T>, ClientError> {
+        let reply = check_reply(self.request(command, arguments)?)?;
+        if reply == Protocol::EMPTY_REPLY {
+            return Ok(None);
+        }
+        parse(&mut Reply::new(&reply)).map(Some)
+    }
+
+    /// Sends a query replying with a count followed by the items, and parses its reply.
+    fn query_list<T>(&self, command: &str, arguments: &[String], parse: impl Fn(&mut Reply) -> Result<T, ClientError>) -> Result<Option<Vec<T>>, ClientError> {
+        self.query(command, arguments, |reply| {
+            let count = reply.value::<usize>()?;
+            (0..count).map(|_| parse(reply)).collect()
+        })
+    }
+
+    /// Renames the bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The new name of the bot, also used when reconnecting.
+    ///
+    pub fn set_name(&mut self, name: &str) -> Result<(), ClientError> {
+        let name = name.trim();
+        self.command(Protocol::SET_NAME, &[name.to_string()])?;
+        self.name = name.to_string();
+        Ok(())
+    }
+
+    /// Sets the color of the bot. The server does not apply colors yet.
+    ///
+    /// # Returns
+    ///
+    /// The raw reply of the server.
+    ///
+    pub fn set_color(&self, red: u8, green: u8, blue: u8) -> Result<String, ClientError> {
+        check_reply(self.request(Protocol::SET_COLOR, &[red.to_string(), green.to_string(), blue.to_string()])?)
+    }
+
+    /// Tells the server the bot is alive, which the heartbeat thread already does when the bot is silent.
+    pub fn alive(&self) -> Result<(), ClientError> {
+        check_reply(self.request(Protocol::ALIVE, &[])?).map(|_| ())
+    }
+
+    /// Sends a short message. The server does not relay messages yet.
+    ///
+    /// # Returns
+    ///
+    /// The raw reply of the server.
+    ///
+    pub fn message(&self, text: &str) -> Result<String, ClientError> {
+        check_reply(self.request(Protocol::MESSAGE, &[text.to_string()])?)
+    }
+
+    /// Sets the speed the bot accelerates to along its heading, negative to move backwards.
+    pub fn set_speed(&self, speed: f32) -> Result<(), ClientError> {
+        self.command(Protocol::MOVE, &[speed.to_string()])
+    }
+
+    /// Turns the body of the bot towards an absolute heading in radians.
+    pub fn turn(&self, heading: f64) -> Result<(), ClientError> {
+        self.command(Protocol::TURN, &[heading.to_string()])
+    }
+
+    /// Turns the gun turret towards an absolute orientation in radians.
+    pub fn aim(&self, orientation: f64) -> Result<(), ClientError> {
+        self.command(Protocol::AIM, &[orientation.to_string()])
+    }
+
+    /// Fires a bullet in the direction of the gun turret.
+    ///
+    /// # Returns
+    ///
+    /// `ClientError::Rejected` with the reason when the bot cannot fire: `NO_AMMO`, `RELOADING`,
+    /// `OVERHEATED`, `COOLDOWN`, `PENALTY` or `DEAD`.
+    ///
+    pub fn fire(&self) -> Result<(), ClientError> {
+        self.command(Protocol::FIRE, &[])
+    }
+
+    /// Queries the name of the current weapon.
+    pub fn weapon(&self) -> Result<String, ClientError> {
+        check_reply(self.request(Protocol::WEAPON, &[])?)
+    }
+
+    /// Switches weapon, starting with an empty magazine being reloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `weapon` - The name of the weapon: `PISTOL`, `SHOTGUN`, `RIFLE`, `MACHINEGUN` or `RICOCHET`.
+    ///
+    pub fn set_weapon(&self, weapon: &str) -> Result<(), ClientError> {
+        self.command(Protocol::WEAPON, &[weapon.to_string()])
+    }
+
+    /// Starts reloading the weapon.
+    pub fn reload(&self) -> Result<(), ClientError> {
+        self.command(Protocol::RELOAD, &[])
+    }
+
+    /// Queries the closest visible bot, `None` if the bot sees nobody.
+    pub fn closest_bot(&self) -> Result<Option<BotInfo>, ClientError> {
+        self.query(Protocol::QUERY_CLOSEST_BOT, &[], BotInfo::parse)
+    }
+
+    /// Queries the closest visible bullet, `None` if the bot sees none.
+    pub fn closest_projectile(&self) -> Result<Option<Projectile>, ClientError> {
+        self.query(Protocol::QUERY_CLOSEST_PROJECTILE, &[], Projectile::parse)
+    }
+
+    /// Queries a bot by name, `None` if it is not visible or does not exist.
+    pub fn bot_by_name(&self, name: &str) -> Result<Option<BotPosition>, ClientError> {
+        self.query(Protocol::QUERY_BY_NAME, &[name.to_string()], BotPosition::parse)
+    }
+
+    /// Queries the list of names. The server does not answer this query yet.
+    ///
+    /// # Returns
+    ///
+    /// The raw reply of the server.
+    ///
+    pub fn name_list(&self) -> Result<String, ClientError> {
+        check_reply(self.request(Protocol::QUERY_NAME_LIST, &[])?)
+    }
+
+    /// Queries the orientations of the body and of the gun turret.
+    pub fn orientation(&self) -> Result<Orientation, ClientError> {
+        let reply = check_reply(self.request(Protocol::QUERY_ORIENTATION, &[])?)?;
+        Orientation::parse(&mut Reply::new(&reply))
+    }
+
+    /// Queries the messages sent by a user. The server does not answer this query yet.
+    ///
+    /// # Returns
+    ///
+    /// The raw reply of the server.
+    ///
+    pub fn messages_from(&self, name: &str) -> Result<String, ClientError> {
+        check_reply(self.request(Protocol::QUERY_MESSAGES_FROM_USER, &[name.to_string()])?)
+    }
+
+    /// Queries the health and weapon state of the bot.
+    pub fn status(&self) -> Result<Status, ClientError> {
+        let reply = check_reply(self.request(Protocol::QUERY_STATUS, &[])?)?;
+        Status::parse(&mut Reply::new(&reply))
+    }
+
+    /// Queries the visible pickups, closest first.
+    pub fn pickups(&self) -> Result<Vec<Pickup>, ClientError> {
+        Ok(self.query_list(Protocol::QUERY_PICKUPS, &[], Pickup::parse)?.unwrap_or_default())
+    }
+
+    /// Plans a path around the obstacles.
+    ///
+    /// # Returns
+    ///
+    /// The (x, y) waypoints leading to the position, ending with the position itself, or `None` if it
+    /// cannot be reached.
+    ///
+    pub fn path(&self, x: f32, y: f32) -> Result<Option<Vec<(f32, f32)>>, ClientError> {
+        self.query_list(Protocol::QUERY_PATH, &[x.to_string(), y.to_string()], |reply| Ok((reply.value()?, reply.value()?)))
+    }
+
+    /// Queries the zone of the current game mode, `None` in the modes without a zone.
+    pub fn zone(&self) -> Result<Option<Zone>, ClientError> {
+        self.query(Protocol::QUERY_ZONE, &[], Zone::parse)
+    }
+
+    /// Queries the obstacles and their hit points.
+    pub fn obstacles(&self) -> Result<Vec<Obstacle>, ClientError> {
+        Ok(self.query_list(Protocol::QUERY_OBSTACLES, &[], Obstacle::parse)?.unwrap_or_default())
+    }
+
+    /// Casts a ray from the bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - The direction of the ray relative to the heading, in radians.
+    /// * `max_range` - The maximum distance of the ray, the server's maximum if `None`.
+    ///
+    /// # Returns
+    ///
+    /// The distance to the first hit and what was hit, or `None` if nothing is within range.
+    ///
+    pub fn ray(&self, angle: f32, max_range: Option<f32>) -> Result<Option<(f32, RayHit)>, ClientError> {
+        let mut arguments = vec![angle.to_string()];
+        arguments.extend(max_range.map(|range| range.to_string()));
+        self.query(Protocol::QUERY_RAY, &arguments, |reply| Ok((reply.value()?, RayHit::parse(reply)?)))
+    }
+
+    /// Casts rays evenly spread around the bot.
+    ///
+    /// # Arguments
+    ///
+    /// * `ray_count` - The number of rays.
+    /// * `max_range` - The maximum distance of the rays, the server's maximum if `None`.
+    ///
+    /// # Returns
+    ///
+    /// The distances of the rays, starting from the heading and turning counterclockwise, the max range for
+    /// the rays hitting nothing.
+    ///
+    pub fn lidar(&self, ray_count: usize, max_range: Option<f32>) -> Result<Vec<f32>, ClientError> {
+        let mut arguments = vec![ray_count.to_string()];
+        arguments.extend(max_range.map(|range| range.to_string()));
+        let reply = check_reply(self.request(Protocol::QUERY_LIDAR, &arguments)?)?;
+        let mut values = Reply::new(&reply);
+        (0..ray_count).map(|_| values.value()).collect()
+    }
+
+    /// Leaves the game, removing the bot from the arena.
+    pub fn quit(self) -> Result<(), ClientError> {
+        if let Some(mut connection) = self.connection.lock().unwrap().take() {
+            connection.send(Protocol::QUIT)?;
+            connection.writer.get_ref().shutdown(Shutdown::Both).ok();
+        }
+        Ok(())
+    }
+}
+
+/// The commands sent again after a reconnection, the queries which change nothing in the game.
+const RETRIED_COMMANDS: [&str; 13] = [
+    Protocol::QUERY_CLOSEST_BOT,
+    Protocol::QUERY_CLOSEST_PROJECTILE,
+    Protocol::QUERY_BY_NAME,
+    Protocol::QUERY_NAME_LIST,
+    Protocol::QUERY_ORIENTATION,
+    Protocol::QUERY_MESSAGES_FROM_USER,
+    Protocol::QUERY_STATUS,
+    Protocol::QUERY_PICKUPS,
+    Protocol::QUERY_PATH,
+    Protocol::QUERY_ZONE,
+    Protocol::QUERY_OBSTACLES,
+    Protocol::QUERY_RAY,
+    Protocol::QUERY_LIDAR,
+];
+
+/// Checks that an argument can be sent without breaking the framing of the protocol.
+///
+/// # Arguments
+///
+/// * `argument` - The argument to send.
+///
+/// # Returns
+///
+/// `ClientError::InvalidArgument` if the argument contains a separator or a line ending.
+///
+fn check_argument(argument: &str) -> Result<(), ClientError> {
+    if [Protocol::COMMAND_SEP, Protocol::ARGUMENT_SEP, "\n", "\r"].iter().any(|separator| argument.contains(separator)) {
+        Err(ClientError::InvalidArgument(argument.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Turns `ERROR` replies into errors.
+///
+/// # Arguments
+///
+/// * `reply` - The reply of the server.
+///
+/// # Returns
+///
+/// The reply, or `ClientError::Rejected` with the reason given by the server.
+///
+fn check_reply(reply: String) -> Result<String, ClientError> {
+    let mut values = reply.splitn(2, Protocol::ARGUMENT_SEP);
+    if values.next() == Some(Protocol::ERROR_REPLY) {
+        Err(ClientError::Rejected(values.next().map(str::to_string)))
+    } else {
+        Ok(reply)
+    }
+}
+
+/// Starts the thread sending `LIVE` whenever the client has been silent for the heartbeat interval.
+///
+/// The thread stops once the client is dropped. A failed heartbeat closes the connection, which the next
+/// command opens again.
+///
+/// # Arguments
+///
+/// * `connection` - The connection of the client.
+/// * `interval` - The delay of silence after which a heartbeat is sent.
+///
+fn spawn_heartbeat(connection: Weak<Mutex<Option<Connection>>>, interval: Duration) {
+    thread::spawn(move || loop {
+        thread::sleep(interval / 4);
+        let Some(connection) = connection.upgrade() else {
+            return;
+        };
+        let mut connection = connection.lock().unwrap();
+        if let Some(open) = connection.as_mut().filter(|open| open.last_sent.elapsed() >= interval) {
+            if open.exchange(Protocol::ALIVE).is_err() {
+                if let Some(broken) = connection.take() {
+                    broken.close();
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Starts a server accepting one connection per script, each script listing the replies to the lines
+    /// received, the connection being dropped once the script runs out.
+    ///
+    /// # Returns
+    ///
+    /// The address of the server, and the thread returning the lines received on each connection.
+    ///
+    fn scripted_server(scripts: Vec<Vec<&'static str>>) -> (String, thread::JoinHandle<Vec<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            scripts.into_iter()
+                .map(|replies| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut writer = stream;
+                    let mut received = Vec::new();
+                    for reply in replies {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        received.push(line.trim_end().to_string());
+                        if !reply.is_empty() {
+                            writeln!(writer, "{}", reply).unwrap();
+                        }
+                    }
+                    received
+                })
+                .collect()
+        });
+        (address, server)
+    }
+
+    #[test]
+    fn only_queries_are_sent_again_after_a_reconnection() {
+        let (address, server) = scripted_server(vec![
+            vec!["OK", ""],
+            vec!["OK", ""],
+            vec!["OK", "1=2"],
+        ]);
+        let mut client = Client::connect_with_heartbeat(&address, "Rusty", Duration::from_secs(60)).unwrap();
+        client.reconnect_delay = Duration::from_millis(10);
+
+        assert!(matches!(client.fire(), Err(ClientError::Io(_))));
+        assert_eq!(client.orientation().unwrap(), Orientation { heading: 1.0, gun: 2.0 });
+        assert_eq!(server.join().unwrap(), vec![
+            vec!["NAME=Rusty", "FIRE"],
+            vec!["NAME=Rusty", "ORIENT"],
+            vec!["NAME=Rusty", "ORIENT"],
+        ]);
+    }
+}
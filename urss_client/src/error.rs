@@ -0,0 +1,42 @@
+use std::fmt;
+use std::io;
+
+/// Represents the ways a request to the server can fail.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The connection failed and could not be established again.
+    Io(io::Error),
+    /// The server replied `ERROR`, with the reason it gave, if any (e.g. `NO_AMMO` for `FIRE`).
+    Rejected(Option<String>),
+    /// The reply of the server could not be understood.
+    Malformed(String),
+    /// An argument contains a separator or a line ending, and would break the framing of the protocol.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(e) => write!(f, "Connection error: {}", e),
+            ClientError::Rejected(Some(reason)) => write!(f, "Rejected by the server: {}", reason),
+            ClientError::Rejected(None) => write!(f, "Rejected by the server"),
+            ClientError::Malformed(reply) => write!(f, "Malformed reply: {:?}", reply),
+            ClientError::InvalidArgument(argument) => write!(f, "Invalid argument: {:?}", argument),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(e: io::Error) -> Self {
+        ClientError::Io(e)
+    }
+}
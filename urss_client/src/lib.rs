@@ -0,0 +1,13 @@
+//! Client library for the bots of the Universal Rust Server Software.
+//!
+//! The server speaks a line protocol: each line holds a command and its arguments separated by `=`,
+//! and the server answers each command with one line. [`Client`] wraps every command in a typed method,
+//! keeps the connection alive with heartbeats and reconnects when it breaks.
+
+mod client;
+mod error;
+mod protocol;
+
+pub use client::Client;
+pub use error::ClientError;
+pub use protocol::{BotInfo, BotPosition, Obstacle, Orientation, Pickup, Projectile, Protocol, RayHit, Status, Zone};
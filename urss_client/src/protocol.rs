@@ -0,0 +1,277 @@
+use crate::error::ClientError;
+
+/// The keywords and separators of the line protocol, shared with the server.
+pub use urss_protocol::Keywords as Protocol;
+
+/// Represents the closest bot, as replied to `CBOT`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BotInfo {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    /// The distance between the bot and the client's bot.
+    pub distance: f32,
+}
+
+/// Represents a bot found by name, as replied to `NBOT`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BotPosition {
+    pub x: f32,
+    pub y: f32,
+    /// The distance between the bot and the client's bot.
+    pub distance: f32,
+}
+
+/// Represents the closest bullet, as replied to `CPROJ`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projectile {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// The distance between the bullet and the client's bot.
+    pub distance: f32,
+}
+
+/// Represents the orientations of the client's bot in radians, as replied to `ORIENT`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orientation {
+    /// The heading of the body.
+    pub heading: f64,
+    /// The orientation of the gun turret.
+    pub gun: f64,
+}
+
+/// Represents the health and weapon state of the client's bot, as replied to `STATUS`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub health: f32,
+    /// The name of the current weapon.
+    pub weapon: String,
+    /// The ammunition left in the magazine.
+    pub ammo: u32,
+    /// The size of the magazine.
+    pub magazine: u32,
+    /// The time left before the reload ends, in seconds.
+    pub reload: f32,
+    /// The heat of the weapon, from 0 to 1.
+    pub heat: f32,
+    /// Whether the weapon cools down after overheating.
+    pub overheated: bool,
+}
+
+/// Represents a pickup lying in the arena, as replied to `PICK`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pickup {
+    /// The kind of the pickup: `HEALTH`, `SPEED`, `SHIELD`, `RAPIDFIRE` or `DAMAGE`.
+    pub kind: String,
+    pub x: f32,
+    pub y: f32,
+    /// The distance between the pickup and the client's bot.
+    pub distance: f32,
+}
+
+/// Represents the zone of the current game mode, as replied to `ZONE`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Zone {
+    /// The King of the Hill control zone, and the name of the bot holding it alone, if any.
+    Control { x: f32, y: f32, radius: f32, owner: Option<String> },
+    /// The Battle Royale safe zone.
+    Safe { x: f32, y: f32, radius: f32 },
+}
+
+/// Represents an obstacle, as replied to `OBST`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obstacle {
+    pub x: f32,
+    pub y: f32,
+    /// The radius of a circle enclosing the obstacle.
+    pub radius: f32,
+    /// The hit points of the obstacle, `None` if it is indestructible, zero once destroyed.
+    pub health: Option<f32>,
+}
+
+/// Represents what a ray hit first.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RayHit {
+    Wall,
+    Obstacle,
+    /// A bot, with its name.
+    Bot(String),
+    Bullet,
+}
+
+/// Represents a reply split into its arguments, read one after the other.
+pub(crate) struct Reply<'a> {
+    line: &'a str,
+    values: std::str::Split<'a, &'static str>,
+}
+
+impl<'a> Reply<'a> {
+    /// Splits a reply into its arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The reply, without its line ending.
+    ///
+    pub(crate) fn new(line: &'a str) -> Self {
+        Self { line, values: line.split(Protocol::ARGUMENT_SEP) }
+    }
+
+    /// Reads the next argument as text.
+    ///
+    /// # Returns
+    ///
+    /// The argument, or `ClientError::Malformed` if the reply has no more arguments.
+    ///
+    pub(crate) fn text(&mut self) -> Result<&'a str, ClientError> {
+        self.values.next().ok_or_else(|| ClientError::Malformed(self.line.to_string()))
+    }
+
+    /// Reads the next argument as a value.
+    ///
+    /// # Returns
+    ///
+    /// The parsed argument, or `ClientError::Malformed` if it is missing or invalid.
+    ///
+    pub(crate) fn value<T: std::str::FromStr>(&mut self) -> Result<T, ClientError> {
+        self.text()?.parse().map_err(|_| ClientError::Malformed(self.line.to_string()))
+    }
+}
+
+impl BotInfo {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self { name: reply.text()?.to_string(), x: reply.value()?, y: reply.value()?, distance: reply.value()? })
+    }
+}
+
+impl BotPosition {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self { x: reply.value()?, y: reply.value()?, distance: reply.value()? })
+    }
+}
+
+impl Projectile {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self { x: reply.value()?, y: reply.value()?, vx: reply.value()?, vy: reply.value()?, distance: reply.value()? })
+    }
+}
+
+impl Orientation {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self { heading: reply.value()?, gun: reply.value()? })
+    }
+}
+
+impl Status {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self {
+            health: reply.value()?,
+            weapon: reply.text()?.to_string(),
+            ammo: reply.value()?,
+            magazine: reply.value()?,
+            reload: reply.value()?,
+            heat: reply.value()?,
+            overheated: reply.value::<u8>()? != 0,
+        })
+    }
+}
+
+impl Pickup {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        Ok(Self { kind: reply.text()?.to_string(), x: reply.value()?, y: reply.value()?, distance: reply.value()? })
+    }
+}
+
+impl Zone {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        match reply.text()? {
+            Protocol::CONTROL_ZONE_REPLY => {
+                let (x, y, radius) = (reply.value()?, reply.value()?, reply.value()?);
+                let owner = Some(reply.text()?).filter(|owner| *owner != Protocol::EMPTY_REPLY).map(str::to_string);
+                Ok(Zone::Control { x, y, radius, owner })
+            }
+            Protocol::SAFE_ZONE_REPLY => Ok(Zone::Safe { x: reply.value()?, y: reply.value()?, radius: reply.value()? }),
+            _ => Err(ClientError::Malformed(reply.line.to_string())),
+        }
+    }
+}
+
+impl Obstacle {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        let (x, y, radius, health) = (reply.value()?, reply.value()?, reply.value()?, reply.value::<f32>()?);
+        Ok(Self { x, y, radius, health: (health >= 0.0).then_some(health) })
+    }
+}
+
+impl RayHit {
+    /// Parses the next arguments of a reply.
+    pub(crate) fn parse(reply: &mut Reply) -> Result<Self, ClientError> {
+        match reply.text()? {
+            Protocol::RAY_HIT_WALL => Ok(RayHit::Wall),
+            Protocol::RAY_HIT_OBSTACLE => Ok(RayHit::Obstacle),
+            Protocol::RAY_HIT_BOT => Ok(RayHit::Bot(reply.text()?.to_string())),
+            Protocol::RAY_HIT_BULLET => Ok(RayHit::Bullet),
+            _ => Err(ClientError::Malformed(reply.line.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_arguments_are_read_in_order() {
+        let mut reply = Reply::new("Rusty=12.5=40=3");
+        assert_eq!(BotInfo::parse(&mut reply).unwrap(), BotInfo { name: "Rusty".to_string(), x: 12.5, y: 40.0, distance: 3.0 });
+        assert!(reply.text().is_err());
+    }
+
+    #[test]
+    fn missing_or_invalid_argument_is_malformed() {
+        assert!(matches!(Orientation::parse(&mut Reply::new("1.5")), Err(ClientError::Malformed(line)) if line == "1.5"));
+        assert!(matches!(BotPosition::parse(&mut Reply::new("1=two=3")), Err(ClientError::Malformed(_))));
+    }
+
+    #[test]
+    fn status_reads_the_overheated_flag() {
+        let status = Status::parse(&mut Reply::new("80=PISTOL=5=12=0=0.75=1")).unwrap();
+        assert_eq!(status.weapon, "PISTOL");
+        assert_eq!((status.ammo, status.magazine), (5, 12));
+        assert!(status.overheated);
+    }
+
+    #[test]
+    fn zone_owner_is_empty_when_nobody_holds_it() {
+        let zone = Zone::parse(&mut Reply::new("CONTROL=500=400=100=EMPTY")).unwrap();
+        assert_eq!(zone, Zone::Control { x: 500.0, y: 400.0, radius: 100.0, owner: None });
+        let zone = Zone::parse(&mut Reply::new("CONTROL=500=400=100=Rusty")).unwrap();
+        assert_eq!(zone, Zone::Control { x: 500.0, y: 400.0, radius: 100.0, owner: Some("Rusty".to_string()) });
+        assert_eq!(Zone::parse(&mut Reply::new("SAFE=1=2=3")).unwrap(), Zone::Safe { x: 1.0, y: 2.0, radius: 3.0 });
+        assert!(Zone::parse(&mut Reply::new("CIRCLE=1=2=3")).is_err());
+    }
+
+    #[test]
+    fn indestructible_obstacle_has_no_health() {
+        let obstacle = Obstacle::parse(&mut Reply::new("10=20=5=-1")).unwrap();
+        assert_eq!(obstacle.health, None);
+        let obstacle = Obstacle::parse(&mut Reply::new("10=20=5=0")).unwrap();
+        assert_eq!(obstacle.health, Some(0.0));
+    }
+
+    #[test]
+    fn ray_hit_bot_carries_its_name() {
+        assert_eq!(RayHit::parse(&mut Reply::new("BOT=Rusty")).unwrap(), RayHit::Bot("Rusty".to_string()));
+        assert_eq!(RayHit::parse(&mut Reply::new("WALL")).unwrap(), RayHit::Wall);
+        assert!(RayHit::parse(&mut Reply::new("TREE")).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! Keywords of the line protocol spoken between the Universal Rust Server Software and its bots.
+//!
+//! Both the server and the client library read the commands, reply tags and separators from here,
+//! so they cannot drift apart.
+
+/// The keywords and separators of the line protocol.
+pub struct Keywords;
+
+impl Keywords {
+    /// Command to set the user's name. Argument: string.
+    pub const SET_NAME: &'static str = "NAME";
+    /// Command to set the user's color. Arguments: 3 integers for RGB.
+    pub const SET_COLOR: &'static str = "COL";
+    /// Command to quit. No arguments.
+    pub const QUIT: &'static str = "EXIT";
+    /// Command to indicate the user is alive. No arguments.
+    pub const ALIVE: &'static str = "LIVE";
+    /// Command to send a message. Argument: string (a short message).
+    pub const MESSAGE: &'static str = "MSG";
+    /// Command to set the speed the bot accelerates to along its heading. Argument: speed in units per second, negative to move backwards.
+    pub const MOVE: &'static str = "MOVE";
+    /// Command to turn the body of the bot towards a heading. Argument: absolute angle in radians.
+    pub const TURN: &'static str = "TURN";
+    /// Command to turn the gun turret towards an orientation. Argument: absolute angle in radians.
+    pub const AIM: &'static str = "AIM";
+    /// Command to fire a bullet in the direction of the gun turret. No arguments.
+    pub const FIRE: &'static str = "FIRE";
+    /// Command to switch weapon. Argument: the name of the weapon, or none to query the current weapon.
+    pub const WEAPON: &'static str = "WEAPON";
+    /// Command to reload the weapon. No arguments.
+    pub const RELOAD: &'static str = "RELOAD";
+    /// Command to watch the game without a bot, receiving the world state periodically. Argument: optional interval in milliseconds.
+    pub const SPECTATE: &'static str = "SPECTATE";
+    /// Command to switch the protocol from the next line on. Argument: `TEXT` for `#`/`=` lines, `JSON` for JSON lines.
+    pub const PROTOCOL: &'static str = "PROTOCOL";
+    /// Name of the default protocol, with commands separated by `#` and arguments by `=`.
+    pub const PROTOCOL_TEXT: &'static str = "TEXT";
+    /// Name of the protocol exchanging one typed JSON object per line.
+    pub const PROTOCOL_JSON: &'static str = "JSON";
+
+    /// Command to query the closest bot. No arguments.
+    pub const QUERY_CLOSEST_BOT: &'static str = "CBOT";
+    /// Command to query the closest projectile. No arguments.
+    pub const QUERY_CLOSEST_PROJECTILE: &'static str = "CPROJ";
+    /// Command to query a bot by name. Argument: string (name of the player).
+    pub const QUERY_BY_NAME: &'static str = "NBOT";
+    /// Command to query the list of names. No arguments.
+    pub const QUERY_NAME_LIST: &'static str = "NLIST";
+    /// Command to query the orientation. No arguments.
+    pub const QUERY_ORIENTATION: &'static str = "ORIENT";
+    /// Command to query messages from a user. Argument: string (name of the player).
+    pub const QUERY_MESSAGES_FROM_USER: &'static str = "USRMSG";
+    /// Command to query the health and weapon state of the bot. No arguments.
+    pub const QUERY_STATUS: &'static str = "STATUS";
+    /// Command to query the pickups lying in the arena. No arguments.
+    pub const QUERY_PICKUPS: &'static str = "PICK";
+    /// Command to plan a path around the obstacles. Arguments: the x and y position to reach.
+    pub const QUERY_PATH: &'static str = "PATH";
+    /// Command to query the zone of the current game mode. No arguments.
+    pub const QUERY_ZONE: &'static str = "ZONE";
+    /// Command to query the obstacles and their hit points. No arguments.
+    pub const QUERY_OBSTACLES: &'static str = "OBST";
+    /// Command to cast a ray. Arguments: angle relative to the heading in radians, optional max range.
+    pub const QUERY_RAY: &'static str = "RAY";
+    /// Command to cast rays evenly spread around the bot. Arguments: number of rays, optional max range.
+    pub const QUERY_LIDAR: &'static str = "LIDAR";
+
+    /// Reply tag for a King of the Hill control zone.
+    pub const CONTROL_ZONE_REPLY: &'static str = "CONTROL";
+    /// Reply tag for a Battle Royale safe zone.
+    pub const SAFE_ZONE_REPLY: &'static str = "SAFE";
+    /// Section tag of a streamed world state, followed by the game mode and the arena size.
+    pub const STATE_SECTION: &'static str = "STATE";
+    /// Section tag of the entities in a streamed world state.
+    pub const ENTITIES_SECTION: &'static str = "ENTITIES";
+    /// Section tag of the bullets in a streamed world state.
+    pub const BULLETS_SECTION: &'static str = "BULLETS";
+    /// Section tag of the obstacles in a streamed world state.
+    pub const OBSTACLES_SECTION: &'static str = "OBSTACLES";
+    /// Section tag of the pickups in a streamed world state.
+    pub const PICKUPS_SECTION: &'static str = "PICKUPS";
+    /// Section tag of the zone in a streamed world state.
+    pub const ZONE_SECTION: &'static str = "ZONE";
+    /// Reply tag for a ray hitting the arena boundaries.
+    pub const RAY_HIT_WALL: &'static str = "WALL";
+    /// Reply tag for a ray hitting an obstacle.
+    pub const RAY_HIT_OBSTACLE: &'static str = "OBSTACLE";
+    /// Reply tag for a ray hitting a bot, followed by its name.
+    pub const RAY_HIT_BOT: &'static str = "BOT";
+    /// Reply tag for a ray hitting a bullet.
+    pub const RAY_HIT_BULLET: &'static str = "BULLET";
+
+    /// Reply to a command that succeeded. No arguments.
+    pub const OK_REPLY: &'static str = "OK";
+    /// Reply to an unknown or invalid command, optionally followed by a reason.
+    pub const ERROR_REPLY: &'static str = "ERROR";
+    /// Error reason for a JSON line that is not a valid request.
+    pub const INVALID_REQUEST_REPLY: &'static str = "INVALID_REQUEST";
+
+    /// Command for an empty reply. No arguments.
+    pub const EMPTY_REPLY: &'static str = "EMPTY";
+
+    /// Separator for commands.
+    pub const COMMAND_SEP: &'static str = "#";
+    /// Separator for arguments.
+    pub const ARGUMENT_SEP: &'static str = "=";
+}